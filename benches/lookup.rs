@@ -0,0 +1,53 @@
+//! Run with `cargo bench` and again with `cargo bench --no-default-features` to compare the `memchr` scan against the fallback.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use line_col::LineColLookup;
+
+/// Builds roughly `len` bytes of code-like text with lines of varying length.
+fn sample_source(len: usize) -> String {
+    let mut src = String::with_capacity(len + 64);
+    let mut i = 0usize;
+    while src.len() < len {
+        src.push_str(&"    let value = compute(argument);"[..8 + i % 26]);
+        src.push('\n');
+        i += 1;
+    }
+    src
+}
+
+fn build_table(c: &mut Criterion) {
+    let src = sample_source(8 << 20);
+    let mut group = c.benchmark_group("build_table");
+    group.throughput(Throughput::Bytes(src.len() as u64));
+    group.bench_function("8MiB", |b| b.iter(|| LineColLookup::new_eager(black_box(src.as_str()))));
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let src = sample_source(1 << 20);
+    let lookup = LineColLookup::new_eager(src.as_str());
+    c.bench_function("get", |b| {
+        let mut index = 0;
+        b.iter(|| {
+            index = (index + 7919) % src.len();
+            lookup.get(black_box(index))
+        })
+    });
+}
+
+/// Diagnostics-like lookups: bursts of nearby indices scattered across the source.
+fn clustered_lookup(c: &mut Criterion) {
+    let src = sample_source(1 << 20);
+    let lookup = LineColLookup::new_eager(src.as_str());
+    let mut indices = Vec::new();
+    for cluster in 0..256 {
+        let base = (cluster * 104_729) % (src.len() - 4096);
+        indices.extend((0..64).map(|i| base + (i * 37) % 4096));
+    }
+    c.bench_function("get_clustered", |b| {
+        b.iter(|| indices.iter().map(|&index| lookup.get(black_box(index)).0).sum::<usize>())
+    });
+}
+
+criterion_group!(benches, build_table, lookup, clustered_lookup);
+criterion_main!(benches);
@@ -0,0 +1,87 @@
+use crate::{heads::push_line_starts, line_lookup::line_range_in, LineLookup, Position, StrLookup};
+use alloc::vec::Vec;
+use core::{alloc::Allocator, ops::Range};
+
+/// An eagerly built line/column lookup table whose line head table lives in a caller-provided allocator.
+///
+/// Compilers that arena-allocate their per-file data can keep the index in the same arena and free it wholesale.
+/// Unlike [`LineColLookup`](crate::LineColLookup), the table is built up front and never changes, so it needs no locks.
+///
+/// # Example
+/// ```rust
+/// #![feature(allocator_api)]
+/// use line_col::*;
+/// use std::alloc::Global;
+/// let lookup = LineColLookupIn::new_in("One\nTwo", Global);
+/// assert_eq!(lookup.get(5), (2, 2));
+/// ```
+pub struct LineColLookupIn<'source, A: Allocator> {
+    src: &'source str,
+    starts: Vec<usize, A>,
+}
+
+impl<'source, A: Allocator> LineColLookupIn<'source, A> {
+    /// Creates a lookup table over `src`, allocating its line head table with `alloc`.
+    ///
+    /// This scans the whole source, which is an O(n) operation.
+    pub fn new_in(src: &'source str, alloc: A) -> Self {
+        let mut starts = Vec::new_in(alloc);
+        starts.push(0);
+        push_line_starts(&mut starts, src.as_bytes(), 0);
+        Self { src, starts }
+    }
+
+    /// Gets a reference to the source string.
+    pub fn src(&self) -> &'source str {
+        self.src
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`LineColLookup::get`](crate::LineColLookup::get) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn get(&self, index: usize) -> Position {
+        if index > self.src.len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let line = self.starts.partition_point(|&start| start <= index) - 1;
+        (line + 1, index - self.starts[line] + 1)
+    }
+}
+
+impl<A: Allocator> LineLookup for LineColLookupIn<'_, A> {
+    fn get(&self, index: usize) -> Position {
+        LineColLookupIn::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range_in(&self.starts, self.src.len(), line.checked_sub(1)?)
+    }
+}
+
+impl<A: Allocator> StrLookup for LineColLookupIn<'_, A> {
+    fn src(&self) -> &str {
+        self.src
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::alloc::Global;
+
+    #[test]
+    fn allocated_in_matches_default() {
+        let text = "a\nab\n\nabc\n";
+        let lookup = LineColLookupIn::new_in(text, Global);
+        let default = LineColLookup::new(text);
+        for i in 0..=text.len() {
+            assert_eq!(lookup.get(i), default.get(i));
+        }
+    }
+}
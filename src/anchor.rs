@@ -0,0 +1,110 @@
+use crate::{Bias, Edit};
+use alloc::vec::Vec;
+
+/// A handle to a position registered with an [`AnchorSet`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Anchor(usize);
+
+/// A set of byte positions that follow the text they point at as edits are applied, like the marks of an editor.
+///
+/// Each anchor has a [`Bias`] deciding which side of text inserted at its position it ends up on.
+/// Diagnostics or bookmarks pinned to anchors stay attached to the same code while the source changes around them.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut lookup = OwnedLineColLookup::new("let x = 1;".to_string());
+/// let mut anchors = AnchorSet::new();
+/// let x = anchors.insert(4, Bias::Before);
+/// anchors.apply(&lookup.apply_edit(0..0, "\n\n"));
+/// assert_eq!(anchors.get(x), Some(6));
+/// assert_eq!(lookup.get(anchors.get(x).unwrap()), (3, 5));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct AnchorSet {
+    /// The position and bias of each anchor, or `None` for anchors that have been removed.
+    anchors: Vec<Option<(usize, Bias)>>,
+    /// Slots of removed anchors, to be reused by later insertions.
+    free: Vec<usize>,
+}
+
+impl AnchorSet {
+    /// Creates an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers an anchor at the byte `index` of the current source.
+    pub fn insert(&mut self, index: usize, bias: Bias) -> Anchor {
+        match self.free.pop() {
+            Some(slot) => {
+                self.anchors[slot] = Some((index, bias));
+                Anchor(slot)
+            }
+            None => {
+                self.anchors.push(Some((index, bias)));
+                Anchor(self.anchors.len() - 1)
+            }
+        }
+    }
+
+    /// Returns the current byte index of `anchor`, or `None` if it has been removed.
+    pub fn get(&self, anchor: Anchor) -> Option<usize> {
+        self.anchors.get(anchor.0).copied().flatten().map(|(index, _)| index)
+    }
+
+    /// Removes `anchor`, returning its last byte index. Its handle may be reused by a later insertion.
+    pub fn remove(&mut self, anchor: Anchor) -> Option<usize> {
+        let (index, _) = self.anchors.get_mut(anchor.0)?.take()?;
+        self.free.push(anchor.0);
+        Some(index)
+    }
+
+    /// Moves every anchor to match `edit`, which must have been applied to the source the anchors point into.
+    pub fn apply(&mut self, edit: &Edit) {
+        for (index, bias) in self.anchors.iter_mut().flatten() {
+            *index = edit.map_index(*index, *bias);
+        }
+    }
+
+    /// Returns the number of anchors in the set.
+    pub fn len(&self) -> usize {
+        self.anchors.len() - self.free.len()
+    }
+
+    /// Returns `true` if the set holds no anchors.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the anchors in the set and their current byte indices.
+    pub fn iter(&self) -> impl Iterator<Item = (Anchor, usize)> + '_ {
+        self.anchors.iter().enumerate().filter_map(|(slot, anchor)| anchor.map(|(index, _)| (Anchor(slot), index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn anchors_follow_edits() {
+        let mut lookup = OwnedLineColLookup::new("ab\ncd\nef".to_string());
+        let mut anchors = AnchorSet::new();
+        let before = anchors.insert(3, Bias::Before);
+        let after = anchors.insert(3, Bias::After);
+        let inside = anchors.insert(4, Bias::After);
+        let later = anchors.insert(7, Bias::Before);
+        anchors.apply(&lookup.apply_edit(3..3, "xyz"));
+        anchors.apply(&lookup.apply_edit(6..8, "w"));
+        assert_eq!(lookup.src(), "ab\nxyzw\nef");
+        assert_eq!(anchors.get(before), Some(3));
+        assert_eq!(anchors.get(after), Some(6));
+        assert_eq!(anchors.get(inside), Some(7));
+        assert_eq!(anchors.get(later), Some(9));
+        assert_eq!(anchors.remove(inside), Some(7));
+        assert_eq!(anchors.get(inside), None);
+        assert_eq!(anchors.insert(0, Bias::Before), inside);
+        assert_eq!(anchors.len(), 4);
+    }
+}
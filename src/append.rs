@@ -0,0 +1,64 @@
+use crate::OwnedLineColLookup;
+
+impl OwnedLineColLookup {
+    /// Appends `appended` to the source, keeping the line head table built so far.
+    ///
+    /// Only the appended text is scanned: immediately if the table already covered the whole source, otherwise when next needed.
+    /// This suits sources that only ever grow, such as a log being followed.
+    ///
+    /// If clones or views share this lookup's caches, it first takes its own copy of them, so they are unaffected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let mut lookup = OwnedLineColLookup::new_eager("One\nTw".to_string());
+    /// lookup.extend_with("o\nThree");
+    /// assert_eq!(lookup.get(6), (2, 3));
+    /// assert_eq!(lookup.get(9), (3, 2));
+    /// ```
+    pub fn extend_with(&mut self, appended: &str) {
+        let old_len = self.src.len();
+        let heads = self.heads_mut();
+        let complete = heads.covers(old_len);
+        // The line containing the end of the scanned text is the first one whose contents may change
+        #[cfg(feature = "grapheme-lite")]
+        let changed_line = heads.starts.len() - 1;
+        #[cfg(feature = "grapheme-lite")]
+        self.clusters.invalidate_from(changed_line);
+        self.src.push_str(appended);
+        if complete {
+            self.write_heads().scan_through(&self.src, self.src.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn appending_matches_rebuilding() {
+        let parts = ["a\nä", "b", "\n\nab", "c\n", "", "\ndef"];
+        for eager in [false, true] {
+            let mut lookup = OwnedLineColLookup::new(String::new());
+            let mut text = String::new();
+            for part in parts {
+                if eager {
+                    lookup.precompute();
+                }
+                let clone = lookup.clone();
+                lookup.extend_with(part);
+                text.push_str(part);
+                assert_eq!(clone.line_count(), LineColLookup::new(clone.src()).line_count());
+                let rebuilt = LineColLookup::new(text.as_str());
+                assert_eq!(lookup.src(), text);
+                for i in 0..=text.len() {
+                    assert_eq!(lookup.get(i), rebuilt.get(i));
+                    assert_eq!(lookup.get_by_char(i), rebuilt.get_by_char(i));
+                    #[cfg(feature = "grapheme-lite")]
+                    assert_eq!(lookup.get_by_cluster(i), rebuilt.get_by_cluster(i));
+                }
+            }
+        }
+    }
+}
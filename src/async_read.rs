@@ -0,0 +1,42 @@
+use crate::{heads::push_line_starts, OwnedLineColLookup};
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+impl OwnedLineColLookup {
+    /// Asynchronously reads all of `reader` into a new owned lookup table.
+    ///
+    /// Line heads are recorded as each chunk arrives, so the table is complete as soon as the input ends.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised by `reader`, or an error of kind [`io::ErrorKind::InvalidData`] if the input is not valid UTF-8.
+    pub async fn from_async_reader<R: AsyncRead + Unpin>(mut reader: R) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        let mut line_heads = vec![0];
+        loop {
+            let chunk_start = bytes.len();
+            if reader.read_buf(&mut bytes).await? == 0 {
+                break;
+            }
+            push_line_starts(&mut line_heads, &bytes[chunk_start..], chunk_start);
+        }
+        let src = String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(Self::with_heads(src, line_heads))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[tokio::test]
+    async fn from_async_reader_matches_new() {
+        let text = "a\nab\n\nabc\n";
+        let read = LineColLookup::from_async_reader(text.as_bytes()).await.unwrap();
+        let scanned = LineColLookup::new(text);
+        assert_eq!(read.src(), text);
+        for i in 0..=text.len() {
+            assert_eq!(read.get(i), scanned.get(i));
+        }
+    }
+}
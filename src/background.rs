@@ -0,0 +1,115 @@
+use crate::{heads::LineHeads, LineColLookup};
+use std::{sync::{RwLockReadGuard, TryLockError}, task::Poll, thread::JoinHandle};
+
+impl<S: AsRef<str> + Clone + Send + Sync + 'static> LineColLookup<'static, S> {
+    /// Returns a task that builds the whole line head table when run, for handing to a custom executor or thread pool.
+    ///
+    /// The task shares this lookup's table, so once it finishes, every lookup on this table is served from it.
+    pub fn indexing_task(&self) -> impl FnOnce() + Send + 'static {
+        let lookup = self.clone();
+        move || lookup.precompute()
+    }
+
+    /// Spawns a thread that builds the whole line head table in the background.
+    ///
+    /// While it runs, [`LineColLookup::get`] blocks until the table is ready,
+    /// [`LineColLookup::try_get`] returns [`Poll::Pending`], and [`LineColLookup::get_or_scan`] falls back to a linear scan.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// use std::sync::Arc;
+    /// let lookup = SharedLineColLookup::from(Arc::<str>::from("One\nTwo"));
+    /// lookup.spawn_indexing().join().unwrap();
+    /// assert!(lookup.is_indexed());
+    /// assert_eq!(lookup.try_get(5), std::task::Poll::Ready((2, 2)));
+    /// ```
+    pub fn spawn_indexing(&self) -> JoinHandle<()> {
+        std::thread::spawn(self.indexing_task())
+    }
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Gets the line head table without blocking, or `None` if another thread is extending it.
+    fn try_heads(&self) -> Option<RwLockReadGuard<'_, LineHeads>> {
+        match self.line_heads.try_read() {
+            Ok(heads) => Some(heads),
+            Err(TryLockError::Poisoned(err)) => Some(err.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// Returns `true` if the line head table covers the whole source.
+    pub fn is_indexed(&self) -> bool {
+        self.try_heads().is_some_and(|heads| heads.covers(self.src().len()))
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index without waiting on,
+    /// or doing, any indexing work.
+    ///
+    /// Returns [`Poll::Pending`] if the line head table is being built by another thread
+    /// or does not yet reach `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn try_get(&self, index: usize) -> Poll<(usize, usize)> {
+        if index > self.src().len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+
+        match self.try_heads() {
+            // Answered under this guard, as going through `get` would lock the table again and could block on a writer
+            Some(heads) if heads.covers(index) => {
+                let (line, line_start, _) = self.line_info_in(&heads, index);
+                Poll::Ready((line + 1, index - line_start + 1))
+            }
+            _ => Poll::Pending,
+        }
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index,
+    /// scanning the source directly if the line head table isn't ready.
+    ///
+    /// This never blocks on a background indexing task, but the fallback is O(n).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn get_or_scan(&self, index: usize) -> (usize, usize) {
+        if let Poll::Ready(pos) = self.try_get(index) {
+            return pos;
+        }
+        let before = &self.src().as_bytes()[..index];
+        let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+        let line_start = before.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+        (line, index - line_start + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::task::Poll;
+
+    #[test]
+    fn pending_until_indexed() {
+        let text = "a\nab\nabc";
+        let lookup = LineColLookup::new(text);
+        assert_eq!(lookup.try_get(7), Poll::Pending);
+        assert!(!lookup.is_indexed());
+        for i in 0..=text.len() {
+            assert_eq!(lookup.get_or_scan(i), LineColLookup::new(text).get(i));
+        }
+        lookup.precompute();
+        assert!(lookup.is_indexed());
+        assert_eq!(lookup.try_get(7), Poll::Ready((3, 3)));
+    }
+
+    #[test]
+    fn indexing_task_shares_table() {
+        let lookup = OwnedLineColLookup::new("a\nab".to_string());
+        lookup.indexing_task()();
+        assert!(lookup.is_indexed());
+    }
+}
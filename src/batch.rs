@@ -0,0 +1,184 @@
+use crate::{table::LineTable, LineColLookup, Position};
+use alloc::{vec, vec::Vec};
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Looks up the 1-based line and column numbers of each of the specified byte indices, as [`LineColLookup::get`] would.
+    ///
+    /// `sorted_indices` should be in ascending order. The line head table is then walked once alongside them,
+    /// reading O(n + m) line starts for `n` lines and `m` indices. Each read is constant-time in the default format,
+    /// but see [`LineTableFormat`](crate::LineTableFormat) for the cost in the others.
+    /// Out-of-order indices still produce the right positions, but each one costs a binary search.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("One\nTwo");
+    /// assert_eq!(lookup.get_many(&[0, 2, 4, 7]), vec![(1, 1), (1, 3), (2, 1), (2, 4)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is greater than the length of the input `&str`.
+    pub fn get_many(&self, sorted_indices: &[usize]) -> Vec<Position> {
+        match self.max_index(sorted_indices) {
+            Some(max) => get_sorted(&self.heads_through(max).starts, sorted_indices),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the greatest of `indices`, or `None` if there are none.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is greater than the length of the input `&str`.
+    pub(crate) fn max_index(&self, indices: &[usize]) -> Option<usize> {
+        let max = indices.iter().copied().max()?;
+        if max > self.src().len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        Some(max)
+    }
+
+    /// Looks up the 1-based line and column numbers of each of the specified byte indices, in any order.
+    ///
+    /// The indices are sorted internally, converted with [`LineColLookup::get_many`], and the positions are
+    /// returned in the same order as `indices`. Sorting adds O(m log m) time for `m` indices to the cost of the walk.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("One\nTwo");
+    /// assert_eq!(lookup.get_many_unsorted(&[7, 0, 4]), vec![(2, 4), (1, 1), (2, 1)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is greater than the length of the input `&str`.
+    pub fn get_many_unsorted(&self, indices: &[usize]) -> Vec<Position> {
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        order.sort_unstable_by_key(|&i| indices[i]);
+        let sorted: Vec<usize> = order.iter().map(|&i| indices[i]).collect();
+        let mut positions = vec![(0, 0); indices.len()];
+        for (i, pos) in order.into_iter().zip(self.get_many(&sorted)) {
+            positions[i] = pos;
+        }
+        positions
+    }
+}
+
+/// Converts byte indices, ideally in ascending order, by walking `starts` alongside them.
+pub(crate) fn get_sorted(starts: &LineTable, sorted_indices: &[usize]) -> Vec<Position> {
+    // Start from the first index's line so that a chunk in the middle of a batch doesn't walk the whole table
+    let mut line = sorted_indices.first().map_or(0, |&index| starts.line_of(index));
+    sorted_indices
+        .iter()
+        .map(|&index| {
+            line = seek_line(starts, line, index);
+            (line + 1, index - starts.get(line) + 1)
+        })
+        .collect()
+}
+
+/// Finds the 0-based line containing `index`, walking forward from `line` if `index` is at or after its start,
+/// and falling back to a binary search otherwise.
+pub(crate) fn seek_line(starts: &LineTable, line: usize, index: usize) -> usize {
+    // The table may have been cleared since `line` was found
+    let mut line = line.min(starts.len() - 1);
+    if index < starts.get(line) {
+        return starts.line_of(index);
+    }
+    while line + 1 < starts.len() && starts.get(line + 1) <= index {
+        line += 1;
+    }
+    line
+}
+
+/// An iterator converting byte indices into positions, returned by [`LineColLookup::positions`].
+pub struct Positions<'lookup, 'source, S, I> {
+    lookup: &'lookup LineColLookup<'source, S>,
+    indices: I,
+    line: usize,
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Lazily converts a stream of byte indices into positions, as [`LineColLookup::get`] would.
+    ///
+    /// The iterator remembers the line of the previous index and walks forward from it,
+    /// so ascending streams, such as a lexer's token offsets, read O(n + m) line starts overall for `n` lines and `m` indices.
+    /// An index before the previous one falls back to a binary search.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("let x\n= 1;");
+    /// let tokens = [0, 4, 6, 8, 9];
+    /// let positions: Vec<_> = lookup.positions(tokens).collect();
+    /// assert_eq!(positions, vec![(1, 1), (1, 5), (2, 1), (2, 3), (2, 4)]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// The iterator panics if any index is greater than the length of the input `&str`.
+    pub fn positions<I: IntoIterator<Item = usize>>(&self, indices: I) -> Positions<'_, 'source, S, I::IntoIter> {
+        Positions {
+            lookup: self,
+            indices: indices.into_iter(),
+            line: 0,
+        }
+    }
+}
+
+impl<S: AsRef<str>, I: Iterator<Item = usize>> Iterator for Positions<'_, '_, S, I> {
+    type Item = Position;
+
+    fn next(&mut self) -> Option<Position> {
+        let index = self.indices.next()?;
+        if index > self.lookup.src().len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let heads = self.lookup.heads_through(index);
+        self.line = seek_line(&heads.starts, self.line, index);
+        Some((self.line + 1, index - heads.starts.get(self.line) + 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn get_many_matches_get() {
+        let text = "a\nab\n\nabc\n";
+        let lookup = LineColLookup::new(text);
+        let sorted: Vec<usize> = (0..=text.len()).collect();
+        let expected: Vec<_> = sorted.iter().map(|&i| lookup.get(i)).collect();
+        assert_eq!(lookup.get_many(&sorted), expected);
+
+        let unsorted = [9, 0, 5, 4, 10, 1];
+        let expected: Vec<_> = unsorted.iter().map(|&i| lookup.get(i)).collect();
+        assert_eq!(lookup.get_many(&unsorted), expected);
+        assert!(lookup.get_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn positions_matches_get() {
+        let text = "a\nab\n\nabc\n";
+        let lookup = LineColLookup::new(text);
+        let indices = [0, 1, 2, 5, 9, 3, 3, 10, 0];
+        let expected: Vec<_> = indices.iter().map(|&i| lookup.get(i)).collect();
+        assert_eq!(lookup.positions(indices).collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn get_many_unsorted_preserves_order() {
+        let text = "a\nab\n\nabc\n";
+        let lookup = LineColLookup::new(text);
+        let indices = [9, 0, 5, 4, 10, 1, 5];
+        let expected: Vec<_> = indices.iter().map(|&i| lookup.get(i)).collect();
+        assert_eq!(lookup.get_many_unsorted(&indices), expected);
+    }
+}
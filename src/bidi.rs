@@ -0,0 +1,83 @@
+use crate::{LineColLookup, Position};
+use unicode_bidi::BidiInfo;
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Looks up the 1-based line and visual column numbers of the specified byte index.
+    /// The column number counts the `char`s displayed to the left of the one at the index, plus one,
+    /// once the line is reordered for display by the [Unicode Bidirectional Algorithm](https://www.unicode.org/reports/tr9/).
+    ///
+    /// In a line of only left-to-right text, this is the column [`LineColLookup::get_by_char`] returns.
+    /// An index at the end of a line gives the column after all of it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// // Displayed as "ab גבא"
+    /// let lookup = LineColLookup::new("ab \u{5d0}\u{5d1}\u{5d2}");
+    /// assert_eq!(lookup.get_by_char(3), (1, 4));
+    /// assert_eq!(lookup.get_visual(3), (1, 6));
+    /// assert_eq!(lookup.get_visual(7), (1, 4));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn get_visual(&self, index: usize) -> Position {
+        let (line, col) = self.get_by_char(index);
+        (line, self.visual_col(line, col).expect("get_by_char returns an existing line and column"))
+    }
+
+    /// Converts a 1-based `char` column on the 1-based `line` to the column its character is displayed at,
+    /// after reordering the line as [`LineColLookup::get_visual`] does.
+    ///
+    /// A column past the end of the line means the end of the line, excluding its terminating `'\n'`, which maps to the column after all of it.
+    /// Returns `None` if there is no such line, or `col` is 0.
+    ///
+    /// # Notes
+    /// Each lookup reorders the whole line, with the paragraph direction taken from its first strongly directional character.
+    pub fn visual_col(&self, line: usize, col: usize) -> Option<usize> {
+        let range = self.line_range(line)?;
+        if col == 0 {
+            return None;
+        }
+        let text = &self.src()[range];
+        let target = match text.char_indices().nth(col - 1) {
+            Some((target, _)) => target,
+            None => return Some(text.chars().count() + 1),
+        };
+        let bidi = BidiInfo::new(text, None);
+        let mut left = 0;
+        // Paragraph separators other than '\n' split a line into paragraphs, which are displayed one after another
+        for paragraph in &bidi.paragraphs {
+            let (levels, runs) = bidi.visual_runs(paragraph, paragraph.range.clone());
+            for run in runs {
+                if !run.contains(&target) {
+                    left += text[run].chars().count();
+                } else if levels[target].is_rtl() {
+                    return Some(left + text[target..run.end].chars().count());
+                } else {
+                    return Some(left + text[run.start..target].chars().count() + 1);
+                }
+            }
+        }
+        unreachable!("the runs of the paragraphs cover the line")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn visual_columns() {
+        // The second line is right-to-left and displayed as "x = 123 ןב", and the third as "x = 1 ןב"
+        let text = "ltr\n\u{5d1}\u{5df} 123 = x\nx = \u{5d1}\u{5df} 1";
+        let lookup = LineColLookup::new(text);
+        let columns = |line: usize| (1..=12).map(|col| lookup.visual_col(line, col).unwrap()).collect::<Vec<_>>();
+        assert_eq!(columns(1), [1, 2, 3, 4, 4, 4, 4, 4, 4, 4, 4, 4]);
+        assert_eq!(columns(2), [10, 9, 8, 5, 6, 7, 4, 3, 2, 1, 11, 11]);
+        assert_eq!(columns(3), [1, 2, 3, 4, 8, 7, 6, 5, 9, 9, 9, 9]);
+        assert_eq!(lookup.get_visual(text.rfind('\u{5df}').unwrap()), (3, 7));
+        assert_eq!((lookup.visual_col(1, 0), lookup.visual_col(4, 1)), (None, None));
+    }
+}
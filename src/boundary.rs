@@ -0,0 +1,104 @@
+use crate::{Bias, LineColLookup};
+use core::fmt;
+
+/// The ways an index passed to [`LineColLookup::get_checked`] can be invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IndexError {
+    /// The index is greater than the length of the source.
+    OutOfBounds { index: usize, len: usize },
+    /// The index falls inside the UTF-8 encoding of a character.
+    NotCharBoundary { index: usize },
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfBounds { index, len } => write!(f, "index {} is past the end of a source of {} bytes", index, len),
+            Self::NotCharBoundary { index } => write!(f, "index {} is inside a character", index),
+        }
+    }
+}
+
+impl core::error::Error for IndexError {}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Finds the closest character boundary at or before `index`, or the end of the source if `index` is past it.
+    pub fn floor_char_boundary(&self, index: usize) -> usize {
+        let src = self.src();
+        if index >= src.len() {
+            return src.len();
+        }
+        // A character is at most 4 bytes long, so a boundary is never more than 3 bytes back
+        (index.saturating_sub(3)..=index).rev().find(|&i| src.is_char_boundary(i)).unwrap_or(0)
+    }
+
+    /// Finds the closest character boundary at or after `index`, or the end of the source if `index` is past it.
+    pub fn ceil_char_boundary(&self, index: usize) -> usize {
+        let src = self.src();
+        (index..src.len()).find(|&i| src.is_char_boundary(i)).unwrap_or(src.len())
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, like [`get`](Self::get),
+    /// but returns an error instead of panicking or reporting a misleading column for an invalid index.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`IndexError::OutOfBounds`] if `index` is greater than the length of the source,
+    /// or [`IndexError::NotCharBoundary`] if it falls inside a character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("añb");
+    /// assert_eq!(lookup.get_checked(3), Ok((1, 4)));
+    /// assert_eq!(lookup.get_checked(2), Err(IndexError::NotCharBoundary { index: 2 }));
+    /// assert_eq!(lookup.get_snapped(2, Bias::Before), (1, 2));
+    /// assert_eq!(lookup.get_snapped(2, Bias::After), (1, 4));
+    /// ```
+    pub fn get_checked(&self, index: usize) -> Result<(usize, usize), IndexError> {
+        let src = self.src();
+        if index > src.len() {
+            return Err(IndexError::OutOfBounds { index, len: src.len() });
+        }
+        if !src.is_char_boundary(index) {
+            return Err(IndexError::NotCharBoundary { index });
+        }
+        Ok(self.get(index))
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, like [`get`](Self::get),
+    /// after moving an index inside a character to the character's start for [`Bias::Before`] or its end for [`Bias::After`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn get_snapped(&self, index: usize, bias: Bias) -> (usize, usize) {
+        let snapped = match bias {
+            _ if index > self.src().len() => index,
+            Bias::Before => self.floor_char_boundary(index),
+            Bias::After => self.ceil_char_boundary(index),
+        };
+        self.get(snapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn snapping_to_char_boundaries() {
+        let text = "a😀\nñ";
+        let lookup = LineColLookup::new(text);
+        let floors = [0, 1, 1, 1, 1, 5, 6, 6, 8];
+        let ceils = [0, 1, 5, 5, 5, 5, 6, 8, 8];
+        for index in 0..=text.len() {
+            assert_eq!(lookup.floor_char_boundary(index), floors[index]);
+            assert_eq!(lookup.ceil_char_boundary(index), ceils[index]);
+        }
+        assert_eq!(lookup.floor_char_boundary(20), 8);
+        assert_eq!(lookup.get_snapped(3, Bias::After), (1, 6));
+        assert_eq!(lookup.get_checked(9), Err(IndexError::OutOfBounds { index: 9, len: 8 }));
+    }
+}
@@ -0,0 +1,193 @@
+use crate::{heads::HeadsConfig, LineColLookup, LineTableFormat};
+use core::{fmt, marker::PhantomData};
+#[cfg(feature = "grapheme-lite")]
+use {crate::{clusters::ClusterCache, Segmenter}, alloc::sync::Arc};
+
+/// The ways building a lookup table with [`LookupBuilder`] can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The source is too long to be indexed with the requested [`LineTableFormat`].
+    SourceTooLong { len: usize, format: LineTableFormat },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SourceTooLong { len, format } => write!(
+                f,
+                "source of {} bytes is longer than the {} bytes the {:?} line table format supports",
+                len,
+                format.max_source_len(),
+                format
+            ),
+        }
+    }
+}
+
+impl core::error::Error for BuildError {}
+
+/// Configures how a [`LineColLookup`] stores and builds its line head table.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = LineColLookup::builder("One\nTwo")
+///     .table_format(LineTableFormat::U32)
+///     .build()
+///     .unwrap();
+/// assert_eq!(lookup.get(5), (2, 2));
+/// ```
+pub struct LookupBuilder<'source, S = &'source str> {
+    src: S,
+    config: HeadsConfig,
+    #[cfg(feature = "grapheme-lite")]
+    segmenter: Option<Arc<dyn Segmenter>>,
+    _source: PhantomData<&'source str>,
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Creates a builder for a lookup table over `src`.
+    pub fn builder(src: S) -> LookupBuilder<'source, S> {
+        LookupBuilder {
+            src,
+            config: HeadsConfig::default(),
+            #[cfg(feature = "grapheme-lite")]
+            segmenter: None,
+            _source: PhantomData,
+        }
+    }
+}
+
+impl<'source, S: AsRef<str>> LookupBuilder<'source, S> {
+    /// Sets the storage format of the line head table. Defaults to [`LineTableFormat::Usize`].
+    pub fn table_format(mut self, format: LineTableFormat) -> Self {
+        self.config.format = format;
+        self
+    }
+
+    /// Sets whether to build a dense index recording the line of every 64-byte block of the source. Defaults to `false`.
+    ///
+    /// With a dense index, finding an index's line is an array read plus a forward walk over the few lines
+    /// starting in its block, instead of a binary search over every line.
+    /// The cost is one `usize` per 64 bytes of source, or 1/8 of the source size on 64-bit targets.
+    pub fn dense_index(mut self, dense: bool) -> Self {
+        self.config.dense = dense;
+        self
+    }
+
+    /// Sets whether to record the number of `char`s preceding every line start. Defaults to `false`.
+    ///
+    /// With char counts, [`LineColLookup::byte_to_char`] and [`LineColLookup::char_to_byte`] only walk one line
+    /// rather than the whole source before the position. The cost is one `usize` per line.
+    pub fn char_counts(mut self, char_counts: bool) -> Self {
+        self.config.char_counts = char_counts;
+        self
+    }
+
+    /// Sets whether to record the number of UTF-16 code units preceding every line start. Defaults to `false`.
+    ///
+    /// With UTF-16 counts, [`LineColLookup::byte_to_utf16`] and [`LineColLookup::utf16_to_byte`] only walk one line
+    /// rather than the whole source before the position. The cost is one `usize` per line.
+    pub fn utf16_counts(mut self, utf16_counts: bool) -> Self {
+        self.config.utf16_counts = utf16_counts;
+        self
+    }
+
+    /// Sets whether to record a hash of the content of every line as the source is scanned. Defaults to `false`.
+    ///
+    /// With line hashes, [`LineColLookup::line_hash`] is answered without rereading the line,
+    /// which makes comparing many lines between two versions of a file cheap. The cost is one `u64` per line.
+    pub fn line_hashes(mut self, line_hashes: bool) -> Self {
+        self.config.line_hashes = line_hashes;
+        self
+    }
+
+    /// Sets the [`Segmenter`] that grapheme cluster lookups find clusters with. Defaults to
+    #[cfg_attr(feature = "grapheme-clusters", doc = "[`UnicodeSegmenter`](crate::UnicodeSegmenter),")]
+    #[cfg_attr(not(feature = "grapheme-clusters"), doc = "`UnicodeSegmenter`,")]
+    /// or [`LiteSegmenter`](crate::LiteSegmenter) if only the `grapheme-lite` feature is enabled.
+    #[cfg(feature = "grapheme-lite")]
+    pub fn segmenter(mut self, segmenter: impl Segmenter + 'static) -> Self {
+        self.segmenter = Some(Arc::new(segmenter));
+        self
+    }
+
+    /// Creates the lookup table.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BuildError::SourceTooLong`] if the source cannot be indexed with the configured table format.
+    pub fn build(self) -> Result<LineColLookup<'source, S>, BuildError> {
+        let len = self.src.as_ref().len();
+        let format = self.config.format;
+        if len > format.max_source_len() {
+            return Err(BuildError::SourceTooLong { len, format });
+        }
+        #[allow(unused_mut)]
+        let mut lookup = LineColLookup::with_config(self.src, self.config);
+        #[cfg(feature = "grapheme-lite")]
+        if let Some(segmenter) = self.segmenter {
+            lookup.clusters = Arc::new(ClusterCache::with_segmenter(segmenter));
+        }
+        Ok(lookup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn formats_match_default() {
+        let text = "a\nab\nabc\n".repeat(100);
+        let wide = LineColLookup::new(text.as_str());
+        for format in [LineTableFormat::U32, LineTableFormat::Delta, LineTableFormat::Paged] {
+            let lookup = LineColLookup::builder(text.as_str()).table_format(format).build().unwrap();
+            for i in 0..=text.len() {
+                assert_eq!(lookup.get(i), wide.get(i));
+            }
+            assert_eq!(lookup.read_heads().starts.format(), format);
+        }
+    }
+
+    #[test]
+    fn dense_index_matches_default() {
+        let text = "a\nab\nabc\n".repeat(100);
+        let wide = LineColLookup::new(text.as_str());
+        let dense = LineColLookup::builder(text.as_str()).dense_index(true).build().unwrap();
+        for i in (0..=text.len()).rev() {
+            assert_eq!(dense.get(i), wide.get(i));
+        }
+        dense.clear_cache();
+        assert!(dense.read_heads().config.dense);
+    }
+
+    #[test]
+    fn char_counts_match_default() {
+        let text = "añb\n😀\n\nü".repeat(100);
+        let plain = LineColLookup::new(text.as_str());
+        let counted = LineColLookup::builder(text.as_str()).char_counts(true).build().unwrap();
+        for (chars, (i, _)) in text.char_indices().enumerate() {
+            assert_eq!(counted.byte_to_char(i), plain.byte_to_char(i));
+            assert_eq!(counted.byte_to_char(i), chars);
+            assert_eq!(counted.char_to_byte(chars), i);
+            assert_eq!(plain.char_to_byte(chars), i);
+        }
+        assert_eq!(counted.byte_to_char(text.len()), text.chars().count());
+        assert_eq!(counted.char_to_byte(text.chars().count() + 1), text.len());
+    }
+
+    #[test]
+    fn line_hashes_match_default() {
+        let text = "a\nb\r\n\nü\na".repeat(50);
+        let mut hashed = LineColLookup::builder(text.clone()).line_hashes(true).build().unwrap();
+        hashed.apply_edit(3..6, "x\na\n");
+        let plain = LineColLookup::new(hashed.src());
+        for line in 1..=plain.line_count() {
+            assert_eq!(hashed.line_hash(line), plain.line_hash(line));
+        }
+        assert!(hashed.lines_equal(1, &plain, 3) && !hashed.lines_equal(1, &plain, 5));
+        assert_eq!(hashed.line_hash(plain.line_count() + 1), None);
+    }
+}
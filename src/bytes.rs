@@ -0,0 +1,216 @@
+use crate::{heads::push_line_starts, line_lookup::line_range_in, LineColLookup, LineLookup, Position};
+use alloc::{borrow::Cow, string::String, vec, vec::Vec};
+use core::ops::Range;
+
+/// How a [`ByteLineColLookup`] interprets its bytes as text, when asked for the text of a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ByteMode {
+    /// Every byte is the character with the same code point, as in ISO 8859-1.
+    Latin1,
+    /// The bytes are UTF-8, with invalid sequences replaced by U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Each invalid sequence is one character, as [`String::from_utf8_lossy`] replaces it with one U+FFFD,
+    /// but keeps its original length in bytes, so byte indices still refer to the original bytes.
+    Utf8Lossy,
+}
+
+/// A line/column lookup table over a byte slice that need not be valid UTF-8, such as a log file with occasional garbage bytes.
+///
+/// Lines split on `'\n'` bytes, and the columns reported by [`get`](Self::get) count bytes, so no lossy copy of the source is needed
+/// to report positions in it. The table is built eagerly.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let log = b"ok\nbad \xff byte\n";
+/// let lookup = LineColLookup::from_bytes(log, ByteMode::Utf8Lossy);
+/// assert_eq!(lookup.get(9), (2, 7));
+/// assert_eq!(lookup.line_text(2).unwrap(), "bad \u{FFFD} byte");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ByteLineColLookup<'source> {
+    bytes: &'source [u8],
+    starts: Vec<usize>,
+    mode: ByteMode,
+}
+
+impl<'source> LineColLookup<'source> {
+    /// Creates a lookup table over `bytes`, which are interpreted as text according to `mode`.
+    pub fn from_bytes(bytes: &'source [u8], mode: ByteMode) -> ByteLineColLookup<'source> {
+        let mut starts = vec![0];
+        push_line_starts(&mut starts, bytes, 0);
+        ByteLineColLookup { bytes, starts, mode }
+    }
+}
+
+impl<'source> ByteLineColLookup<'source> {
+    /// Gets the source bytes.
+    pub fn bytes(&self) -> &'source [u8] {
+        self.bytes
+    }
+
+    /// Gets how the bytes are interpreted as text.
+    pub fn mode(&self) -> ByteMode {
+        self.mode
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, counting columns in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn get(&self, index: usize) -> Position {
+        let (line, start) = self.line_of(index);
+        (line + 1, index - start + 1)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, counting columns in characters
+    /// as the lookup's [`ByteMode`] decodes them, so for [`ByteMode::Utf8Lossy`] each invalid sequence is one column.
+    ///
+    /// An index inside a character counts that character, as an index inside a grapheme cluster does for
+    #[cfg_attr(feature = "grapheme-lite", doc = "[`LineColLookup::get_by_cluster`].")]
+    #[cfg_attr(not(feature = "grapheme-lite"), doc = "`LineColLookup::get_by_cluster`.")]
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let bytes = b"\xc3\xa9\xe2\x82 \xff!";
+    /// let lookup = LineColLookup::from_bytes(bytes, ByteMode::Utf8Lossy);
+    /// assert_eq!(lookup.get_by_char(2), (1, 2)); // The truncated "\xe2\x82" is one column
+    /// assert_eq!(lookup.get_by_char(6), (1, 5));
+    /// ```
+    pub fn get_by_char(&self, index: usize) -> Position {
+        let (line, start) = self.line_of(index);
+        let offset = index - start;
+        let col = match self.mode {
+            ByteMode::Latin1 => offset,
+            ByteMode::Utf8Lossy => {
+                let line_bytes = &self.bytes[line_range_in(&self.starts, self.bytes.len(), line).expect("the line exists")];
+                let mut units = 0;
+                let mut pos = 0;
+                'chunks: for chunk in line_bytes.utf8_chunks() {
+                    for (i, _) in chunk.valid().char_indices() {
+                        if pos + i >= offset {
+                            break 'chunks;
+                        }
+                        units += 1;
+                    }
+                    pos += chunk.valid().len();
+                    if !chunk.invalid().is_empty() {
+                        if pos >= offset {
+                            break;
+                        }
+                        units += 1;
+                        pos += chunk.invalid().len();
+                    }
+                }
+                units
+            }
+        };
+        (line + 1, col + 1)
+    }
+
+    /// Iterates over the byte ranges of the invalid UTF-8 sequences in the source, each of which [`ByteMode::Utf8Lossy`] decodes as U+FFFD.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::from_bytes(b"ok\nbad \xff\xfe", ByteMode::Utf8Lossy);
+    /// let invalid: Vec<_> = lookup.invalid_utf8().map(|range| lookup.get_by_char(range.start)).collect();
+    /// assert_eq!(invalid, [(2, 5), (2, 6)]);
+    /// ```
+    pub fn invalid_utf8(&self) -> impl Iterator<Item = Range<usize>> + 'source {
+        let base = self.bytes.as_ptr() as usize;
+        self.bytes.utf8_chunks().filter(|chunk| !chunk.invalid().is_empty()).map(move |chunk| {
+            let start = chunk.invalid().as_ptr() as usize - base;
+            start..start + chunk.invalid().len()
+        })
+    }
+
+    /// Finds the 0-based line containing `index` and the index its line starts at.
+    fn line_of(&self, index: usize) -> (usize, usize) {
+        if index > self.bytes.len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let line = self.starts.partition_point(|&start| start <= index) - 1;
+        (line, self.starts[line])
+    }
+
+    /// Returns the number of lines in the source.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns the byte range of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range_in(&self.starts, self.bytes.len(), line.checked_sub(1)?)
+    }
+
+    /// Gets the text of the 1-based `line`, excluding its terminating `'\n'`, decoded according to the lookup's [`ByteMode`],
+    /// or `None` if there is no such line. Lines that need no decoding are borrowed.
+    pub fn line_text(&self, line: usize) -> Option<Cow<'source, str>> {
+        let bytes = &self.bytes[self.line_range(line)?];
+        Some(match self.mode {
+            ByteMode::Latin1 => match core::str::from_utf8(bytes) {
+                Ok(text) if text.is_ascii() => Cow::Borrowed(text),
+                _ => Cow::Owned(bytes.iter().map(|&b| b as char).collect::<String>()),
+            },
+            ByteMode::Utf8Lossy => String::from_utf8_lossy(bytes),
+        })
+    }
+}
+
+impl LineLookup for ByteLineColLookup<'_> {
+    fn get(&self, index: usize) -> Position {
+        ByteLineColLookup::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        ByteLineColLookup::line_count(self)
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        ByteLineColLookup::line_range(self, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn byte_sources() {
+        let bytes = b"caf\xe9\r\n\n\xff";
+        let lookup = LineColLookup::from_bytes(bytes, ByteMode::Latin1);
+        assert_eq!(lookup.line_count(), 3);
+        assert_eq!(lookup.get(3), (1, 4));
+        assert_eq!(lookup.get(7), (3, 1));
+        assert_eq!(lookup.get(8), (3, 2));
+        assert_eq!(lookup.line_range(1), Some(0..5));
+        assert_eq!(lookup.line_text(1).unwrap(), "caf\u{e9}\r");
+        assert_eq!(lookup.line_text(3).unwrap(), "\u{ff}");
+        assert!(matches!(lookup.line_text(2), Some(std::borrow::Cow::Borrowed(""))));
+        assert_eq!(LineColLookup::from_bytes(bytes, ByteMode::Utf8Lossy).line_text(1).unwrap(), "caf\u{FFFD}\r");
+        assert_eq!(lookup.line_text(4), None);
+        let dynamic: &dyn LineLookup = &lookup;
+        assert_eq!((dynamic.line_count(), dynamic.line_range(3), dynamic.get(8)), (3, Some(7..8), (3, 2)));
+    }
+
+    #[test]
+    fn lossy_columns_match_lossy_decoding() {
+        let bytes = b"a\xf0\x9f\x98\x80\xf0\x9f\x98\xe9z\n\xc3\xa9\x80\x80x";
+        let lookup = LineColLookup::from_bytes(bytes, ByteMode::Utf8Lossy);
+        let lossy = String::from_utf8_lossy(&bytes[..10]);
+        assert_eq!(lossy, "a😀\u{FFFD}\u{FFFD}z");
+        assert_eq!(lookup.get_by_char(10), (1, lossy.chars().count() + 1));
+        let cols: Vec<_> = (0..bytes.len()).map(|index| lookup.get_by_char(index).1).collect();
+        assert_eq!(cols, [1, 2, 3, 3, 3, 3, 4, 4, 4, 5, 6, 1, 2, 2, 3, 4]);
+        let invalid: Vec<_> = lookup.invalid_utf8().collect();
+        assert_eq!(invalid, [5..8, 8..9, 13..14, 14..15]);
+        assert_eq!(LineColLookup::from_bytes(bytes, ByteMode::Latin1).get_by_char(9), (1, 10));
+    }
+}
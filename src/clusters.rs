@@ -0,0 +1,331 @@
+use crate::{Bias, LineColLookup};
+use crate::{sync, sync::RwLock};
+use alloc::{collections::BTreeMap, sync::Arc, vec::Vec};
+use core::ops::Range;
+#[cfg(feature = "grapheme-clusters")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A source of grapheme cluster boundaries, which the grapheme cluster APIs of [`LineColLookup`] find clusters with.
+///
+#[cfg_attr(feature = "grapheme-clusters", doc = "[`UnicodeSegmenter`]")]
+#[cfg_attr(not(feature = "grapheme-clusters"), doc = "`UnicodeSegmenter`")]
+/// is used by default, or [`LiteSegmenter`] if only the `grapheme-lite` feature is enabled; another can be set with [`LookupBuilder::segmenter`](crate::LookupBuilder::segmenter),
+/// for example to follow ICU or a custom emoji-aware splitter, or to stub segmentation out in tests.
+///
+/// Lines of pure ASCII never reach the segmenter, since each of their characters is taken to be its own cluster,
+/// except that a `"\r\n"` is always one cluster.
+pub trait Segmenter: Send + Sync {
+    /// Appends the byte offset of the start of each grapheme cluster of `line` to `starts`, in increasing order.
+    ///
+    /// `line` excludes its terminating `'\n'`, and the first offset appended for a non-empty line must be 0.
+    fn cluster_starts(&self, line: &str, starts: &mut Vec<usize>);
+}
+
+/// The default [`Segmenter`], which finds extended grapheme clusters with the `unicode-segmentation` crate.
+#[cfg(feature = "grapheme-clusters")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnicodeSegmenter;
+
+#[cfg(feature = "grapheme-clusters")]
+impl Segmenter for UnicodeSegmenter {
+    fn cluster_starts(&self, line: &str, starts: &mut Vec<usize>) {
+        starts.extend(line.grapheme_indices(true).map(|(i, _)| i));
+    }
+}
+
+/// A dependency-free [`Segmenter`] approximating extended grapheme clusters, for builds that can't afford `unicode-segmentation`.
+///
+/// It keeps together a character and the combining marks, variation selectors and emoji modifiers following it,
+/// emoji joined by zero-width joiners, and pairs of regional indicators forming flags.
+/// This handles most accented and emoji text, but not scripts whose clusters depend on other rules, such as Hangul syllables
+/// built from jamo or the spacing marks of many Indic scripts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LiteSegmenter;
+
+impl Segmenter for LiteSegmenter {
+    fn cluster_starts(&self, line: &str, starts: &mut Vec<usize>) {
+        let mut previous = None;
+        // The number of regional indicators in a row ending at the previous character
+        let mut indicators = 0;
+        for (i, c) in line.char_indices() {
+            let indicator = matches!(c, '\u{1F1E6}'..='\u{1F1FF}');
+            let joined = match previous {
+                None => false,
+                Some('\u{200D}') => is_pictographic(c),
+                Some(_) => is_extending(c) || (indicator && indicators % 2 == 1),
+            };
+            if !joined {
+                starts.push(i);
+            }
+            indicators = if indicator { indicators + 1 } else { 0 };
+            previous = Some(c);
+        }
+    }
+}
+
+/// Returns `true` for characters that attach to the cluster before them: combining marks in the main combining blocks,
+/// variation selectors, the zero-width joiner, emoji modifiers and tag characters.
+fn is_extending(c: char) -> bool {
+    matches!(c,
+        '\u{300}'..='\u{36F}'
+        | '\u{483}'..='\u{489}'
+        | '\u{591}'..='\u{5BD}'
+        | '\u{610}'..='\u{61A}'
+        | '\u{64B}'..='\u{65F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200D}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{1F3FB}'..='\u{1F3FF}'
+        | '\u{E0020}'..='\u{E007F}'
+        | '\u{E0100}'..='\u{E01EF}')
+}
+
+/// Returns `true` for characters in the blocks where pictographs joined into emoji sequences come from.
+fn is_pictographic(c: char) -> bool {
+    matches!(c, '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' | '\u{1F000}'..='\u{1FAFF}')
+}
+
+/// The grapheme cluster boundaries of individual lines, keyed by 0-based line number.
+///
+/// Each entry lists the byte offset, relative to the line start, at which each of the line's clusters begins.
+pub(crate) struct ClusterCache {
+    segmenter: Arc<dyn Segmenter>,
+    lines: RwLock<BTreeMap<usize, Arc<[usize]>>>,
+}
+
+/// The [`Segmenter`] used when no other is given.
+#[cfg(feature = "grapheme-clusters")]
+pub(crate) const DEFAULT_SEGMENTER: &dyn Segmenter = &UnicodeSegmenter;
+/// The [`Segmenter`] used when no other is given.
+#[cfg(not(feature = "grapheme-clusters"))]
+pub(crate) const DEFAULT_SEGMENTER: &dyn Segmenter = &LiteSegmenter;
+
+/// Counts the grapheme clusters that `segmenter` finds in `line` starting before `offset`,
+/// giving the 1-based column of `offset` in the line.
+pub(crate) fn cluster_col(segmenter: &dyn Segmenter, line: &str, offset: usize) -> usize {
+    let mut starts = Vec::new();
+    segmenter.cluster_starts(line, &mut starts);
+    // Every cluster starting before `offset` counts, including one that `offset` falls inside of
+    starts.iter().take_while(|&&start| start < offset).count() + 1
+}
+
+impl Default for ClusterCache {
+    fn default() -> Self {
+        #[cfg(feature = "grapheme-clusters")]
+        let segmenter = Arc::new(UnicodeSegmenter);
+        #[cfg(not(feature = "grapheme-clusters"))]
+        let segmenter = Arc::new(LiteSegmenter);
+        Self::with_segmenter(segmenter)
+    }
+}
+
+impl ClusterCache {
+    pub(crate) fn with_segmenter(segmenter: Arc<dyn Segmenter>) -> Self {
+        Self {
+            segmenter,
+            lines: Default::default(),
+        }
+    }
+
+    /// Creates an empty cache using the same segmenter as this one.
+    pub(crate) fn detached(&self) -> Self {
+        Self::with_segmenter(Arc::clone(&self.segmenter))
+    }
+
+    /// Gets the cluster boundaries of `line`, which is the text of the 0-based line `number`, computing them if needed.
+    pub(crate) fn boundaries(&self, number: usize, line: &str) -> Arc<[usize]> {
+        if let Some(boundaries) = sync::read(&self.lines).get(&number) {
+            return Arc::clone(boundaries);
+        }
+        let mut starts = Vec::new();
+        self.segmenter.cluster_starts(line, &mut starts);
+        let boundaries: Arc<[usize]> = starts.into();
+        sync::write(&self.lines).insert(number, Arc::clone(&boundaries));
+        boundaries
+    }
+
+    /// Discards the boundaries of every line from the 0-based line `number` onwards.
+    pub(crate) fn invalidate_from(&self, number: usize) {
+        sync::write(&self.lines).retain(|&line, _| line < number);
+    }
+
+    pub(crate) fn clear(&self) {
+        sync::write(&self.lines).clear();
+    }
+
+    pub(crate) fn memory_usage(&self) -> usize {
+        let lines = sync::read(&self.lines);
+        lines.len() * core::mem::size_of::<(usize, Arc<[usize]>)>()
+            + lines.values().map(|boundaries| boundaries.len() * core::mem::size_of::<usize>()).sum::<usize>()
+    }
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Looks up the 1-based line and column numbers of the specified byte index.
+    /// The column number correlates to the number of grapheme clusters up to and at the specified index.
+    ///
+    /// Returns a tuple with the line number first, then column number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Notes
+    /// This function uses a binary search to locate the line on which `index` resides.
+    /// This means that it runs in approximately O(log n) time.
+    ///
+    /// The first lookup on a non-ASCII line segments the whole line and caches its cluster boundaries,
+    /// so later lookups on the same line are O(log n) as well.
+    /// The cache can be discarded with [`LineColLookup::clear_cache`].
+    pub fn get_by_cluster(&self, index: usize) -> (usize, usize) {
+        let (line, line_start_index, ascii) = self.line_info(index);
+        if ascii {
+            // Outside of "\r\n", which can't occur before the end of a line, every ASCII character is its own cluster
+            return (line + 1, index - line_start_index + 1);
+        }
+        let rest = &self.src()[line_start_index..];
+        let text = &rest[..rest.find('\n').unwrap_or(rest.len())];
+        let boundaries = self.clusters.boundaries(line, text);
+        // Every cluster starting before `index` counts, including one that `index` falls inside of
+        let col = boundaries.partition_point(|&start| start < index - line_start_index) + 1;
+        (line + 1, col)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, like [`get_by_cluster`](Self::get_by_cluster),
+    /// after moving an index inside a grapheme cluster to the cluster's start or end according to `bias`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn get_by_cluster_snapped(&self, index: usize, bias: Bias) -> (usize, usize) {
+        self.get_by_cluster(self.snap_to_grapheme(index, bias))
+    }
+
+    /// Moves a byte index inside a grapheme cluster, such as between the parts of a ZWJ emoji sequence,
+    /// to the start of the cluster for [`Bias::Before`] or its end for [`Bias::After`].
+    /// Indices already on a cluster boundary are returned unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let text = "a👨‍👩‍👦b";
+    /// let lookup = LineColLookup::new(text);
+    /// let zwj = text.find('\u{200d}').unwrap();
+    /// assert_eq!(lookup.snap_to_grapheme(zwj, Bias::Before), 1);
+    /// assert_eq!(lookup.snap_to_grapheme(zwj, Bias::After), text.len() - 1);
+    /// ```
+    pub fn snap_to_grapheme(&self, index: usize, bias: Bias) -> usize {
+        let cluster = self.cluster_range(index);
+        match bias {
+            _ if cluster.start == index => index,
+            Bias::Before => cluster.start,
+            Bias::After => cluster.end,
+        }
+    }
+
+    /// Finds the byte range of the grapheme cluster containing `index`, which is empty at the end of the source.
+    pub(crate) fn cluster_range(&self, index: usize) -> Range<usize> {
+        let (line, line_start_index, ascii) = self.line_info(index);
+        let src = self.src();
+        let rest = &src[line_start_index..];
+        let len = rest.find('\n').unwrap_or(rest.len());
+        let text = &rest[..len];
+        // "\r\n" is one cluster, but the cached boundaries stop at the end of the line's text
+        if text.ends_with('\r') && len < rest.len() {
+            let crlf = line_start_index + len - 1;
+            if index == crlf || index == crlf + 1 {
+                return crlf..crlf + 2;
+            }
+        }
+        let offset = index - line_start_index;
+        if offset == len {
+            return index..(index + 1).min(src.len());
+        }
+        if ascii {
+            return index..index + 1;
+        }
+        let boundaries = self.clusters.boundaries(line, text);
+        let next = boundaries.partition_point(|&start| start <= offset);
+        line_start_index + boundaries[next - 1]..line_start_index + boundaries.get(next).copied().unwrap_or(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    #[cfg(feature = "grapheme-clusters")]
+    use unicode_segmentation::UnicodeSegmentation;
+
+    #[test]
+    #[cfg(feature = "grapheme-clusters")]
+    fn cached_clusters_match_segmentation() {
+        let text = "ab\ne\u{301}👨‍👩‍👦x\r\nok";
+        let lookup = LineColLookup::new(text);
+        for _ in 0..2 {
+            for (index, _) in text.char_indices().chain(Some((text.len(), ' '))) {
+                let line_start = text[..index].rfind('\n').map_or(0, |i| i + 1);
+                let expected = text[line_start..index].graphemes(true).count() + 1;
+                assert_eq!(lookup.get_by_cluster(index).1, expected, "index {}", index);
+            }
+        }
+        assert!(lookup.memory_usage() > 0);
+        lookup.clear_cache();
+        assert!(sync::read(&lookup.clusters.lines).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "grapheme-clusters")]
+    fn snapping_to_clusters() {
+        let text = "e\u{301}👨‍👩‍👦\r\nab\r\n";
+        let lookup = LineColLookup::new(text);
+        let boundaries: Vec<usize> = text.grapheme_indices(true).map(|(i, _)| i).chain(Some(text.len())).collect();
+        for index in 0..=text.len() {
+            let before = boundaries.iter().rev().find(|&&b| b <= index).copied().unwrap();
+            let after = boundaries.iter().find(|&&b| b >= index).copied().unwrap();
+            assert_eq!(lookup.snap_to_grapheme(index, Bias::Before), before, "index {}", index);
+            assert_eq!(lookup.snap_to_grapheme(index, Bias::After), after, "index {}", index);
+        }
+        assert_eq!(lookup.get_by_cluster_snapped(5, Bias::Before), (1, 2));
+        assert_eq!(lookup.get_by_cluster_snapped(5, Bias::After), (1, 3));
+        assert_eq!(lookup.get_by_cluster(5), (1, 3));
+    }
+
+    #[test]
+    fn lite_segmenter_matches_common_text() {
+        let text = "e\u{301}👨‍👩‍👦 🇯🇵🇺🇸🇫 👍🏽 1\u{FE0F}\u{20E3} x\u{200D}y";
+        let mut starts = Vec::new();
+        LiteSegmenter.cluster_starts(text, &mut starts);
+        let clusters: Vec<_> = starts.iter().zip(starts.iter().skip(1).chain(Some(&text.len()))).map(|(&a, &b)| &text[a..b]).collect();
+        assert_eq!(
+            clusters,
+            ["e\u{301}", "👨‍👩‍👦", " ", "🇯🇵", "🇺🇸", "🇫", " ", "👍🏽", " ", "1\u{FE0F}\u{20E3}", " ", "x\u{200D}", "y"]
+        );
+        #[cfg(feature = "grapheme-clusters")]
+        assert_eq!(clusters, text.graphemes(true).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn custom_segmenter() {
+        struct CharSegmenter;
+        impl Segmenter for CharSegmenter {
+            fn cluster_starts(&self, line: &str, starts: &mut Vec<usize>) {
+                starts.extend(line.char_indices().map(|(i, _)| i));
+            }
+        }
+        let text = "e\u{301}x\ne\u{301}";
+        let mut lookup = LineColLookup::builder(text.to_string()).segmenter(CharSegmenter).build().unwrap();
+        assert_eq!(lookup.get_by_cluster(3), (1, 3));
+        let copy = lookup.clone();
+        lookup.apply_edit(0..0, "a");
+        assert_eq!(lookup.get_by_cluster(9), (2, 3));
+        assert_eq!(copy.get_by_cluster(3), (1, 3));
+        assert_eq!(LineColLookup::new(text).get_by_cluster(3), (1, 2));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{heads::{count_chars, Unit}, LineColLookup};
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Looks up the 1-based line and column numbers of the specified byte index.
+    /// The column number correlates to the number of `char`s (Unicode scalar values) up to and at the specified index.
+    ///
+    /// Returns a tuple with the line number first, then column number.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("añb\nc");
+    /// assert_eq!(lookup.get(3), (1, 4));
+    /// assert_eq!(lookup.get_by_char(3), (1, 3));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Notes
+    /// Lines known to be pure ASCII are answered in O(log n) time; otherwise, the line is walked up to `index`.
+    pub fn get_by_char(&self, index: usize) -> (usize, usize) {
+        let (line, line_start_index, ascii) = self.line_info(index);
+        if ascii {
+            return (line + 1, index - line_start_index + 1);
+        }
+        let bytes = &self.src().as_bytes()[line_start_index..index];
+        (line + 1, count_chars(bytes) + 1)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index.
+    /// The column number correlates to the number of UTF-16 code units up to and at the specified index,
+    /// as used by JavaScript strings and the Language Server Protocol.
+    ///
+    /// Returns a tuple with the line number first, then column number.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a😀b");
+    /// assert_eq!(lookup.get_by_utf16(5), (1, 4));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Notes
+    /// Lines known to be pure ASCII are answered in O(log n) time; otherwise, the line is walked up to `index`.
+    pub fn get_by_utf16(&self, index: usize) -> (usize, usize) {
+        let (line, line_start_index, ascii) = self.line_info(index);
+        if ascii {
+            return (line + 1, index - line_start_index + 1);
+        }
+        let bytes = &self.src().as_bytes()[line_start_index..index];
+        (line + 1, bytes.iter().map(|&b| utf16_len_of_byte(b)).sum::<usize>() + 1)
+    }
+
+    /// Converts a byte index into the source into the number of `char`s preceding it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::builder("añb\nc").char_counts(true).build().unwrap();
+    /// assert_eq!(lookup.byte_to_char(5), 4);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Notes
+    /// Tables built with [`LookupBuilder::char_counts`](crate::LookupBuilder::char_counts) only walk the line containing `index`,
+    /// and not even that if the line is known to be pure ASCII. Otherwise, the whole source up to `index` is walked.
+    pub fn byte_to_char(&self, index: usize) -> usize {
+        self.byte_to_unit(index, Unit::Char)
+    }
+
+    /// Converts a count of `char`s from the start of the source into the byte index of the `char` it leads to,
+    /// the inverse of [`byte_to_char`](Self::byte_to_char). A count past the end of the source maps to its end.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::builder("añb\nc").char_counts(true).build().unwrap();
+    /// assert_eq!(lookup.char_to_byte(2), 3);
+    /// assert_eq!(lookup.char_to_byte(4), 5);
+    /// ```
+    ///
+    /// # Notes
+    /// Tables built with [`LookupBuilder::char_counts`](crate::LookupBuilder::char_counts) find the line containing the `char`
+    /// in O(log n) time and then walk it, unless it is known to be pure ASCII. Otherwise, the whole source up to the `char` is walked.
+    /// Either way, the whole line head table is built first.
+    pub fn char_to_byte(&self, char_index: usize) -> usize {
+        self.unit_to_byte(char_index, Unit::Char)
+    }
+
+    /// Looks up the 1-based line and column numbers of the `char` at a `char` index, counted from the start of the source,
+    /// along with the byte index it starts at. The column counts `char`s, as with [`get_by_char`](Self::get_by_char).
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("añb\nc");
+    /// assert_eq!(lookup.get_at_char(2), ((1, 3), 3));
+    /// assert_eq!(lookup.get_at_char(5), ((2, 2), 6));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `char_index` is greater than the number of `char`s in the source.
+    ///
+    /// # Notes
+    /// This costs as much as [`char_to_byte`](Self::char_to_byte) followed by [`get_by_char`](Self::get_by_char).
+    pub fn get_at_char(&self, char_index: usize) -> ((usize, usize), usize) {
+        let index = self.char_to_byte(char_index);
+        if index == self.src().len() && self.byte_to_char(index) < char_index {
+            panic!("Char index cannot be greater than the number of chars in the input slice.");
+        }
+        (self.get_by_char(index), index)
+    }
+
+    /// Converts a byte index into the source into the number of UTF-16 code units preceding it,
+    /// as a JavaScript string or a Language Server Protocol client using UTF-16 offsets counts them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::builder("a😀\nb").utf16_counts(true).build().unwrap();
+    /// assert_eq!(lookup.byte_to_utf16(6), 4);
+    /// assert_eq!(lookup.utf16_to_byte(4), 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Notes
+    /// Tables built with [`LookupBuilder::utf16_counts`](crate::LookupBuilder::utf16_counts) only walk the line containing `index`,
+    /// and not even that if the line is known to be pure ASCII. Otherwise, the whole source up to `index` is walked.
+    pub fn byte_to_utf16(&self, index: usize) -> usize {
+        self.byte_to_unit(index, Unit::Utf16)
+    }
+
+    /// Converts a count of UTF-16 code units from the start of the source into the byte index of the same position.
+    ///
+    /// An offset between the two halves of a surrogate pair maps to the start of their character,
+    /// and an offset past the end of the source maps to its end.
+    ///
+    /// # Notes
+    /// Tables built with [`LookupBuilder::utf16_counts`](crate::LookupBuilder::utf16_counts) find the line containing `offset`
+    /// in O(log n) time and then walk it, unless it is known to be pure ASCII. Otherwise, the whole source up to `offset` is walked.
+    /// Either way, the whole line head table is built first.
+    pub fn utf16_to_byte(&self, offset: usize) -> usize {
+        self.unit_to_byte(offset, Unit::Utf16)
+    }
+
+    /// Counts the `unit`s preceding a byte index.
+    fn byte_to_unit(&self, index: usize, unit: Unit) -> usize {
+        if index > self.src().len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        // The line and its count must come from the same table, which a clone sharing it may clear in between locks
+        let (line_start_index, ascii, units) = {
+            let heads = self.heads_through(index);
+            let (line, line_start_index, ascii) = self.line_info_in(&heads, index);
+            (line_start_index, ascii, heads.unit_start(unit, line))
+        };
+        match units {
+            Some(units) if ascii => units + index - line_start_index,
+            Some(units) => units + unit.count(&self.src().as_bytes()[line_start_index..index]),
+            None => unit.count(&self.src().as_bytes()[..index]),
+        }
+    }
+
+    /// Finds the byte index that `offset` `unit`s from the start of the source lead to.
+    fn unit_to_byte(&self, offset: usize, unit: Unit) -> usize {
+        let src = self.src();
+        let (line_start, mut units) = {
+            let heads = self.heads_through(src.len());
+            match heads.unit_starts_of(unit) {
+                Some(starts) => {
+                    let (line, units) = starts.line_of(offset);
+                    let line_start = heads.starts.get(line);
+                    if heads.is_ascii_line(line) {
+                        return (line_start + offset - units).min(src.len());
+                    }
+                    (line_start, units)
+                }
+                None => (0, 0),
+            }
+        };
+        for (i, c) in src[line_start..].char_indices() {
+            let len = match unit {
+                Unit::Char => 1,
+                Unit::Utf16 => c.len_utf16(),
+            };
+            if units + len > offset {
+                return line_start + i;
+            }
+            units += len;
+        }
+        src.len()
+    }
+}
+
+/// Returns `true` if `b` continues, rather than begins, a UTF-8 sequence.
+#[inline]
+pub(crate) fn is_continuation_byte(b: u8) -> bool {
+    b & 0xc0 == 0x80
+}
+
+/// Returns how many UTF-16 code units the byte `b` contributes to its character:
+/// 2 for the first byte of a 4-byte sequence, 1 for any other leading byte, and 0 for continuation bytes.
+#[inline]
+pub(crate) fn utf16_len_of_byte(b: u8) -> usize {
+    if b >= 0xf0 {
+        2
+    } else {
+        !is_continuation_byte(b) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn char_and_utf16_columns() {
+        let text = "ab\né😀x\ncd";
+        let lookup = LineColLookup::new(text);
+        for (index, _) in text.char_indices().chain(Some((text.len(), ' '))) {
+            let line_start = text[..index].rfind('\n').map_or(0, |i| i + 1);
+            let prefix = &text[line_start..index];
+            let (line, _) = lookup.get(index);
+            assert_eq!(lookup.get_by_char(index), (line, prefix.chars().count() + 1));
+            assert_eq!(lookup.get_by_utf16(index), (line, prefix.encode_utf16().count() + 1));
+        }
+    }
+
+    #[test]
+    fn positions_at_char_indices() {
+        let text = "aé\n😀\n\nb";
+        let lookup = LineColLookup::builder(text).char_counts(true).build().unwrap();
+        for (char_index, (index, _)) in text.char_indices().chain(Some((text.len(), ' '))).enumerate() {
+            assert_eq!(lookup.get_at_char(char_index), (lookup.get_by_char(index), index));
+        }
+    }
+
+    #[test]
+    fn utf16_offsets_match_encoding() {
+        let text = "ab\né😀x\n\ncd😀".repeat(3);
+        let plain = LineColLookup::new(text.as_str());
+        let counted = LineColLookup::builder(text.as_str()).utf16_counts(true).build().unwrap();
+        for (index, c) in text.char_indices().chain(Some((text.len(), ' '))) {
+            let offset = text[..index].encode_utf16().count();
+            for lookup in [&plain, &counted] {
+                assert_eq!(lookup.byte_to_utf16(index), offset);
+                assert_eq!(lookup.utf16_to_byte(offset), index);
+                if c.len_utf16() == 2 {
+                    assert_eq!(lookup.utf16_to_byte(offset + 1), index);
+                }
+            }
+        }
+        assert_eq!(counted.utf16_to_byte(1000), text.len());
+        assert_eq!(plain.utf16_to_byte(1000), text.len());
+    }
+
+    #[test]
+    fn columns_without_ascii_info() {
+        let lookup = LineColLookup::from_line_starts("ab\ncd", vec![0, 3]).unwrap();
+        assert_eq!(lookup.get_by_char(5), (2, 3));
+        assert_eq!(lookup.get_by_utf16(1), (1, 2));
+    }
+}
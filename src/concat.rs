@@ -0,0 +1,168 @@
+use crate::{LineColLookup, OwnedLineColLookup};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, ops::Range};
+
+/// A buffer built by concatenating named pieces of text, which resolves offsets into the whole buffer
+/// back to the piece they fall in and the line and column within it.
+///
+/// This suits parsing many small snippets in one pass while still reporting errors against the snippet they came from.
+/// Unlike a [`SourceMap`](crate::SourceMap), the pieces really are adjacent in one string, so an offset at the end
+/// of one piece is the start of the next and resolves to the latter.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut buffer = ConcatenatedSource::new();
+/// buffer.push("first", "a = 1;\n");
+/// buffer.push("second", "b = 2;\nc = ;\n");
+/// let error = buffer.src().find(" ;").unwrap();
+/// assert_eq!(buffer.resolve(error).unwrap().to_string(), "second:2:4");
+/// ```
+#[derive(Clone)]
+pub struct ConcatenatedSource {
+    lookup: OwnedLineColLookup,
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone)]
+struct Piece {
+    name: String,
+    /// The offset of the piece's first byte in the buffer.
+    start: usize,
+}
+
+/// A position resolved by a [`ConcatenatedSource`]: the piece it lies in and its 1-based line and column numbers there.
+///
+/// Displays as `name:line:column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PieceLocation<'buf> {
+    /// The index of the piece in the order pieces were pushed.
+    pub piece: usize,
+    pub name: &'buf str,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for PieceLocation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.name, self.line, self.col)
+    }
+}
+
+impl Default for ConcatenatedSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConcatenatedSource {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self {
+            lookup: LineColLookup::new(String::new()),
+            pieces: Vec::new(),
+        }
+    }
+
+    /// Appends a named piece of text to the buffer, returning its index.
+    pub fn push(&mut self, name: impl Into<String>, text: &str) -> usize {
+        self.pieces.push(Piece {
+            name: name.into(),
+            start: self.lookup.src().len(),
+        });
+        self.lookup.extend_with(text);
+        self.pieces.len() - 1
+    }
+
+    /// Gets the whole buffer.
+    pub fn src(&self) -> &str {
+        self.lookup.src()
+    }
+
+    /// Gets the lookup table over the whole buffer.
+    pub fn lookup(&self) -> &OwnedLineColLookup {
+        &self.lookup
+    }
+
+    /// Returns the number of pieces in the buffer.
+    pub fn len(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// Returns `true` if no pieces have been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    /// Gets the name of a piece.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `piece` is out of bounds.
+    pub fn name(&self, piece: usize) -> &str {
+        &self.pieces[piece].name
+    }
+
+    /// Returns the range of the buffer a piece occupies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `piece` is out of bounds.
+    pub fn piece_range(&self, piece: usize) -> Range<usize> {
+        let end = self.pieces.get(piece + 1).map_or(self.src().len(), |next| next.start);
+        self.pieces[piece].start..end
+    }
+
+    /// Finds the piece containing an offset into the buffer and the byte offset into that piece,
+    /// or `None` if the offset is past the end of the buffer or no pieces have been pushed.
+    pub fn local_offset(&self, global: usize) -> Option<(usize, usize)> {
+        if global > self.src().len() {
+            return None;
+        }
+        let piece = self.pieces.partition_point(|piece| piece.start <= global).checked_sub(1)?;
+        Some((piece, global - self.pieces[piece].start))
+    }
+
+    /// Resolves an offset into the buffer to its piece, line and column, or `None` if [`local_offset`](Self::local_offset) finds no piece.
+    ///
+    /// Lines and columns are counted from the start of the piece, as [`LineColLookup::get`] counts them from the start of a source.
+    pub fn resolve(&self, global: usize) -> Option<PieceLocation<'_>> {
+        let (piece, _) = self.local_offset(global)?;
+        let (start_line, start_col) = self.lookup.get(self.pieces[piece].start);
+        let (line, col) = self.lookup.get(global);
+        Some(PieceLocation {
+            piece,
+            name: &self.pieces[piece].name,
+            line: line - start_line + 1,
+            col: if line == start_line { col - start_col + 1 } else { col },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn resolving_pieces() {
+        let mut buffer = ConcatenatedSource::new();
+        assert_eq!(buffer.resolve(0), None);
+        let pieces = [("a", "x\nyz"), ("empty", ""), ("b", "w\n"), ("c", "ä\nb")];
+        for (name, text) in pieces {
+            buffer.push(name, text);
+        }
+        assert_eq!(buffer.src(), "x\nyzw\nä\nb");
+        assert_eq!(buffer.piece_range(2), 4..6);
+        for (piece, (name, text)) in pieces.iter().enumerate() {
+            let own = LineColLookup::new(*text);
+            for offset in 0..text.len() {
+                let location = buffer.resolve(buffer.piece_range(piece).start + offset).unwrap();
+                assert_eq!((location.piece, location.name), (piece, *name));
+                assert_eq!((location.line, location.col), own.get(offset));
+            }
+        }
+        assert_eq!(buffer.resolve(4).unwrap().to_string(), "b:1:1");
+        assert_eq!(buffer.resolve(buffer.src().len()).unwrap().to_string(), "c:2:2");
+        assert_eq!(buffer.local_offset(buffer.src().len() + 1), None);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{Line, LineColLookup, StrLookup};
+use alloc::vec::Vec;
+use core::{fmt, ops::Range};
+
+/// A line printed by [`ContextLines`], and whether a match touches it or it is only context.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContextLine<'a> {
+    pub line: Line<'a>,
+    pub is_match: bool,
+}
+
+/// The lines touched by some matches along with the lines of context around them, as `grep -C` prints them.
+///
+/// Displays each line as `number:text` if a match touches it and `number-text` otherwise,
+/// with a `--` line between groups of lines that aren't contiguous.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ContextLines<'a> {
+    lines: Vec<ContextLine<'a>>,
+}
+
+impl<'a> ContextLines<'a> {
+    /// Gets the lines to print, in increasing order.
+    pub fn lines(&self) -> &[ContextLine<'a>] {
+        &self.lines
+    }
+}
+
+impl fmt::Display for ContextLines<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut previous = None;
+        for ContextLine { line, is_match } in &self.lines {
+            if previous.is_some_and(|previous| line.number > previous + 1) {
+                writeln!(f, "--")?;
+            }
+            writeln!(f, "{}{}{}", line.number, if *is_match { ':' } else { '-' }, line.text)?;
+            previous = Some(line.number);
+        }
+        Ok(())
+    }
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Collects the lines touched by the byte ranges in `matches`, along with up to `before` lines of context before
+    /// and `after` lines after each, for printing as `grep` does.
+    ///
+    /// The matches may come in any order, and lines shared by overlapping context are only included once.
+    /// An empty range touches the line it is on.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range is out of bounds of the input `&str`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a\nb\nmatch\nc\nd\ne\nf\nmatch");
+    /// let matches = lookup.src().match_indices("match").map(|(i, m)| i..i + m.len());
+    /// assert_eq!(lookup.context_lines(matches, 1, 1).to_string(), "2-b\n3:match\n4-c\n--\n7-f\n8:match\n");
+    /// ```
+    pub fn context_lines(&self, matches: impl IntoIterator<Item = Range<usize>>, before: usize, after: usize) -> ContextLines<'_> {
+        context_lines(self, matches, before, after)
+    }
+}
+
+/// Collects the lines touched by the byte ranges in `matches` of any [`StrLookup`], along with lines of context around them,
+/// as [`LineColLookup::context_lines`] does.
+pub fn context_lines<L>(lookup: &L, matches: impl IntoIterator<Item = Range<usize>>, before: usize, after: usize) -> ContextLines<'_>
+where
+    L: StrLookup + ?Sized,
+{
+    let mut spans: Vec<(usize, usize)> = matches
+        .into_iter()
+        .map(|range| {
+            let (first, _) = lookup.get(range.start);
+            // A range ending at the start of a line doesn't touch it
+            let (last, _) = if range.end > range.start { lookup.get(range.end - 1) } else { (first, 0) };
+            (first, last)
+        })
+        .collect();
+    spans.sort_unstable();
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(spans.len());
+    for (first, last) in spans {
+        match merged.last_mut() {
+            Some(span) if first <= span.1 + 1 => span.1 = span.1.max(last),
+            _ => merged.push((first, last)),
+        }
+    }
+
+    let line_count = lookup.line_count();
+    let mut lines = Vec::new();
+    let mut next = 1;
+    for &(first, last) in &merged {
+        for number in first.saturating_sub(before).max(next)..=(last + after).min(line_count) {
+            let span = merged.partition_point(|&(_, last)| last < number);
+            let is_match = merged.get(span).is_some_and(|&(first, _)| first <= number);
+            let range = lookup.line_range(number).expect("the line exists");
+            lines.push(ContextLine { line: Line { number, text: &lookup.src()[range.clone()], range }, is_match });
+            next = number + 1;
+        }
+    }
+    ContextLines { lines }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn grep_context() {
+        let text = (1..=12).map(|n| format!("line {}\n", n)).collect::<String>();
+        let lookup = LineColLookup::new(text.as_str());
+        let at = |line: usize| lookup.line_range(line).unwrap();
+        // Lines 5 and 7 share their context, and a range spanning lines 10-11 matches both
+        let matches = [at(7).start..at(7).start + 1, at(5), at(10).start + 2..at(11).start + 1, at(1).start..at(1).start];
+        let context = lookup.context_lines(matches.clone(), 1, 1);
+        let numbers: Vec<_> = context.lines().iter().map(|line| (line.line.number, line.is_match)).collect();
+        assert_eq!(numbers, [
+            (1, true), (2, false), (4, false), (5, true), (6, false), (7, true), (8, false),
+            (9, false), (10, true), (11, true), (12, false),
+        ]);
+        assert!(context.to_string().starts_with("1:line 1\n2-line 2\n--\n4-line 4\n"));
+        assert_eq!(lookup.context_lines(Some(at(3)), 0, 0).to_string(), "3:line 3\n");
+        assert_eq!(lookup.context_lines(Some(at(2).start..at(3).start), 0, 5).lines().len(), 6);
+        let sharded = ShardedLineColLookup::with_shard_len(text.as_str(), 16);
+        assert_eq!(context_lines(&sharded, matches, 1, 1), context);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{LineColLookup, OwnedLineColLookup, Position};
+use alloc::{string::String, vec::Vec};
+
+/// A source paired with a copy of itself in which every `"\r\n"` has been normalized to `"\n"`,
+/// with lookups over both and a record of what the normalization removed.
+///
+/// This suits parsers that only handle `'\n'` line endings but must report errors against the original file:
+/// parse the [normalized](CrlfNormalizedLookup::normalized) text, then map its indices back with
+/// [`CrlfNormalizedLookup::to_original`]. Lone `'\r'`s are left in place.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = CrlfNormalizedLookup::new("a\r\nbc\r\nd");
+/// assert_eq!(lookup.normalized(), "a\nbc\nd");
+/// // The `d`
+/// assert_eq!(lookup.to_original(5), 7);
+/// assert_eq!(lookup.to_normalized(7), 5);
+/// assert_eq!(lookup.get_original(5), (3, 1));
+/// ```
+#[derive(Clone)]
+pub struct CrlfNormalizedLookup<'source> {
+    original: LineColLookup<'source>,
+    normalized: OwnedLineColLookup,
+    /// The index in the normalized text of each `'\n'` that had a `'\r'` removed before it, in increasing order.
+    removed: Vec<usize>,
+}
+
+impl<'source> CrlfNormalizedLookup<'source> {
+    /// Normalizes the line endings of `original`.
+    pub fn new(original: &'source str) -> Self {
+        let mut normalized = String::with_capacity(original.len());
+        let mut removed = Vec::new();
+        let mut rest = original;
+        while let Some(i) = rest.find("\r\n") {
+            normalized.push_str(&rest[..i]);
+            removed.push(normalized.len());
+            rest = &rest[i + 1..];
+        }
+        normalized.push_str(rest);
+        Self {
+            original: LineColLookup::new(original),
+            normalized: LineColLookup::new(normalized),
+            removed,
+        }
+    }
+
+    /// Gets the original text.
+    pub fn original(&self) -> &str {
+        self.original.src()
+    }
+
+    /// Gets the normalized text.
+    pub fn normalized(&self) -> &str {
+        self.normalized.src()
+    }
+
+    /// Gets the lookup table over the original text.
+    pub fn original_lookup(&self) -> &LineColLookup<'source> {
+        &self.original
+    }
+
+    /// Gets the lookup table over the normalized text.
+    pub fn normalized_lookup(&self) -> &OwnedLineColLookup {
+        &self.normalized
+    }
+
+    /// Maps a byte index into the normalized text to the original text.
+    /// A normalized `'\n'` maps to the `'\r'` that preceded it, so that spans ending at a line break include all of it.
+    pub fn to_original(&self, index: usize) -> usize {
+        index + self.removed.partition_point(|&removed| removed < index)
+    }
+
+    /// Maps a byte index into the original text to the normalized text.
+    /// Both bytes of a `"\r\n"` map to the `'\n'` that replaced it.
+    pub fn to_normalized(&self, index: usize) -> usize {
+        // In the original text, the `'\r'` removed before the `i`th normalized `'\n'` sits at `removed[i] + i`
+        let (mut lo, mut hi) = (0, self.removed.len());
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.removed[mid] + mid < index {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        index - lo
+    }
+
+    /// Looks up the 1-based line and column numbers in the original text of a byte index into the normalized text.
+    pub fn get_original(&self, index: usize) -> Position {
+        self.original.get(self.to_original(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn mapping_both_ways() {
+        let original = "\r\na\r\r\nb\rc\r\n";
+        let lookup = CrlfNormalizedLookup::new(original);
+        assert_eq!(lookup.normalized(), "\na\r\nb\rc\n");
+        for (normalized, expected) in [(0, 0), (1, 2), (2, 3), (3, 4), (4, 6), (7, 9), (8, 11)] {
+            assert_eq!(lookup.to_original(normalized), expected);
+        }
+        for (index, expected) in [(0, 0), (1, 0), (2, 1), (4, 3), (5, 3), (6, 4), (9, 7), (10, 7), (11, 8)] {
+            assert_eq!(lookup.to_normalized(index), expected);
+        }
+        assert_eq!(lookup.normalized_lookup().line_count(), lookup.original_lookup().line_count());
+        assert_eq!(lookup.get_original(4), (3, 1));
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{table::LineTable, LineColLookup, Position, TextSegment};
+use core::ops::Range;
+
+/// A stateful reader over a [`LineColLookup`] that remembers the last line it found.
+///
+/// Each lookup first checks that line and the one after it, only falling back to a binary search when both miss.
+/// For access patterns that move through the source a little at a time, such as walking tokens,
+/// this lets most lookups read a handful of line starts rather than search the table.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = LineColLookup::new("One\nTwo");
+/// let mut cursor = lookup.cursor();
+/// assert_eq!(cursor.get(1), (1, 2));
+/// assert_eq!(cursor.get(5), (2, 2));
+/// assert_eq!(cursor.line(), 2);
+/// ```
+pub struct LookupCursor<'lookup, 'source, S = &'source str> {
+    lookup: &'lookup LineColLookup<'source, S>,
+    line: usize,
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Creates a cursor over this lookup table, starting on the first line.
+    pub fn cursor(&self) -> LookupCursor<'_, 'source, S> {
+        LookupCursor { lookup: self, line: 0 }
+    }
+}
+
+impl<'lookup, 'source, S: AsRef<str>> LookupCursor<'lookup, 'source, S> {
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`LineColLookup::get`] would.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn get(&mut self, index: usize) -> Position {
+        if index > self.lookup.src().len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let heads = self.lookup.heads_through(index);
+        self.line = seek_line_near(&heads.starts, self.line, index);
+        (self.line + 1, index - heads.starts.get(self.line) + 1)
+    }
+
+    /// Looks up the positions of both ends of a byte range, along with its text.
+    ///
+    /// Ranges on the same or neighbouring lines as the last one found cost little more than a single lookup.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds of the input `&str`, or either end is inside a character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("One\nTwo");
+    /// let segment = lookup.cursor().segment(2..5);
+    /// assert_eq!((segment.text, segment.start, segment.end), ("e\nT", (1, 3), (2, 2)));
+    /// ```
+    pub fn segment(&mut self, range: Range<usize>) -> TextSegment<'lookup> {
+        TextSegment {
+            text: &self.lookup.src()[range.clone()],
+            start: self.get(range.start),
+            end: self.get(range.end),
+            range,
+        }
+    }
+
+    /// Returns the 1-based number of the last line found.
+    pub fn line(&self) -> usize {
+        self.line + 1
+    }
+}
+
+/// Finds the 0-based line containing `index`, checking `line` and the line after it before searching.
+pub(crate) fn seek_line_near(starts: &LineTable, line: usize, index: usize) -> usize {
+    let contains = |line: usize| {
+        line < starts.len() && starts.get(line) <= index && (line + 1 == starts.len() || index < starts.get(line + 1))
+    };
+    if contains(line) {
+        line
+    } else if contains(line + 1) {
+        line + 1
+    } else {
+        starts.line_of(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn cursor_matches_get() {
+        let text = "a\nab\n\nabc\n";
+        let lookup = LineColLookup::new(text);
+        let mut cursor = lookup.cursor();
+        for index in [0, 1, 2, 3, 5, 6, 10, 4, 0, 9] {
+            assert_eq!(cursor.get(index), lookup.get(index));
+            assert_eq!(cursor.line(), lookup.get(index).0);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// The differences between two versions of a source, for mapping positions from one to the other without an edit history.
+///
+/// The texts are compared line by line with Myers' algorithm, so each difference is a hunk of whole lines
+/// replaced by other whole lines. Positions outside the hunks map exactly; positions inside one are reported as changed.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let old = "fn main() {\n    run();\n}\n";
+/// let new = "// Entry point\nfn main() {\n    run(1);\n}\n";
+/// let diff = TextDiff::new(old, new);
+/// // The `main` in the old text
+/// assert_eq!(diff.map_index(3), DiffPosition::Unchanged(18));
+/// // The `run` call was edited
+/// assert_eq!(diff.map_index(18), DiffPosition::Changed(27..39));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextDiff {
+    /// The byte ranges of each differing hunk in the old and new texts, in increasing order.
+    hunks: Vec<(Range<usize>, Range<usize>)>,
+}
+
+/// Where a position from one version of a source ended up in the other, as found by [`TextDiff::map_index`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DiffPosition {
+    /// The position lies outside the changed text, and maps to this byte index.
+    Unchanged(usize),
+    /// The position lies within a changed hunk, which became this byte range.
+    Changed(Range<usize>),
+}
+
+/// A span mapped between versions of a source by [`TextDiff::map_span`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MappedSpan {
+    /// The resulting byte range. Ends that fell within a changed hunk are widened to cover the whole hunk.
+    pub range: Range<usize>,
+    /// Whether any part of the span was changed.
+    pub changed: bool,
+}
+
+impl TextDiff {
+    /// Compares `old` with `new`.
+    ///
+    /// Takes O((n + m) d) time, where `n` and `m` are the numbers of lines of each text and `d` is the number of lines that differ,
+    /// after lines shared by the start and end of both texts have been skipped.
+    pub fn new(old: &str, new: &str) -> Self {
+        let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+        let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+        let old_starts = line_starts(&old_lines);
+        let new_starts = line_starts(&new_lines);
+        let hunks = diff_lines(&old_lines, &new_lines)
+            .into_iter()
+            .map(|(old, new)| (old_starts[old.start]..old_starts[old.end], new_starts[new.start]..new_starts[new.end]))
+            .collect();
+        Self { hunks }
+    }
+
+    /// Iterates over the byte ranges of each differing hunk in the old text and the range it became in the new text.
+    pub fn hunks(&self) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+        self.hunks.iter().cloned()
+    }
+
+    /// Returns `true` if the two texts are identical.
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Returns the diff from the new text back to the old one.
+    pub fn inverted(&self) -> Self {
+        Self {
+            hunks: self.hunks.iter().map(|(old, new)| (new.clone(), old.clone())).collect(),
+        }
+    }
+
+    /// Maps a byte `index` into the old text to the new text.
+    ///
+    /// Indices at either end of a changed hunk count as unchanged, since the text on one side of them is.
+    /// Indices where lines were only inserted stay before the inserted lines.
+    pub fn map_index(&self, index: usize) -> DiffPosition {
+        // The first hunk ending at or after `index`
+        let hunk = self.hunks.partition_point(|(old, _)| old.end < index);
+        match self.hunks.get(hunk) {
+            Some((old, new)) if old.start < index && index < old.end => DiffPosition::Changed(new.clone()),
+            Some((old, new)) if index == old.end && !old.is_empty() => DiffPosition::Unchanged(new.end),
+            _ => DiffPosition::Unchanged(match hunk.checked_sub(1).map(|hunk| &self.hunks[hunk]) {
+                Some((old, new)) => index - old.end + new.end,
+                None => index,
+            }),
+        }
+    }
+
+    /// Maps a byte range of the old text to the new text, widening it to cover any hunk that either end fell within.
+    pub fn map_span(&self, span: Range<usize>) -> MappedSpan {
+        let start = match self.map_index(span.start) {
+            DiffPosition::Unchanged(index) => index,
+            DiffPosition::Changed(hunk) => hunk.start,
+        };
+        let end = match self.map_index(span.end) {
+            DiffPosition::Unchanged(index) => index,
+            DiffPosition::Changed(hunk) => hunk.end,
+        };
+        let changed = self.hunks.iter().any(|(old, _)| {
+            // Empty hunks are insertions, which change a span if they fall strictly within it
+            if old.is_empty() {
+                span.start < old.start && old.start < span.end
+            } else {
+                old.start < span.end && span.start < old.end
+            }
+        });
+        MappedSpan { range: start..end.max(start), changed }
+    }
+}
+
+/// Returns the byte index at which each of `lines` starts, followed by the total length.
+fn line_starts(lines: &[&str]) -> Vec<usize> {
+    let mut starts = Vec::with_capacity(lines.len() + 1);
+    starts.push(0);
+    for line in lines {
+        starts.push(starts[starts.len() - 1] + line.len());
+    }
+    starts
+}
+
+/// Finds the ranges of lines differing between `a` and `b`, as pairs of line ranges into each, in increasing order.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<(Range<usize>, Range<usize>)> {
+    // Lines shared by both ends don't need to go through the full search
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut matches = matching_lines(a_mid, b_mid);
+    matches.push((a_mid.len(), b_mid.len()));
+    let mut hunks = Vec::new();
+    let (mut x, mut y) = (0, 0);
+    for (match_x, match_y) in matches {
+        if match_x > x || match_y > y {
+            hunks.push((prefix + x..prefix + match_x, prefix + y..prefix + match_y));
+        }
+        (x, y) = (match_x + 1, match_y + 1);
+    }
+    hunks
+}
+
+/// Finds a longest common subsequence of `a` and `b` with Myers' algorithm, returning the indices of each matched pair of lines.
+fn matching_lines(a: &[&str], b: &[&str]) -> Vec<(usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    // The furthest `x` reached on each diagonal `k = x - y`, offset so that `k` can index it
+    let index = |k: isize| (k + max + 1) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk back through the furthest reaching paths, collecting the diagonal moves
+    let mut matches = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) { k + 1 } else { k - 1 };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    matches.reverse();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn hunks_between_texts() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "a\nc\nx\ny\ne\nf";
+        let diff = TextDiff::new(old, new);
+        assert_eq!(diff.hunks().collect::<Vec<_>>(), [(2..4, 2..2), (6..8, 4..8), (10..10, 10..11)]);
+        assert!(TextDiff::new(old, old).is_empty());
+        assert_eq!(TextDiff::new("", "a").hunks().collect::<Vec<_>>(), [(0..0, 0..1)]);
+        assert_eq!(diff.inverted().hunks().next(), Some((2..2, 2..4)));
+    }
+
+    #[test]
+    fn mapping_positions_and_spans() {
+        let diff = TextDiff::new("a\nb\nc\nd\ne\n", "a\nc\nx\ny\ne\nf");
+        assert_eq!(diff.map_index(1), DiffPosition::Unchanged(1));
+        assert_eq!(diff.map_index(3), DiffPosition::Changed(2..2));
+        assert_eq!(diff.map_index(5), DiffPosition::Unchanged(3));
+        assert_eq!(diff.map_index(7), DiffPosition::Changed(4..8));
+        assert_eq!(diff.map_index(8), DiffPosition::Unchanged(8));
+        assert_eq!(diff.map_index(10), DiffPosition::Unchanged(10));
+        assert_eq!(diff.map_span(4..6), MappedSpan { range: 2..4, changed: false });
+        assert_eq!(diff.map_span(5..9), MappedSpan { range: 3..9, changed: true });
+        assert_eq!(diff.map_span(0..1), MappedSpan { range: 0..1, changed: false });
+    }
+}
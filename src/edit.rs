@@ -0,0 +1,298 @@
+use crate::OwnedLineColLookup;
+use alloc::string::String;
+use core::ops::Range;
+
+/// A replacement of a byte range of a source with new text, described by the range and the new text's length.
+///
+/// Returned by [`OwnedLineColLookup::apply_edit`](crate::LineColLookup::apply_edit) so that positions recorded
+/// before the edit, such as those of an [`AnchorSet`](crate::AnchorSet), can be moved to match.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Edit {
+    /// The byte range of the source that was replaced.
+    pub replaced: Range<usize>,
+    /// The length in bytes of the text that replaced it.
+    pub new_len: usize,
+    /// How the edit changed the source's lines, if known.
+    pub lines: Option<LineEdit>,
+}
+
+impl Edit {
+    /// Returns the byte range of the replacement text in the edited source.
+    pub fn inserted(&self) -> Range<usize> {
+        self.replaced.start..self.replaced.start + self.new_len
+    }
+
+    /// Moves a byte index into the source before the edit to the matching index after it.
+    ///
+    /// Indices outside the replaced range keep their place relative to the untouched text, as do its two ends if it isn't empty.
+    /// Indices strictly within the replaced range, or where an empty range had text inserted, end up before or after
+    /// the new text according to `bias`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let edit = Edit { replaced: 2..4, new_len: 3, lines: None };
+    /// assert_eq!(edit.map_index(2, Bias::After), 2);
+    /// assert_eq!(edit.map_index(3, Bias::Before), 2);
+    /// assert_eq!(edit.map_index(3, Bias::After), 5);
+    /// assert_eq!(edit.map_index(4, Bias::Before), 5);
+    /// ```
+    pub fn map_index(&self, index: usize, bias: Bias) -> usize {
+        let Range { start, end } = self.replaced;
+        if index < start || (index == start && start < end) {
+            index
+        } else if index > end || (index == end && start < end) {
+            index - (end - start) + self.new_len
+        } else {
+            match bias {
+                Bias::Before => start,
+                Bias::After => start + self.new_len,
+            }
+        }
+    }
+
+    /// Moves a byte index into the edited source back to the matching index before the edit,
+    /// or returns `None` if it lies strictly within the replacement text, which had no counterpart.
+    ///
+    /// Indices at either end of the replacement text map to the same end of the replaced range,
+    /// except that if the replacement was empty, `bias` decides which end of the removed text to map to.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let edit = Edit { replaced: 2..4, new_len: 3, lines: None };
+    /// assert_eq!(edit.map_index_back(5, Bias::Before), Some(4));
+    /// assert_eq!(edit.map_index_back(3, Bias::Before), None);
+    /// assert_eq!(edit.map_index_back(7, Bias::Before), Some(6));
+    /// ```
+    pub fn map_index_back(&self, index: usize, bias: Bias) -> Option<usize> {
+        let Range { start, end } = self.inserted();
+        if index < start {
+            Some(index)
+        } else if index > end {
+            Some(index - self.new_len + self.replaced.len())
+        } else if start == end {
+            Some(match bias {
+                Bias::Before => self.replaced.start,
+                Bias::After => self.replaced.end,
+            })
+        } else if index == start {
+            Some(self.replaced.start)
+        } else if index == end {
+            Some(self.replaced.end)
+        } else {
+            None
+        }
+    }
+}
+
+/// The edit that undoes an applied [`Edit`]: the range its replacement text now occupies, and the text it removed.
+///
+/// Returned by [`OwnedLineColLookup::apply_edit_with_inverse`](crate::LineColLookup::apply_edit_with_inverse).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InverseEdit {
+    /// The byte range of the edited source to replace.
+    pub replaced: Range<usize>,
+    /// The text to replace it with.
+    pub text: String,
+}
+
+/// How an [`Edit`] changed the lines of a source, for carrying per-line information such as analysis results across it.
+///
+/// Every line not touched by the edit keeps its identity, moved by the number of lines the edit added or removed.
+/// Of the touched lines, the one containing the start of the edit keeps its identity if the edit left its beginning in place,
+/// and the one containing the end of the edit keeps its identity as the line now holding the text that followed the edit.
+/// Lines entirely within the replaced range are gone. When an edit joins lines, both survivors map to the joined line.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut lookup = OwnedLineColLookup::new("a\nb\nc\nd".to_string());
+/// let lines = lookup.apply_edit(2..2, "x\ny\n").lines.unwrap();
+/// assert_eq!(lookup.src(), "a\nx\ny\nb\nc\nd");
+/// assert_eq!(lines.map_line(1), Some(1));
+/// assert_eq!(lines.map_line(2), Some(4));
+/// assert_eq!(lines.map_line(4), Some(6));
+/// assert_eq!(lines.map_line(0), None);
+/// assert_eq!(lines.changed_lines(), 2..5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LineEdit {
+    /// The 0-based line containing the start of the edit.
+    first: usize,
+    /// The number of line starts within the replaced range.
+    removed: usize,
+    /// The number of line starts within the replacement text.
+    inserted: usize,
+    /// Whether the edit started after the beginning of its first line.
+    kept_first: bool,
+}
+
+impl LineEdit {
+    /// Returns the 1-based line that the 1-based `line` from before the edit became, or `None` if the edit removed it.
+    ///
+    /// Lines are numbered from 1, as everywhere else in this crate, so there is no line 0 to map and it gives `None`.
+    pub fn map_line(&self, line: usize) -> Option<usize> {
+        let last = self.first + self.removed;
+        match line.checked_sub(1)? {
+            line if line < self.first => Some(line + 1),
+            line if line > last => Some(line - self.removed + self.inserted + 1),
+            line if line == self.first && self.kept_first => Some(self.first + 1),
+            line if line == last => Some(self.first + self.inserted + 1),
+            _ => None,
+        }
+    }
+
+    /// Returns the 1-based lines after the edit whose contents it changed. Every other line is identical to the line it was mapped from.
+    pub fn changed_lines(&self) -> Range<usize> {
+        self.first + 1..self.first + self.inserted + 2
+    }
+
+    /// Returns the number of lines the edit added, or removed if negative.
+    pub fn line_delta(&self) -> isize {
+        self.inserted as isize - self.removed as isize
+    }
+}
+
+/// Which side of text inserted at a position the position stays on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Bias {
+    /// The position stays before the inserted text, like the start of a selection.
+    #[default]
+    Before,
+    /// The position moves past the inserted text, like a cursor being typed at.
+    After,
+}
+
+impl OwnedLineColLookup {
+    /// Replaces the bytes in `replaced` with `new_text`, updating the line head table in place.
+    ///
+    /// Instead of rescanning the source, the line starts inside the replaced range are swapped for those in `new_text`
+    /// and every later line start is moved, so an edit costs time proportional to its size plus the number of lines after it.
+    /// [`LineTableFormat::Paged`](crate::LineTableFormat::Paged) tables move the later lines a whole page at a time,
+    /// while [`LineTableFormat::Delta`](crate::LineTableFormat::Delta) tables re-encode each of them, which is several times slower.
+    /// This suits editor buffers that are looked up between keystrokes.
+    /// If the line head table doesn't reach the end of the edit yet, it is first built up to there.
+    ///
+    /// If clones or views share this lookup's caches, it first takes its own copy of them, so they are unaffected.
+    ///
+    /// Returns the applied [`Edit`], for remapping positions and lines recorded before it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replaced` is out of bounds or doesn't lie on `char` boundaries.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let mut lookup = OwnedLineColLookup::new_eager("One\nTwo\nThree".to_string());
+    /// let edit = lookup.apply_edit(3..8, " and a half\n");
+    /// assert_eq!(edit.inserted(), 3..15);
+    /// assert_eq!(lookup.src(), "One and a half\nThree");
+    /// assert_eq!(lookup.get(16), (2, 2));
+    /// ```
+    pub fn apply_edit(&mut self, replaced: Range<usize>, new_text: &str) -> Edit {
+        let Range { start, end } = replaced;
+        self.heads_mut();
+        let lines = {
+            let heads = self.heads_through(end);
+            let first = heads.starts.line_of(start);
+            LineEdit {
+                first,
+                removed: heads.starts.line_of(end) - first,
+                inserted: new_text.bytes().filter(|&b| b == b'\n').count(),
+                kept_first: heads.starts.get(first) < start,
+            }
+        };
+        // The line containing the start of the edit is the first one whose contents may change
+        #[cfg(feature = "grapheme-lite")]
+        self.clusters.invalidate_from(lines.first);
+        self.src.replace_range(start..end, new_text);
+        self.write_heads().splice(&self.src, start..end, new_text.len());
+        Edit { replaced: start..end, new_len: new_text.len(), lines: Some(lines) }
+    }
+
+    /// Applies an edit like [`LineColLookup::apply_edit`](crate::LineColLookup::apply_edit),
+    /// and also returns the [`InverseEdit`] that would undo it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replaced` is out of bounds or doesn't lie on `char` boundaries.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let mut lookup = OwnedLineColLookup::new("let x;".to_string());
+    /// let (_, inverse) = lookup.apply_edit_with_inverse(4..5, "long");
+    /// assert_eq!(inverse, InverseEdit { replaced: 4..8, text: "x".to_string() });
+    /// lookup.apply_edit(inverse.replaced, &inverse.text);
+    /// assert_eq!(lookup.src(), "let x;");
+    /// ```
+    pub fn apply_edit_with_inverse(&mut self, replaced: Range<usize>, new_text: &str) -> (Edit, InverseEdit) {
+        let removed = String::from(&self.src[replaced.clone()]);
+        let edit = self.apply_edit(replaced, new_text);
+        let inverse = InverseEdit { replaced: edit.inserted(), text: removed };
+        (edit, inverse)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn editing_matches_rebuilding() {
+        let edits = [(0..0, "a\nä"), (1..2, ""), (3..3, "b\n\nab"), (0..4, "c\n"), (2..2, ""), (5..6, "\ndéf\n")];
+        for eager in [false, true] {
+            let mut lookup = OwnedLineColLookup::new(String::new());
+            let mut text = String::new();
+            for (replaced, new_text) in edits.iter().cloned() {
+                if eager {
+                    lookup.precompute();
+                }
+                let clone = lookup.clone();
+                lookup.apply_edit(replaced.clone(), new_text);
+                text.replace_range(replaced, new_text);
+                assert_eq!(clone.line_count(), LineColLookup::new(clone.src()).line_count());
+                let rebuilt = LineColLookup::new(text.as_str());
+                assert_eq!(lookup.src(), text);
+                for i in 0..=text.len() {
+                    assert_eq!(lookup.get(i), rebuilt.get(i));
+                    assert_eq!(lookup.get_by_char(i), rebuilt.get_by_char(i));
+                    #[cfg(feature = "grapheme-lite")]
+                    assert_eq!(lookup.get_by_cluster(i), rebuilt.get_by_cluster(i));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mapping_lines() {
+        let mut lookup = OwnedLineColLookup::new("ab\ncd\nef\ngh".to_string());
+        // Joining the first two lines and removing the third
+        let lines = lookup.apply_edit(1..6, "").lines.unwrap();
+        assert_eq!(lookup.src(), "aef\ngh");
+        assert_eq!((1..=4).map(|line| lines.map_line(line)).collect::<Vec<_>>(), [Some(1), None, Some(1), Some(2)]);
+        assert_eq!(lines.changed_lines(), 1..2);
+        assert_eq!(lines.line_delta(), -2);
+        // Replacing a whole line
+        let lines = lookup.apply_edit(4..6, "x\ny").lines.unwrap();
+        assert_eq!(lookup.src(), "aef\nx\ny");
+        assert_eq!([lines.map_line(1), lines.map_line(2)], [Some(1), Some(3)]);
+        assert_eq!(lines.changed_lines(), 2..4);
+    }
+
+    #[test]
+    fn editing_partially_scanned_source() {
+        let text = "ab\n".repeat(5000);
+        let mut lookup = OwnedLineColLookup::new(text.clone());
+        assert_eq!(lookup.get(3), (2, 1));
+        lookup.apply_edit(6000..9000, "x\ny");
+        let mut edited = text;
+        edited.replace_range(6000..9000, "x\ny");
+        let rebuilt = LineColLookup::new(edited.as_str());
+        for i in (0..=edited.len()).step_by(7) {
+            assert_eq!(lookup.get(i), rebuilt.get(i));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::{Bias, Edit};
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// A numbered history of the [`Edit`]s applied to a source, for carrying positions between versions of it.
+///
+/// Each edit pushed onto the log starts a new version. A position recorded against any version still held in the log,
+/// such as a diagnostic computed asynchronously against an older snapshot, can be mapped forward to the current version,
+/// or back to an older one wherever that is unambiguous.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut lookup = OwnedLineColLookup::new("fn f() {}".to_string());
+/// let mut log = EditLog::new();
+/// let snapshot = log.version();
+/// log.push(lookup.apply_edit(0..0, "// f\n"));
+/// log.push(lookup.apply_edit(8..9, "main"));
+/// // The `(` at byte 4 of the snapshot
+/// let index = log.map_index(4, snapshot, log.version(), Bias::Before).unwrap();
+/// assert_eq!(lookup.get(index), (2, 8));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EditLog {
+    /// The edits still held, oldest first. The edit at position `i` leads from version `first_version + i` to the next one.
+    edits: VecDeque<Edit>,
+    first_version: u64,
+}
+
+impl EditLog {
+    /// Creates an empty log at version 0.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty log whose current version is `version`, such as a document version sent by an editor.
+    pub fn starting_at(version: u64) -> Self {
+        Self {
+            edits: VecDeque::new(),
+            first_version: version,
+        }
+    }
+
+    /// Returns the current version: the one reached by the last edit pushed.
+    pub fn version(&self) -> u64 {
+        self.first_version + self.edits.len() as u64
+    }
+
+    /// Returns the oldest version positions can still be mapped from or to.
+    pub fn oldest_version(&self) -> u64 {
+        self.first_version
+    }
+
+    /// Records `edit` as having been applied to the current version, and returns the new version it leads to.
+    pub fn push(&mut self, edit: Edit) -> u64 {
+        self.edits.push_back(edit);
+        self.version()
+    }
+
+    /// Forgets the edits leading up to `version`, so that versions older than it can no longer be mapped.
+    pub fn discard_before(&mut self, version: u64) {
+        let discarded = version.clamp(self.first_version, self.version()) - self.first_version;
+        self.edits.drain(..discarded as usize);
+        self.first_version += discarded;
+    }
+
+    /// Returns the edits leading from version `from` to version `to`, oldest first, or `None` if either isn't held in the log.
+    pub fn edits_between(&self, from: u64, to: u64) -> Option<Vec<&Edit>> {
+        let (from, to) = (self.offset(from)?, self.offset(to)?);
+        Some(self.edits.range(from.min(to)..to.max(from)).collect())
+    }
+
+    /// Maps a byte `index` into version `from` of the source to the matching index into version `to`.
+    ///
+    /// Mapping forward applies [`Edit::map_index`] for each edit in between, with `bias` deciding where positions
+    /// within replaced text end up. Mapping backward applies [`Edit::map_index_back`] in reverse.
+    ///
+    /// Returns `None` if either version isn't held in the log, or if mapping backward, the index lies within text inserted in between.
+    pub fn map_index(&self, index: usize, from: u64, to: u64, bias: Bias) -> Option<usize> {
+        let (start, end) = (self.offset(from)?, self.offset(to)?);
+        if start <= end {
+            Some(self.edits.range(start..end).fold(index, |index, edit| edit.map_index(index, bias)))
+        } else {
+            self.edits.range(end..start).rev().try_fold(index, |index, edit| edit.map_index_back(index, bias))
+        }
+    }
+
+    /// Returns the position of the edit leading away from `version`, or `None` if the version isn't held in the log.
+    fn offset(&self, version: u64) -> Option<usize> {
+        version.checked_sub(self.first_version).filter(|&offset| offset <= self.edits.len() as u64).map(|offset| offset as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn mapping_between_versions() {
+        let mut lookup = OwnedLineColLookup::new("abcdef".to_string());
+        let mut log = EditLog::starting_at(7);
+        log.push(lookup.apply_edit(1..3, ""));
+        log.push(lookup.apply_edit(2..2, "xyz"));
+        assert_eq!(lookup.src(), "adxyzef");
+        assert_eq!(log.version(), 9);
+        assert_eq!(log.map_index(4, 7, 9, Bias::Before), Some(2));
+        assert_eq!(log.map_index(4, 7, 9, Bias::After), Some(5));
+        assert_eq!(log.map_index(2, 7, 9, Bias::Before), Some(1));
+        assert_eq!(log.map_index(2, 7, 9, Bias::After), Some(1));
+        assert_eq!(log.map_index(5, 9, 7, Bias::Before), Some(4));
+        assert_eq!(log.map_index(3, 9, 7, Bias::Before), None);
+        assert_eq!(log.map_index(1, 9, 7, Bias::After), Some(3));
+        assert_eq!(log.map_index(0, 6, 9, Bias::Before), None);
+        assert_eq!(log.edits_between(9, 8).unwrap().len(), 1);
+        log.discard_before(8);
+        assert_eq!(log.oldest_version(), 8);
+        assert_eq!(log.map_index(0, 7, 9, Bias::Before), None);
+        assert_eq!(log.map_index(6, 8, 9, Bias::Before), Some(9));
+    }
+}
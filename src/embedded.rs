@@ -0,0 +1,158 @@
+use crate::{LineColLookup, Position, SegmentError};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A lookup table over a host file, such as an HTML template, containing embedded regions of another language,
+/// such as interpolated expressions, that answers queries in the host's coordinates or the region's as appropriate.
+///
+/// Each region's text is the span of the host it was extracted from. Positions within a region are 1-based lines
+/// and byte columns counted from the start of the region, as a parser handed just the region's text would report them.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let src = "<p>\n  {{ user.\n  name }}\n</p>";
+/// let start = src.find("{{").unwrap() + 2;
+/// let end = src.find("}}").unwrap();
+/// let lookup = EmbeddedLookup::new(src, [start..end]).unwrap();
+/// let name = src.find("name").unwrap();
+/// assert_eq!(lookup.get(name), TemplatePosition::Embedded { region: 0, position: (2, 3), host: (3, 3) });
+/// assert_eq!(lookup.get(0), TemplatePosition::Host((1, 1)));
+/// assert_eq!(lookup.to_host(0, 1), start + 1);
+/// ```
+#[derive(Clone)]
+pub struct EmbeddedLookup<'source> {
+    host: LineColLookup<'source>,
+    regions: Vec<Range<usize>>,
+}
+
+/// A position found by [`EmbeddedLookup::get`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplatePosition {
+    /// The position lies outside every embedded region, at this line and column of the host.
+    Host(Position),
+    /// The position lies in the embedded region at index `region`, at `position` within it and `host` in the host.
+    Embedded { region: usize, position: Position, host: Position },
+}
+
+impl<'source> EmbeddedLookup<'source> {
+    /// Creates a lookup table over the host `src` with embedded regions at the given byte ranges, which must be in increasing order
+    /// and not overlap, though one may end where the next starts.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SegmentError`] if a region is reversed or starts before the previous one ends.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a region ends past the end of `src`.
+    pub fn new<I: IntoIterator<Item = Range<usize>>>(src: &'source str, regions: I) -> Result<Self, SegmentError> {
+        let mut checked: Vec<Range<usize>> = Vec::new();
+        for (at, region) in regions.into_iter().enumerate() {
+            if region.start > region.end {
+                return Err(SegmentError::Reversed { at });
+            }
+            if checked.last().is_some_and(|last| region.start < last.end) {
+                return Err(SegmentError::Unordered { at });
+            }
+            assert!(region.end <= src.len(), "region {} ends past the end of the source", at);
+            checked.push(region);
+        }
+        Ok(Self {
+            host: LineColLookup::new(src),
+            regions: checked,
+        })
+    }
+
+    /// Gets the host source.
+    pub fn src(&self) -> &str {
+        self.host.src()
+    }
+
+    /// Gets the lookup table over the host source.
+    pub fn host(&self) -> &LineColLookup<'source> {
+        &self.host
+    }
+
+    /// Iterates over the byte ranges of the embedded regions in the host.
+    pub fn regions(&self) -> impl Iterator<Item = Range<usize>> + '_ {
+        self.regions.iter().cloned()
+    }
+
+    /// Gets the text of an embedded region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `region` is out of bounds.
+    pub fn region_src(&self, region: usize) -> &str {
+        &self.src()[self.regions[region].clone()]
+    }
+
+    /// Finds the embedded region containing a byte index of the host and the byte offset into it, if any.
+    ///
+    /// A region contains its end, and an index where one region ends and the next starts belongs to the latter.
+    pub fn to_embedded(&self, index: usize) -> Option<(usize, usize)> {
+        let region = self.regions.partition_point(|region| region.start <= index).checked_sub(1)?;
+        let range = &self.regions[region];
+        (index <= range.end).then_some((region, index - range.start))
+    }
+
+    /// Converts a byte `offset` into an embedded region to a byte index of the host.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `region` is out of bounds, or `offset` is greater than the length of the region.
+    pub fn to_host(&self, region: usize, offset: usize) -> usize {
+        let range = &self.regions[region];
+        assert!(offset <= range.len(), "offset {} is past the end of region {}", offset, region);
+        range.start + offset
+    }
+
+    /// Looks up the line and column of a byte `offset` into an embedded region, counted from the start of the region.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `region` is out of bounds, or `offset` is greater than the length of the region.
+    pub fn get_embedded(&self, region: usize, offset: usize) -> Position {
+        let (start_line, start_col) = self.host.get(self.regions[region].start);
+        let (line, col) = self.host.get(self.to_host(region, offset));
+        (line - start_line + 1, if line == start_line { col - start_col + 1 } else { col })
+    }
+
+    /// Looks up a byte index of the host, giving its position within the embedded region containing it,
+    /// or its position in the host if no region does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the host.
+    pub fn get(&self, index: usize) -> TemplatePosition {
+        let host = self.host.get(index);
+        match self.to_embedded(index) {
+            Some((region, offset)) => TemplatePosition::Embedded { region, position: self.get_embedded(region, offset), host },
+            None => TemplatePosition::Host(host),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn switching_coordinates() {
+        let src = "a {{x\ny}}{{z}} b";
+        let lookup = EmbeddedLookup::new(src, [4..7, 7..7, 11..12]).unwrap();
+        assert_eq!(lookup.region_src(0), "x\ny");
+        assert_eq!(lookup.to_embedded(3), None);
+        assert_eq!(lookup.to_embedded(7), Some((1, 0)));
+        assert_eq!(lookup.to_embedded(12), Some((2, 1)));
+        assert_eq!(lookup.get(6), TemplatePosition::Embedded { region: 0, position: (2, 1), host: (2, 1) });
+        assert_eq!(lookup.get(11), TemplatePosition::Embedded { region: 2, position: (1, 1), host: (2, 6) });
+        assert_eq!(lookup.get(13), TemplatePosition::Host((2, 8)));
+        assert_eq!(lookup.to_host(2, 1), 12);
+        assert_eq!(
+            EmbeddedLookup::new(src, [4..7, 6..8]).err(),
+            Some(SegmentError::Unordered { at: 1 })
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{Bias, LineColLookup, Position, SegmentError, TransformMap};
+
+/// A lookup table over text that was escaped on output, such as an HTML-escaped template value,
+/// that reports positions in the escaped output in terms of the text before escaping.
+///
+/// Each escape is recorded as the index of the character escaped and the number of bytes its escape added,
+/// so `&` escaped as `&amp;` adds 4. An output index inside an escape maps to the character it escapes.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let src = "a <b>\nc";
+/// let escaped = "a &lt;b&gt;\nc";
+/// let mut lookup = EscapedLookup::new(src);
+/// lookup.push_escape(2, 3).unwrap();
+/// lookup.push_escape(4, 3).unwrap();
+/// assert_eq!(lookup.get(escaped.find('c').unwrap()), (2, 1));
+/// assert_eq!(lookup.get(escaped.find("lt").unwrap()), (1, 3));
+/// ```
+#[derive(Clone)]
+pub struct EscapedLookup<'source> {
+    lookup: LineColLookup<'source>,
+    map: TransformMap,
+}
+
+impl<'source> EscapedLookup<'source> {
+    /// Creates a lookup table over `src`, which has no escapes recorded yet.
+    pub fn new(src: &'source str) -> Self {
+        Self {
+            lookup: LineColLookup::new(src),
+            map: TransformMap::new(),
+        }
+    }
+
+    /// Creates a lookup table over `src`, recording the escapes of HTML-escaping it:
+    /// `&`, `<`, `>`, `"` and `'` as `&amp;`, `&lt;`, `&gt;`, `&quot;` and `&#39;`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = EscapedLookup::html("\"x\" & y");
+    /// assert_eq!(lookup.to_output(6), 20);
+    /// assert_eq!(lookup.to_source(20), 6);
+    /// ```
+    pub fn html(src: &'source str) -> Self {
+        let mut lookup = Self::new(src);
+        for (index, c) in src.match_indices(['&', '<', '>', '"', '\'']) {
+            let delta = match c {
+                "&" | "'" => 4,
+                "\"" => 5,
+                _ => 3,
+            };
+            // Escapes are found in increasing order, so pushing them cannot fail
+            let _ = lookup.push_escape(index, delta);
+        }
+        lookup
+    }
+
+    /// Records that the character at byte `index` of the source was escaped with `delta` more bytes than it has.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SegmentError::Unordered`] if the character is not after the last one escaped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not the index of a character in the source.
+    pub fn push_escape(&mut self, index: usize, delta: usize) -> Result<(), SegmentError> {
+        let len = self.src()[index..].chars().next().expect("escaped index is past the end of the source").len_utf8();
+        self.map.push(index..index + len, len + delta)
+    }
+
+    /// Gets the source, before escaping.
+    pub fn src(&self) -> &str {
+        self.lookup.src()
+    }
+
+    /// Gets the lookup table over the source.
+    pub fn lookup(&self) -> &LineColLookup<'source> {
+        &self.lookup
+    }
+
+    /// Gets the map from the source to the escaped output.
+    pub fn transform(&self) -> &TransformMap {
+        &self.map
+    }
+
+    /// Maps a byte index of the source to the escaped output.
+    pub fn to_output(&self, index: usize) -> usize {
+        self.map.to_output(index, Bias::Before)
+    }
+
+    /// Maps a byte index of the escaped output back to the source.
+    pub fn to_source(&self, index: usize) -> usize {
+        self.map.to_original(index, Bias::Before)
+    }
+
+    /// Looks up the line and column in the source of a byte index of the escaped output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` maps past the end of the source.
+    pub fn get(&self, index: usize) -> Position {
+        self.lookup.get(self.to_source(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn mapping_html_escapes() {
+        let src = "<ä>\n'&'";
+        let escaped = "&lt;ä&gt;\n&#39;&amp;&#39;";
+        let lookup = EscapedLookup::html(src);
+        for (index, c) in src.char_indices() {
+            let output = lookup.to_output(index);
+            if escaped[output..].starts_with(c) {
+                assert_eq!(lookup.to_source(output), index);
+            } else {
+                assert!(escaped[output..].starts_with('&'));
+                for inside in output..lookup.to_output(index + c.len_utf8()) {
+                    assert_eq!(lookup.to_source(inside), index);
+                }
+            }
+        }
+        assert_eq!(lookup.to_output(src.len()), escaped.len());
+        assert_eq!(lookup.get(escaped.find("amp").unwrap()), (2, 2));
+        assert_eq!(lookup.clone().push_escape(0, 1), Err(SegmentError::Unordered { at: 5 }));
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{LineColLookup, Position};
+
+/// Line/column lookups directly on string slices, for when naming [`LineColLookup`] is more ceremony than needed.
+///
+/// # Example
+/// ```rust
+/// use line_col::LineColExt;
+/// let text = "One\nTwo";
+/// assert_eq!(text.line_col(5), (2, 2));
+/// let lookup = text.line_col_lookup();
+/// assert_eq!(lookup.get(7), (2, 4));
+/// ```
+pub trait LineColExt {
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`line_col`](crate::line_col()) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the string.
+    fn line_col(&self, index: usize) -> Position;
+
+    /// Creates a lookup table over the string, for when many positions are needed.
+    fn line_col_lookup(&self) -> LineColLookup<'_>;
+}
+
+impl LineColExt for str {
+    fn line_col(&self, index: usize) -> Position {
+        crate::line_col(self, index)
+    }
+
+    fn line_col_lookup(&self) -> LineColLookup<'_> {
+        LineColLookup::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn ext_matches_lookup() {
+        let text = String::from("a\nab\n");
+        let lookup = text.line_col_lookup();
+        for i in 0..=text.len() {
+            assert_eq!(text.line_col(i), lookup.get(i));
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{line_lookup::line_range_in, LineLookup, Position, StrLookup};
+use core::{fmt, ops::Range};
+
+/// The ways building a [`FixedLineColLookup`] can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CapacityError {
+    /// The source has more lines than the table can hold.
+    TooManyLines { capacity: usize },
+}
+
+impl fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooManyLines { capacity } => write!(f, "source has more than {} lines", capacity),
+        }
+    }
+}
+
+impl core::error::Error for CapacityError {}
+
+/// A line/column lookup table that stores up to `N` line starts inline, never allocating.
+///
+/// This suits targets without a heap: the whole table can live on the stack or in a `static`.
+/// It is built eagerly, since it has no lock to build it lazily behind.
+///
+/// Construction and lookups are `const fn`s, so tables over sources known at compile time can be baked into a `static`.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = FixedLineColLookup::<4>::new("One\nTwo").unwrap();
+/// assert_eq!(lookup.get(5), (2, 2));
+/// assert!(FixedLineColLookup::<1>::new("One\nTwo").is_err());
+///
+/// static TEMPLATE: FixedLineColLookup<'static, 8> = match FixedLineColLookup::new("{{ a }}\n{{ b }}") {
+///     Ok(lookup) => lookup,
+///     Err(_) => panic!("template has too many lines"),
+/// };
+/// const B: Position = TEMPLATE.get(11);
+/// assert_eq!(B, (2, 4));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedLineColLookup<'source, const N: usize> {
+    src: &'source str,
+    starts: [usize; N],
+    lines: usize,
+}
+
+impl<'source, const N: usize> FixedLineColLookup<'source, N> {
+    /// Creates a lookup table over `src`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CapacityError::TooManyLines`] if `src` has more than `N` lines.
+    pub const fn new(src: &'source str) -> Result<Self, CapacityError> {
+        // Even an empty source has one line
+        if N == 0 {
+            return Err(CapacityError::TooManyLines { capacity: N });
+        }
+        let bytes = src.as_bytes();
+        let mut starts = [0; N];
+        let mut lines = 1;
+        let mut i = 0;
+        // Iterators aren't usable in a `const fn`
+        while i < bytes.len() {
+            if bytes[i] == b'\n' {
+                if lines == N {
+                    return Err(CapacityError::TooManyLines { capacity: N });
+                }
+                starts[lines] = i + 1;
+                lines += 1;
+            }
+            i += 1;
+        }
+        Ok(Self { src, starts, lines })
+    }
+
+    /// Gets a reference to the source string.
+    pub const fn src(&self) -> &'source str {
+        self.src
+    }
+
+    /// Returns the number of lines in the source.
+    pub const fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`LineColLookup::get`](crate::LineColLookup::get) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub const fn get(&self, index: usize) -> Position {
+        if index > self.src.len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        // Binary search for the last line starting at or before `index`
+        let (mut low, mut high) = (0, self.lines);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.starts[mid] <= index {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        (low + 1, index - self.starts[low] + 1)
+    }
+}
+
+impl<const N: usize> LineLookup for FixedLineColLookup<'_, N> {
+    fn get(&self, index: usize) -> Position {
+        FixedLineColLookup::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        self.lines
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range_in(&self.starts[..self.lines], self.src.len(), line.checked_sub(1)?)
+    }
+}
+
+impl<const N: usize> StrLookup for FixedLineColLookup<'_, N> {
+    fn src(&self) -> &str {
+        self.src
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn fixed_matches_default() {
+        let text = "a\nab\n\nabc\n";
+        let fixed = FixedLineColLookup::<5>::new(text).unwrap();
+        let default = LineColLookup::new(text);
+        assert_eq!(fixed.lines(), 5);
+        for i in 0..=text.len() {
+            assert_eq!(fixed.get(i), default.get(i));
+        }
+        let err = FixedLineColLookup::<4>::new(text).unwrap_err();
+        assert_eq!(err, CapacityError::TooManyLines { capacity: 4 });
+        assert!(FixedLineColLookup::<0>::new("").is_err());
+    }
+}
@@ -0,0 +1,496 @@
+use crate::{batch::seek_line, columns::{is_continuation_byte, utf16_len_of_byte}, table::{LineTable, LineTableFormat}};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// The smallest number of bytes scanned whenever the line head table is extended.
+const MIN_SCAN: usize = 4096;
+
+/// The number of source bytes covered by each entry of a dense index.
+pub(crate) const DENSE_BLOCK_LEN: usize = 64;
+
+/// How a [`LineHeads`] table is stored and what it records alongside the line starts.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct HeadsConfig {
+    pub(crate) format: LineTableFormat,
+    /// Whether to record the line containing the start of every [`DENSE_BLOCK_LEN`]-byte block.
+    pub(crate) dense: bool,
+    /// Whether to record the number of `char`s preceding every line start.
+    pub(crate) char_counts: bool,
+    /// Whether to record the number of UTF-16 code units preceding every line start.
+    pub(crate) utf16_counts: bool,
+    /// Whether to record a hash of the content of every line.
+    pub(crate) line_hashes: bool,
+}
+
+/// A unit of text that a [`LineHeads`] table can count the occurrences of before every line start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Unit {
+    Char,
+    Utf16,
+}
+
+impl Unit {
+    /// Returns the number of units in the UTF-8 encoded `bytes`, counting a character cut off at either end by its bytes present.
+    pub(crate) fn count(self, bytes: &[u8]) -> usize {
+        match self {
+            Self::Char => count_chars(bytes),
+            Self::Utf16 => bytes.iter().map(|&b| utf16_len_of_byte(b)).sum(),
+        }
+    }
+}
+
+/// The number of some [`Unit`] preceding every line start of a [`LineHeads`] table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnitStarts {
+    unit: Unit,
+    /// The number of units preceding each line start.
+    pub(crate) starts: Vec<usize>,
+    /// The number of units in the scanned bytes.
+    pub(crate) scanned: usize,
+}
+
+impl UnitStarts {
+    fn new(unit: Unit) -> Self {
+        Self { unit, starts: vec![0], scanned: 0 }
+    }
+
+    /// Records the count at every line start found since the last scan, which ended at `start`.
+    fn extend(&mut self, line_starts: &LineTable, bytes: &[u8], start: usize, end: usize) {
+        let mut pos = start;
+        for line in self.starts.len()..line_starts.len() {
+            let line_start = line_starts.get(line);
+            self.scanned += self.unit.count(&bytes[pos..line_start]);
+            self.starts.push(self.scanned);
+            pos = line_start;
+        }
+        self.scanned += self.unit.count(&bytes[pos..end]);
+    }
+
+    /// Extends the counts with those of a chunk scanned directly after the bytes counted so far.
+    #[cfg(feature = "rayon")]
+    fn append(&mut self, chunk: &UnitStarts) {
+        let scanned = self.scanned;
+        self.starts.extend(chunk.starts[1..].iter().map(|units| scanned + units));
+        self.scanned += chunk.scanned;
+    }
+
+    /// Updates the counts after the old lines `first + 1..=last` were replaced by `inserted` new ones,
+    /// recounting from the start of `first` to the end of the last line touching the edit.
+    fn splice(&mut self, line_starts: &LineTable, bytes: &[u8], first: usize, last: usize, inserted: usize, scanned: usize) {
+        let mut pos = line_starts.get(first);
+        let mut count = self.starts[first];
+        let mut counts = Vec::with_capacity(inserted);
+        for line in first + 1..=first + inserted {
+            let line_start = line_starts.get(line);
+            count += self.unit.count(&bytes[pos..line_start]);
+            counts.push(count);
+            pos = line_start;
+        }
+        let next = first + inserted + 1;
+        let end = if next < line_starts.len() { line_starts.get(next) } else { scanned };
+        count += self.unit.count(&bytes[pos..end]);
+        // Everything after the edit moves by the change in the count up to the end of its last line
+        let old = self.starts.get(last + 1).copied().unwrap_or(self.scanned);
+        self.starts[last + 1..].iter_mut().for_each(|units| *units = *units - old + count);
+        self.scanned = self.scanned - old + count;
+        self.starts.splice(first + 1..last + 1, counts);
+    }
+
+    /// Finds the 0-based line containing the `offset`th unit, along with the number of units preceding it,
+    /// for a table that covers the whole source.
+    pub(crate) fn line_of(&self, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&units| units <= offset) - 1;
+        (line, self.starts[line])
+    }
+}
+
+/// A line head table that may only cover a prefix of its source.
+#[derive(Debug, Clone)]
+pub(crate) struct LineHeads {
+    /// Byte indices at which lines start, in increasing order.
+    pub(crate) starts: LineTable,
+    /// Number of source bytes that have been scanned for newlines so far.
+    /// Every line start up to and including this index is present in `starts`.
+    pub(crate) scanned: usize,
+    /// The 0-based lines whose scanned bytes include non-ASCII characters, in increasing order,
+    /// or `None` if the table was built without checking.
+    non_ascii_lines: Option<Vec<usize>>,
+    /// The 0-based line containing the first byte of each scanned block, if a dense index was requested.
+    dense: Option<Vec<usize>>,
+    /// The number of `char`s preceding each line start in `starts`, if char counts were requested.
+    char_starts: Option<UnitStarts>,
+    /// The number of UTF-16 code units preceding each line start in `starts`, if UTF-16 counts were requested.
+    utf16_starts: Option<UnitStarts>,
+    /// The [`line_hash`] of every line whose terminating `'\n'` has been scanned, if line hashes were requested.
+    hashes: Option<Vec<u64>>,
+    pub(crate) config: HeadsConfig,
+}
+
+impl LineHeads {
+    /// Creates an empty table that has not scanned any of its source yet.
+    pub(crate) fn new(config: HeadsConfig) -> Self {
+        Self {
+            starts: LineTable::new(config.format),
+            scanned: 0,
+            non_ascii_lines: Some(Vec::new()),
+            dense: if config.dense { Some(Vec::new()) } else { None },
+            char_starts: if config.char_counts { Some(UnitStarts::new(Unit::Char)) } else { None },
+            utf16_starts: if config.utf16_counts { Some(UnitStarts::new(Unit::Utf16)) } else { None },
+            hashes: if config.line_hashes { Some(Vec::new()) } else { None },
+            config,
+        }
+    }
+
+    /// Creates a table over the whole of `src` from the starts of all its lines after the first.
+    ///
+    /// Everything else is recorded just as [`LineHeads::scan_through`] would, so only the newline scan is skipped.
+    pub(crate) fn complete(config: HeadsConfig, src: &str, starts: impl IntoIterator<Item = usize>) -> Self {
+        let mut heads = Self::new(config);
+        heads.starts.extend(starts);
+        heads.record_scanned(src.as_bytes(), 0, src.len());
+        heads
+    }
+
+    /// Creates a table over all of `bytes`, one chunk of a larger source, as if the chunk were a whole source.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn scan_chunk(config: HeadsConfig, bytes: &[u8]) -> Self {
+        let mut heads = Self::new(config);
+        push_line_starts(&mut heads.starts, bytes, 0);
+        heads.record_scanned(bytes, 0, bytes.len());
+        heads
+    }
+
+    /// Extends the table with `chunk`, which was built by [`LineHeads::scan_chunk`] over the bytes of `src` directly following the ones covered so far.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn append(&mut self, src: &[u8], chunk: &LineHeads) {
+        let base = self.scanned;
+        // The chunk's first line carries on from this table's last one
+        let first = self.starts.len() - 1;
+        self.starts.extend((1..chunk.starts.len()).map(|line| base + chunk.starts.get(line)));
+        if let (Some(non_ascii_lines), Some(chunk_lines)) = (&mut self.non_ascii_lines, &chunk.non_ascii_lines) {
+            for line in chunk_lines.iter().map(|line| first + line) {
+                if non_ascii_lines.last() != Some(&line) {
+                    non_ascii_lines.push(line);
+                }
+            }
+        }
+        let chunk_units = chunk.char_starts.iter().chain(chunk.utf16_starts.iter());
+        for (units, chunk_units) in self.char_starts.iter_mut().chain(self.utf16_starts.iter_mut()).zip(chunk_units) {
+            units.append(chunk_units);
+        }
+        if let (Some(hashes), Some(chunk_hashes)) = (&mut self.hashes, &chunk.hashes) {
+            // The chunk only saw the end of the line it starts in, so that line is hashed again in full
+            if !chunk_hashes.is_empty() {
+                hashes.push(line_hash(&src[self.starts.get(first)..self.starts.get(first + 1) - 1]));
+                hashes.extend_from_slice(&chunk_hashes[1..]);
+            }
+        }
+        self.scanned = base + chunk.scanned;
+        self.extend_dense();
+    }
+
+    /// Finds the 0-based line containing `index`, which must be covered by the table,
+    /// searching outwards from the line `hint` unless a dense index can answer directly.
+    pub(crate) fn line_of_near(&self, hint: usize, index: usize) -> usize {
+        match self.dense.as_ref().and_then(|dense| dense.get(index / DENSE_BLOCK_LEN)) {
+            // At most one block's worth of lines needs to be skipped
+            Some(&line) => seek_line(&self.starts, line, index),
+            None => self.starts.line_of_near(hint, index),
+        }
+    }
+
+    /// Returns the number of heap bytes allocated by the table.
+    pub(crate) fn memory_usage(&self) -> usize {
+        self.starts.memory_usage()
+            + self.non_ascii_lines.as_ref().map_or(0, Vec::capacity) * core::mem::size_of::<usize>()
+            + self.dense.as_ref().map_or(0, Vec::capacity) * core::mem::size_of::<usize>()
+            + self.unit_starts().map(|units| units.starts.capacity()).sum::<usize>() * core::mem::size_of::<usize>()
+            + self.hashes.as_ref().map_or(0, Vec::capacity) * core::mem::size_of::<u64>()
+    }
+
+    /// Releases any excess capacity held by the table.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.starts.shrink_to_fit();
+        self.non_ascii_lines
+            .iter_mut()
+            .chain(self.dense.iter_mut())
+            .chain(self.char_starts.iter_mut().map(|units| &mut units.starts))
+            .chain(self.utf16_starts.iter_mut().map(|units| &mut units.starts))
+            .for_each(Vec::shrink_to_fit);
+        self.hashes.iter_mut().for_each(Vec::shrink_to_fit);
+    }
+
+    /// Records the line of every block whose first byte has been scanned.
+    fn extend_dense(&mut self) {
+        if let Some(dense) = &mut self.dense {
+            let mut line = dense.last().copied().unwrap_or(0);
+            while dense.len() * DENSE_BLOCK_LEN <= self.scanned {
+                line = seek_line(&self.starts, line, dense.len() * DENSE_BLOCK_LEN);
+                dense.push(line);
+            }
+        }
+    }
+
+    /// Returns the number of `unit`s preceding the start of the 0-based `line`, if they were recorded.
+    pub(crate) fn unit_start(&self, unit: Unit, line: usize) -> Option<usize> {
+        self.unit_starts_of(unit).map(|starts| starts.starts[line])
+    }
+
+    /// Gets the counts of `unit` preceding every line start, if they were recorded.
+    pub(crate) fn unit_starts_of(&self, unit: Unit) -> Option<&UnitStarts> {
+        match unit {
+            Unit::Char => self.char_starts.as_ref(),
+            Unit::Utf16 => self.utf16_starts.as_ref(),
+        }
+    }
+
+    fn unit_starts(&self) -> impl Iterator<Item = &UnitStarts> {
+        self.char_starts.iter().chain(self.utf16_starts.iter())
+    }
+
+    /// Returns the recorded [`line_hash`] of the 0-based `line`, if line hashes were requested and its end has been scanned.
+    pub(crate) fn recorded_hash(&self, line: usize) -> Option<u64> {
+        self.hashes.as_ref()?.get(line).copied()
+    }
+
+    /// Hashes every line whose terminating `'\n'` was found since the hashes were last extended.
+    fn extend_hashes(&mut self, bytes: &[u8]) {
+        if let Some(hashes) = &mut self.hashes {
+            for line in hashes.len()..self.starts.len() - 1 {
+                hashes.push(line_hash(&bytes[self.starts.get(line)..self.starts.get(line + 1) - 1]));
+            }
+        }
+    }
+
+    /// Returns `true` if the scanned part of the 0-based `line` is known to be pure ASCII.
+    pub(crate) fn is_ascii_line(&self, line: usize) -> bool {
+        self.non_ascii_lines.as_ref().is_some_and(|lines| lines.binary_search(&line).is_err())
+    }
+
+    /// Returns `true` if every line start up to `index` is known.
+    pub(crate) fn covers(&self, index: usize) -> bool {
+        self.scanned >= index
+    }
+
+    /// Extends the table so that it covers at least `index`.
+    ///
+    /// The table grows geometrically so that repeated small extensions stay linear overall.
+    pub(crate) fn scan_through(&mut self, src: &str, index: usize) {
+        if self.covers(index) {
+            return;
+        }
+        let end = index.max(self.scanned * 2).max(self.scanned + MIN_SCAN).min(src.len());
+        let start = self.scanned;
+        let bytes = src.as_bytes();
+        push_line_starts(&mut self.starts, &bytes[start..end], start);
+        self.record_scanned(bytes, start, end);
+    }
+
+    /// Records what the table tracks about the newly scanned `bytes[start..end]`, whose line starts have already been added.
+    fn record_scanned(&mut self, bytes: &[u8], start: usize, end: usize) {
+        if let Some(non_ascii_lines) = &mut self.non_ascii_lines {
+            if !bytes[start..end].is_ascii() {
+                let mut i = start;
+                while let Some(offset) = bytes[i..end].iter().position(|b| !b.is_ascii()) {
+                    let line = self.starts.line_of(i + offset);
+                    if non_ascii_lines.last() != Some(&line) {
+                        non_ascii_lines.push(line);
+                    }
+                    // The rest of this line doesn't need checking
+                    if line + 1 == self.starts.len() {
+                        break;
+                    }
+                    i = self.starts.get(line + 1);
+                }
+            }
+        }
+        for units in self.char_starts.iter_mut().chain(self.utf16_starts.iter_mut()) {
+            units.extend(&self.starts, bytes, start, end);
+        }
+        self.extend_hashes(bytes);
+        self.scanned = end;
+        self.extend_dense();
+    }
+
+    /// Updates the table after the bytes in `replaced` were replaced by `new_len` bytes of `src`.
+    ///
+    /// Line starts after the edit are moved rather than rescanned, and only the lines touching the edit are reexamined.
+    /// The table must have covered the whole replaced range.
+    pub(crate) fn splice(&mut self, src: &str, replaced: Range<usize>, new_len: usize) {
+        let Range { start, end } = replaced;
+        debug_assert!(self.covers(end));
+        let bytes = src.as_bytes();
+        // Lines up to `first` are unchanged, and the starts of the lines after it up to `last` were replaced
+        let first = self.starts.line_of(start);
+        let last = self.starts.line_of(end);
+        let mut inserted = Vec::new();
+        push_line_starts(&mut inserted, &bytes[start..start + new_len], start);
+        self.starts.splice(first + 1..last + 1, &inserted, new_len as isize - (end - start) as isize);
+        self.scanned = self.scanned - end + start + new_len;
+
+        if let Some(non_ascii_lines) = &mut self.non_ascii_lines {
+            let kept = non_ascii_lines.partition_point(|&line| line < first);
+            let moved = non_ascii_lines.partition_point(|&line| line <= last);
+            let moved: Vec<_> = non_ascii_lines[moved..].iter().map(|line| line - (last - first) + inserted.len()).collect();
+            non_ascii_lines.truncate(kept);
+            for line in first..=first + inserted.len() {
+                let line_start = self.starts.get(line);
+                let line_end = if line + 1 < self.starts.len() { self.starts.get(line + 1) } else { self.scanned };
+                if !bytes[line_start..line_end].is_ascii() {
+                    non_ascii_lines.push(line);
+                }
+            }
+            non_ascii_lines.extend(moved);
+        }
+
+        for units in self.char_starts.iter_mut().chain(self.utf16_starts.iter_mut()) {
+            units.splice(&self.starts, bytes, first, last, inserted.len(), self.scanned);
+        }
+
+        if let Some(hashes) = &mut self.hashes {
+            // Only lines whose end has been scanned are hashed, so the last line may have no hash before or after the edit
+            let starts = &self.starts;
+            let rehashed = (first..=first + inserted.len()).take_while(|&line| line + 1 < starts.len()).map(|line| {
+                line_hash(&bytes[starts.get(line)..starts.get(line + 1) - 1])
+            });
+            let replaced = first..(last + 1).min(hashes.len());
+            hashes.splice(replaced, rehashed.collect::<Vec<_>>());
+        }
+
+        if let Some(dense) = &mut self.dense {
+            // Blocks starting before the edit still begin in the same lines
+            dense.truncate(start / DENSE_BLOCK_LEN + 1);
+        }
+        self.extend_dense();
+    }
+}
+
+/// Hashes `bytes` with 64-bit FNV-1a.
+pub(crate) fn fnv1a<'a>(bytes: impl IntoIterator<Item = &'a u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ b as u64).wrapping_mul(0x100_0000_01b3))
+}
+
+/// Hashes the content of a line, excluding its terminating `'\n'`.
+pub(crate) fn line_hash(line: &[u8]) -> u64 {
+    fnv1a(line)
+}
+
+/// Returns the number of `char`s that begin in `bytes`.
+pub(crate) fn count_chars(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| !is_continuation_byte(b)).count()
+}
+
+/// Pushes `base + i + 1` onto `starts` for the index `i` of every `'\n'` byte in `bytes`.
+#[cfg(feature = "simd")]
+pub(crate) fn push_line_starts(starts: &mut impl Extend<usize>, bytes: &[u8], base: usize) {
+    use core::simd::{cmp::SimdPartialEq, u8x64};
+
+    let newline = u8x64::splat(b'\n');
+    let mut chunks = bytes.chunks_exact(64);
+    let mut offset = base;
+    for chunk in &mut chunks {
+        let mut mask = u8x64::from_slice(chunk).simd_eq(newline).to_bitmask();
+        while mask != 0 {
+            starts.extend(Some(offset + mask.trailing_zeros() as usize + 1));
+            mask &= mask - 1;
+        }
+        offset += 64;
+    }
+    push_line_starts_scalar(starts, chunks.remainder(), offset);
+}
+
+/// Pushes `base + i + 1` onto `starts` for the index `i` of every `'\n'` byte in `bytes`.
+#[cfg(not(feature = "simd"))]
+pub(crate) fn push_line_starts(starts: &mut impl Extend<usize>, bytes: &[u8], base: usize) {
+    push_line_starts_scalar(starts, bytes, base)
+}
+
+#[cfg(feature = "memchr")]
+fn push_line_starts_scalar(starts: &mut impl Extend<usize>, bytes: &[u8], base: usize) {
+    starts.extend(memchr::memchr_iter(b'\n', bytes).map(|i| base + i + 1));
+}
+
+#[cfg(not(feature = "memchr"))]
+fn push_line_starts_scalar(starts: &mut impl Extend<usize>, bytes: &[u8], base: usize) {
+    starts.extend(bytes.iter().enumerate().filter(|(_, &b)| b == b'\n').map(|(i, _)| base + i + 1));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn tracks_ascii_lines() {
+        let text = format!("abc\né\ndef\n{}ü\nghi", "x".repeat(2 * MIN_SCAN));
+        let mut heads = LineHeads::new(HeadsConfig::default());
+        heads.scan_through(&text, 1);
+        heads.scan_through(&text, text.len());
+        assert_eq!(heads.non_ascii_lines, Some(vec![1, 3]));
+        assert!(heads.is_ascii_line(0) && heads.is_ascii_line(2) && heads.is_ascii_line(4));
+        let complete = LineHeads::complete(heads.config, &text, heads.starts.to_vec().into_iter().skip(1));
+        assert_eq!(complete.non_ascii_lines, heads.non_ascii_lines);
+    }
+
+    #[test]
+    fn dense_index_matches_table() {
+        let text = "ab\n\n\nabcdefgh\n".repeat(500) + &"x".repeat(300);
+        let mut heads = LineHeads::new(HeadsConfig { dense: true, ..HeadsConfig::default() });
+        heads.scan_through(&text, text.len());
+        assert_eq!(heads.dense.as_ref().unwrap().len(), text.len() / DENSE_BLOCK_LEN + 1);
+        for index in 0..=text.len() {
+            assert_eq!(heads.line_of_near(0, index), heads.starts.line_of(index));
+        }
+    }
+
+    #[test]
+    fn char_counts_across_scans() {
+        let text = format!("añb\n\n{}é\nü", "x".repeat(2 * MIN_SCAN));
+        let mut heads = LineHeads::new(HeadsConfig { char_counts: true, ..HeadsConfig::default() });
+        heads.scan_through(&text, 1);
+        heads.scan_through(&text, text.len());
+        for line in 0..heads.starts.len() {
+            let start = heads.starts.get(line);
+            assert_eq!(heads.unit_start(Unit::Char, line), Some(text[..start].chars().count()));
+        }
+        assert_eq!(heads.char_starts.as_ref().unwrap().scanned, text.chars().count());
+        let complete = LineHeads::complete(heads.config, &text, heads.starts.to_vec().into_iter().skip(1));
+        assert_eq!(complete.char_starts, heads.char_starts);
+    }
+
+    #[test]
+    fn splice_matches_rescan() {
+        let text = "ab\nü\n".repeat(40);
+        let edits = [(5..9, "x\né\n\n"), (0..text.len() - 1, ""), (71..71, "\n"), (3..150, "y")];
+        for format in [LineTableFormat::Usize, LineTableFormat::U32, LineTableFormat::Delta, LineTableFormat::Paged] {
+            let config = HeadsConfig { format, dense: true, char_counts: true, utf16_counts: true, line_hashes: true };
+            for (replaced, new_text) in edits.iter().cloned() {
+                let mut heads = LineHeads::new(config);
+                heads.scan_through(&text, text.len());
+                let mut edited = text.clone();
+                edited.replace_range(replaced.clone(), new_text);
+                heads.splice(&edited, replaced, new_text.len());
+                let mut rescanned = LineHeads::new(config);
+                rescanned.scan_through(&edited, edited.len());
+                assert_eq!(heads.starts.to_vec(), rescanned.starts.to_vec());
+                assert_eq!(heads.scanned, edited.len());
+                assert_eq!(heads.non_ascii_lines, rescanned.non_ascii_lines);
+                assert_eq!(heads.dense, rescanned.dense);
+                assert_eq!(heads.char_starts, rescanned.char_starts);
+                assert_eq!(heads.utf16_starts, rescanned.utf16_starts);
+                assert_eq!(heads.hashes, rescanned.hashes);
+            }
+        }
+    }
+
+    #[test]
+    fn push_line_starts_across_chunks() {
+        let mut text = "x".repeat(200);
+        for i in [0, 63, 64, 65, 127, 150, 199] {
+            text.replace_range(i..i + 1, "\n");
+        }
+        let mut starts: Vec<usize> = Vec::new();
+        push_line_starts(&mut starts, text.as_bytes(), 10);
+        assert_eq!(starts, [11, 74, 75, 76, 138, 161, 210]);
+    }
+}
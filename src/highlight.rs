@@ -0,0 +1,118 @@
+use crate::{LineColLookup, StrLookup, TextSegment};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A piece of one line covered by the same set of highlight labels, as split by [`LineColLookup::highlight_runs`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct HighlightRun<'a, T> {
+    pub segment: TextSegment<'a>,
+    /// The labels of every span covering the run, in the order the spans were given.
+    pub labels: Vec<T>,
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Splits labeled byte ranges, which may overlap, such as syntax highlighting tokens layered with diagnostics,
+    /// into runs that each lie on one line and are covered by the same labels throughout.
+    ///
+    /// Runs are returned in source order. Parts of the source covered by no span, and the `'\n'` ending each line,
+    /// belong to no run, and neighbouring pieces of a line with equal labels are merged into one run.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any range is out of bounds of the input `&str`, or either of its ends is inside a character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("let x = 1;\nx");
+    /// let runs = lookup.highlight_runs([(0..3, "keyword"), (2..12, "error")]);
+    /// let runs: Vec<_> = runs.iter().map(|run| (run.segment.text, run.labels.as_slice())).collect();
+    /// assert_eq!(runs, [("le", &["keyword"][..]), ("t", &["keyword", "error"]), (" x = 1;", &["error"]), ("x", &["error"])]);
+    /// ```
+    pub fn highlight_runs<T: Clone + PartialEq>(&self, spans: impl IntoIterator<Item = (Range<usize>, T)>) -> Vec<HighlightRun<'_, T>> {
+        highlight_runs(self, spans)
+    }
+}
+
+/// Splits labeled byte ranges of any [`StrLookup`] into runs that each lie on one line and are covered by the same labels throughout,
+/// as [`LineColLookup::highlight_runs`] does.
+pub fn highlight_runs<L, T>(lookup: &L, spans: impl IntoIterator<Item = (Range<usize>, T)>) -> Vec<HighlightRun<'_, T>>
+where
+    L: StrLookup + ?Sized,
+    T: Clone + PartialEq,
+{
+    let labels: Vec<(Range<usize>, T)> = spans.into_iter().collect();
+    // Each span opens and closes once per line it covers; closes sort before opens at the same index
+    let mut events = Vec::new();
+    for (span, (range, _)) in labels.iter().enumerate() {
+        let (mut line, _) = lookup.get(range.start);
+        while let Some(line_range) = lookup.line_range(line).filter(|line_range| line_range.start <= range.end) {
+            let piece = range.start.max(line_range.start)..range.end.min(line_range.end);
+            if !piece.is_empty() {
+                events.push((piece.start, true, span));
+                events.push((piece.end, false, span));
+            }
+            line += 1;
+        }
+    }
+    events.sort_unstable_by_key(|&(index, opens, _)| (index, opens));
+
+    let segment = |range: Range<usize>| TextSegment {
+        text: &lookup.src()[range.clone()],
+        start: lookup.get(range.start),
+        end: lookup.get(range.end),
+        range,
+    };
+    let mut runs: Vec<HighlightRun<'_, T>> = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+    for (i, &(index, opens, span)) in events.iter().enumerate() {
+        let at = active.partition_point(|&other| other < span);
+        if opens {
+            active.insert(at, span);
+        } else {
+            active.remove(at);
+        }
+        let end = match events.get(i + 1) {
+            Some(&(next, _, _)) if next > index && !active.is_empty() => next,
+            _ => continue,
+        };
+        let run_labels: Vec<T> = active.iter().map(|&span| labels[span].1.clone()).collect();
+        match runs.last_mut() {
+            Some(run) if run.segment.range.end == index && run.labels == run_labels => {
+                *run = HighlightRun { segment: segment(run.segment.range.start..end), labels: run_labels };
+            }
+            _ => runs.push(HighlightRun { segment: segment(index..end), labels: run_labels }),
+        }
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn splitting_highlight_runs() {
+        let text = "fn main() {\n    call(é);\n}";
+        let lookup = LineColLookup::new(text);
+        let spans = [(0..2, 'k'), (3..7, 'f'), (3..5, 'f'), (16..20, 'f'), (10..text.len(), 'b'), (21..23, 's')];
+        let runs = lookup.highlight_runs(spans.iter().cloned());
+        let summary: Vec<_> = runs.iter().map(|run| (run.segment.range.clone(), run.labels.iter().collect::<String>())).collect();
+        assert_eq!(summary, [
+            (0..2, "k".to_string()),
+            (3..5, "ff".to_string()),
+            (5..7, "f".to_string()),
+            (10..11, "b".to_string()),
+            (12..16, "b".to_string()),
+            (16..20, "fb".to_string()),
+            (20..21, "b".to_string()),
+            (21..23, "bs".to_string()),
+            (23..25, "b".to_string()),
+            (26..27, "b".to_string()),
+        ]);
+        assert_eq!((runs[8].segment.start, runs[8].segment.end), ((2, 12), (2, 14)));
+        assert!(lookup.highlight_runs(vec![(4..4, ())]).is_empty());
+        let sharded = ShardedLineColLookup::with_shard_len(text, 8);
+        assert_eq!(highlight_runs(&sharded as &dyn StrLookup, spans.iter().cloned()), runs);
+    }
+}
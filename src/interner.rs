@@ -0,0 +1,104 @@
+use crate::{LineColLookup, Position};
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::{convert::TryFrom, ops::Range};
+
+/// A compact handle for a byte range interned by a [`SpanInterner`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpanId(u32);
+
+impl SpanId {
+    /// Returns the raw index of the span in its interner.
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+}
+
+/// Deduplicates byte ranges into 4-byte [`SpanId`]s, so that syntax trees can store a handle instead of a whole range.
+///
+/// Ids are only meaningful to the interner that created them.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = LineColLookup::new("let x = 1;\nx");
+/// let mut spans = SpanInterner::new();
+/// let id = spans.intern(11..12);
+/// assert_eq!(spans.intern(11..12), id);
+/// assert_eq!(spans.span(id), 11..12);
+/// assert_eq!(spans.positions(id, &lookup), ((2, 1), (2, 2)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SpanInterner {
+    spans: Vec<(usize, usize)>,
+    ids: BTreeMap<(usize, usize), SpanId>,
+}
+
+impl SpanInterner {
+    /// Creates an empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `range`, interning it first if it is new.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than `u32::MAX` distinct spans are interned.
+    pub fn intern(&mut self, range: Range<usize>) -> SpanId {
+        let key = (range.start, range.end);
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = SpanId(u32::try_from(self.spans.len()).expect("too many spans to intern"));
+        self.spans.push(key);
+        self.ids.insert(key, id);
+        id
+    }
+
+    /// Returns the byte range of the span `id`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was not created by this interner.
+    pub fn span(&self, id: SpanId) -> Range<usize> {
+        let (start, end) = self.spans[id.0 as usize];
+        start..end
+    }
+
+    /// Looks up the 1-based line and column numbers of the start and end of the span `id` in `lookup`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` was not created by this interner, or the span lies outside `lookup`'s source.
+    pub fn positions<S: AsRef<str>>(&self, id: SpanId, lookup: &LineColLookup<'_, S>) -> (Position, Position) {
+        let span = self.span(id);
+        (lookup.get(span.start), lookup.get(span.end))
+    }
+
+    /// Returns the number of distinct spans interned.
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns `true` if no spans have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn interning_deduplicates() {
+        let mut spans = SpanInterner::new();
+        let a = spans.intern(0..3);
+        let b = spans.intern(3..4);
+        assert_ne!(a, b);
+        assert_eq!(spans.intern(0..3), a);
+        assert_eq!((a.as_u32(), b.as_u32()), (0, 1));
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans.span(b), 3..4);
+    }
+}
@@ -0,0 +1,714 @@
+use crate::Position;
+use alloc::{string::String, vec::Vec};
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+
+/// A position in an original source of a [JavaScript source map](https://tc39.es/ecma426/):
+/// one of the map's sources, 1-based line and column numbers within it, and optionally one of the map's names.
+///
+/// Columns count UTF-16 code units, as [`LineColLookup::get_by_utf16`](crate::LineColLookup::get_by_utf16) returns them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OriginalPosition {
+    /// The index of the source, as returned by [`JsSourceMapBuilder::add_source`].
+    pub source: u32,
+    /// The 1-based line in the source.
+    pub line: usize,
+    /// The 1-based column in the source, counting UTF-16 code units.
+    pub col: usize,
+    /// The index of the original name of the symbol at this position, as returned by [`JsSourceMapBuilder::add_name`].
+    pub name: Option<u32>,
+}
+
+/// Collects mappings from positions in generated code to positions in its original sources,
+/// and serializes them as a version 3 JavaScript source map.
+///
+/// Each mapping marks where a span of generated code starts; it extends to the next mapping.
+/// Positions are 1-based lines and columns counting UTF-16 code units, as [`LineColLookup::get_by_utf16`](crate::LineColLookup::get_by_utf16)
+/// returns them; they are converted to the format's 0-based counts when serialized.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let original = "let answer = 42";
+/// let generated = "var answer=42;";
+/// let (original_lookup, generated_lookup) = (LineColLookup::new(original), LineColLookup::new(generated));
+/// let mut builder = JsSourceMapBuilder::new().file("out.js");
+/// let source = builder.add_source("in.ts", Some(original));
+/// let name = builder.add_name("answer");
+/// for (generated_index, original_index, name) in [(0, 0, None), (4, 4, Some(name)), (11, 13, None)] {
+///     let (line, col) = original_lookup.get_by_utf16(original_index);
+///     builder.add_mapping(generated_lookup.get_by_utf16(generated_index), Some(OriginalPosition { source, line, col, name }));
+/// }
+/// assert_eq!(
+///     builder.to_json(),
+///     r#"{"version":3,"file":"out.js","sources":["in.ts"],"sourcesContent":["let answer = 42"],"names":["answer"],"mappings":"AAAA,IAAIA,OAAS"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JsSourceMapBuilder {
+    file: Option<String>,
+    source_root: Option<String>,
+    sources: Vec<String>,
+    sources_content: Vec<Option<String>>,
+    names: Vec<String>,
+    mappings: Vec<(Position, Option<OriginalPosition>)>,
+}
+
+impl JsSourceMapBuilder {
+    /// Creates a builder with no sources or mappings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the generated file the map describes.
+    pub fn file(mut self, file: impl Into<String>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Sets the path that the names of the sources are relative to.
+    pub fn source_root(mut self, source_root: impl Into<String>) -> Self {
+        self.source_root = Some(source_root.into());
+        self
+    }
+
+    /// Adds an original source, optionally embedding its text in the map, and returns its index.
+    /// Adding a source with the same name again returns the same index, filling in its text if it was missing.
+    pub fn add_source(&mut self, name: &str, content: Option<&str>) -> u32 {
+        match self.sources.iter().position(|source| source == name) {
+            Some(index) => {
+                if self.sources_content[index].is_none() {
+                    self.sources_content[index] = content.map(String::from);
+                }
+                index as u32
+            }
+            None => {
+                self.sources.push(String::from(name));
+                self.sources_content.push(content.map(String::from));
+                (self.sources.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Adds an original symbol name and returns its index. Adding the same name again returns the same index.
+    pub fn add_name(&mut self, name: &str) -> u32 {
+        match self.names.iter().position(|existing| existing == name) {
+            Some(index) => index as u32,
+            None => {
+                self.names.push(String::from(name));
+                (self.names.len() - 1) as u32
+            }
+        }
+    }
+
+    /// Maps the generated code starting at `generated` to `original`, or to no original source if `None`.
+    ///
+    /// Mappings may be added in any order.
+    pub fn add_mapping(&mut self, generated: Position, original: Option<OriginalPosition>) {
+        self.mappings.push((generated, original));
+    }
+
+    /// Serializes the map as JSON.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("{\"version\":3");
+        if let Some(file) = &self.file {
+            json.push_str(",\"file\":");
+            push_json_string(&mut json, file);
+        }
+        if let Some(source_root) = &self.source_root {
+            json.push_str(",\"sourceRoot\":");
+            push_json_string(&mut json, source_root);
+        }
+        json.push_str(",\"sources\":");
+        push_json_array(&mut json, self.sources.iter().map(Some));
+        if self.sources_content.iter().any(Option::is_some) {
+            json.push_str(",\"sourcesContent\":");
+            push_json_array(&mut json, self.sources_content.iter().map(Option::as_ref));
+        }
+        json.push_str(",\"names\":");
+        push_json_array(&mut json, self.names.iter().map(Some));
+        json.push_str(",\"mappings\":\"");
+        json.push_str(&self.encode_mappings());
+        json.push_str("\"}");
+        json
+    }
+
+    /// Encodes the mappings in the format's `mappings` syntax: lines separated by `;`, and segments by `,`,
+    /// each holding base64 VLQ deltas from the previous segment.
+    fn encode_mappings(&self) -> String {
+        let mut mappings: Vec<_> = self.mappings.iter().collect();
+        mappings.sort_by_key(|(generated, _)| *generated);
+        let mut out = String::new();
+        let mut line = 1;
+        let mut prev_col = 0;
+        let (mut prev_source, mut prev_line, mut prev_col_original, mut prev_name) = (0, 0, 0, 0);
+        for (i, &&((generated_line, generated_col), original)) in mappings.iter().enumerate() {
+            if generated_line > line {
+                for _ in line..generated_line {
+                    out.push(';');
+                }
+                line = generated_line;
+                prev_col = 0;
+            } else if i > 0 {
+                out.push(',');
+            }
+            let col = generated_col as i64 - 1;
+            push_vlq(&mut out, col - prev_col);
+            prev_col = col;
+            if let Some(original) = original {
+                push_vlq(&mut out, original.source as i64 - prev_source);
+                push_vlq(&mut out, original.line as i64 - 1 - prev_line);
+                push_vlq(&mut out, original.col as i64 - 1 - prev_col_original);
+                prev_source = original.source as i64;
+                prev_line = original.line as i64 - 1;
+                prev_col_original = original.col as i64 - 1;
+                if let Some(name) = original.name {
+                    push_vlq(&mut out, name as i64 - prev_name);
+                    prev_name = name as i64;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The ways parsing a JavaScript source map with [`JsSourceMap::parse`] can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SourceMapError {
+    /// The input is not valid JSON, or not a JSON object; the problem was found at byte `at`.
+    InvalidJson { at: usize },
+    /// The map is not version 3 of the format.
+    UnsupportedVersion,
+    /// A required field is missing or has the wrong type.
+    InvalidField { field: &'static str },
+    /// The `mappings` string is malformed at byte `at`, or refers to a source or name that doesn't exist.
+    InvalidMappings { at: usize },
+}
+
+impl fmt::Display for SourceMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidJson { at } => write!(f, "source map is not a valid JSON object at byte {}", at),
+            Self::UnsupportedVersion => write!(f, "only version 3 source maps are supported"),
+            Self::InvalidField { field } => write!(f, "source map field `{}` is missing or invalid", field),
+            Self::InvalidMappings { at } => write!(f, "source map mappings are invalid at byte {}", at),
+        }
+    }
+}
+
+impl core::error::Error for SourceMapError {}
+
+/// A parsed version 3 JavaScript source map, for finding the original positions of generated code,
+/// such as the frames of a minified stack trace.
+///
+/// Positions are 1-based lines and columns counting UTF-16 code units, as [`LineColLookup::get_by_utf16`](crate::LineColLookup::get_by_utf16)
+/// returns them. Index maps, which split the map into `sections`, aren't supported.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let map = JsSourceMap::parse(r#"{"version":3,"sources":["in.ts"],"names":["answer"],"mappings":"AAAA,IAAIA,OAAS"}"#).unwrap();
+/// let generated = LineColLookup::new("var answer=42;");
+/// let original = map.original_location(generated.get_by_utf16(6)).unwrap();
+/// assert_eq!((original.source, original.line, original.col, original.name), (Some("in.ts"), 1, 5, Some("answer")));
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsSourceMap {
+    file: Option<String>,
+    source_root: Option<String>,
+    sources: Vec<Option<String>>,
+    sources_content: Vec<Option<String>>,
+    names: Vec<String>,
+    /// The decoded segments, sorted by generated position.
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Segment {
+    /// The 0-based generated line and column.
+    generated: (usize, usize),
+    /// The source index, 0-based line and column, and name index of the original position, if any.
+    original: Option<(usize, usize, usize, Option<usize>)>,
+}
+
+/// A position in an original source found by [`JsSourceMap::original_location`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OriginalLocation<'map> {
+    /// The index of the source in the map's `sources`.
+    pub source_index: usize,
+    /// The name of the source, unless the map left it `null`.
+    pub source: Option<&'map str>,
+    /// The 1-based line in the source.
+    pub line: usize,
+    /// The 1-based column in the source, counting UTF-16 code units.
+    pub col: usize,
+    /// The original name of the symbol at this position, if the map recorded one.
+    pub name: Option<&'map str>,
+}
+
+impl JsSourceMap {
+    /// Parses a source map from its JSON text.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SourceMapError`] if the text is not a valid version 3 source map.
+    pub fn parse(json: &str) -> Result<Self, SourceMapError> {
+        let mut parser = JsonParser { bytes: json.as_bytes(), pos: 0 };
+        let value = parser.value()?;
+        parser.whitespace();
+        if parser.pos != json.len() {
+            return Err(SourceMapError::InvalidJson { at: parser.pos });
+        }
+        let fields = match value {
+            Json::Object(fields) => fields,
+            _ => return Err(SourceMapError::InvalidJson { at: 0 }),
+        };
+        let field = |name: &str| fields.iter().find(|(key, _)| key == name).map(|(_, value)| value);
+        match field("version") {
+            Some(Json::Number(version)) if *version == 3.0 => {}
+            _ => return Err(SourceMapError::UnsupportedVersion),
+        }
+        let string = |name: &'static str| match field(name) {
+            None | Some(Json::Null) => Ok(None),
+            Some(Json::String(value)) => Ok(Some(value.clone())),
+            Some(_) => Err(SourceMapError::InvalidField { field: name }),
+        };
+        let strings = |name: &'static str| match field(name) {
+            None | Some(Json::Null) => Ok(Vec::new()),
+            Some(Json::Array(items)) => items
+                .iter()
+                .map(|item| match item {
+                    Json::Null => Ok(None),
+                    Json::String(value) => Ok(Some(value.clone())),
+                    _ => Err(SourceMapError::InvalidField { field: name }),
+                })
+                .collect(),
+            Some(_) => Err(SourceMapError::InvalidField { field: name }),
+        };
+        let sources = match field("sources") {
+            Some(Json::Array(_)) => strings("sources")?,
+            _ => return Err(SourceMapError::InvalidField { field: "sources" }),
+        };
+        let names = strings("names")?
+            .into_iter()
+            .map(|name| name.ok_or(SourceMapError::InvalidField { field: "names" }))
+            .collect::<Result<_, _>>()?;
+        let mappings = string("mappings")?.ok_or(SourceMapError::InvalidField { field: "mappings" })?;
+        let mut map = Self {
+            file: string("file")?,
+            source_root: string("sourceRoot")?,
+            sources_content: strings("sourcesContent")?,
+            sources,
+            names,
+            segments: Vec::new(),
+        };
+        map.segments = map.decode_mappings(&mappings)?;
+        Ok(map)
+    }
+
+    /// Gets the name of the generated file the map describes, if it gave one.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Gets the path that the names of the sources are relative to, if the map gave one.
+    pub fn source_root(&self) -> Option<&str> {
+        self.source_root.as_deref()
+    }
+
+    /// Gets the names of the original sources. Sources the map left `null` are `None`.
+    pub fn sources(&self) -> impl Iterator<Item = Option<&str>> {
+        self.sources.iter().map(Option::as_deref)
+    }
+
+    /// Gets the text of the source at `index`, if the map embedded it.
+    pub fn source_content(&self, index: usize) -> Option<&str> {
+        self.sources_content.get(index)?.as_deref()
+    }
+
+    /// Finds the original position of the generated code at the 1-based `generated` line and column,
+    /// or `None` if it isn't mapped to any source.
+    ///
+    /// The code is covered by the last mapping starting at or before it on the same line.
+    pub fn original_location(&self, generated: Position) -> Option<OriginalLocation<'_>> {
+        let generated = (generated.0.checked_sub(1)?, generated.1.checked_sub(1)?);
+        let segment = self.segments[..self.segments.partition_point(|segment| segment.generated <= generated)].last()?;
+        if segment.generated.0 != generated.0 {
+            return None;
+        }
+        let (source_index, line, col, name) = segment.original?;
+        Some(OriginalLocation {
+            source_index,
+            source: self.sources[source_index].as_deref(),
+            line: line + 1,
+            col: col + 1,
+            name: name.map(|name| self.names[name].as_str()),
+        })
+    }
+
+    fn decode_mappings(&self, mappings: &str) -> Result<Vec<Segment>, SourceMapError> {
+        let bytes = mappings.as_bytes();
+        let mut segments = Vec::new();
+        let (mut line, mut col) = (0usize, 0i64);
+        let (mut source, mut original_line, mut original_col, mut name) = (0i64, 0i64, 0i64, 0i64);
+        let mut pos = 0;
+        let mut fields = Vec::with_capacity(5);
+        while pos < bytes.len() {
+            match bytes[pos] {
+                b';' => {
+                    line += 1;
+                    col = 0;
+                    pos += 1;
+                    continue;
+                }
+                b',' => {
+                    pos += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            let at = pos;
+            fields.clear();
+            while pos < bytes.len() && bytes[pos] != b',' && bytes[pos] != b';' {
+                fields.push(read_vlq(bytes, &mut pos).ok_or(SourceMapError::InvalidMappings { at: pos })?);
+            }
+            let invalid = SourceMapError::InvalidMappings { at };
+            // Deltas are relative to the previous segment, and a crafted map can add up to more than fits
+            let add = |total: &mut i64, delta: i64| -> Result<(), SourceMapError> {
+                *total = total.checked_add(delta).ok_or_else(|| invalid.clone())?;
+                Ok(())
+            };
+            add(&mut col, fields[0])?;
+            let original = match fields.len() {
+                1 => None,
+                4 | 5 => {
+                    add(&mut source, fields[1])?;
+                    add(&mut original_line, fields[2])?;
+                    add(&mut original_col, fields[3])?;
+                    let named = fields.len() == 5;
+                    if named {
+                        add(&mut name, fields[4])?;
+                    }
+                    let index = |value: i64, len: usize| usize::try_from(value).ok().filter(|&value| value < len);
+                    Some((
+                        index(source, self.sources.len()).ok_or(invalid.clone())?,
+                        usize::try_from(original_line).map_err(|_| invalid.clone())?,
+                        usize::try_from(original_col).map_err(|_| invalid.clone())?,
+                        if named { Some(index(name, self.names.len()).ok_or(invalid.clone())?) } else { None },
+                    ))
+                }
+                _ => return Err(invalid),
+            };
+            let col = usize::try_from(col).map_err(|_| invalid)?;
+            segments.push(Segment { generated: (line, col), original });
+        }
+        segments.sort_by_key(|segment| segment.generated);
+        Ok(segments)
+    }
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Appends `value` in base64 VLQ: the sign in the lowest bit, then five bits per digit, least significant first,
+/// with the sixth bit of every digit but the last set.
+fn push_vlq(out: &mut String, value: i64) {
+    let mut rest = (value.unsigned_abs() << 1) | (value < 0) as u64;
+    loop {
+        let digit = (rest & 0x1f) as usize;
+        rest >>= 5;
+        out.push(BASE64[digit | if rest > 0 { 0x20 } else { 0 }] as char);
+        if rest == 0 {
+            break;
+        }
+    }
+}
+
+/// Reads a base64 VLQ value from `bytes` at `pos`, advancing `pos` past it.
+fn read_vlq(bytes: &[u8], pos: &mut usize) -> Option<i64> {
+    let (mut value, mut shift) = (0u64, 0);
+    loop {
+        let byte = *bytes.get(*pos)?;
+        let digit = BASE64.iter().position(|&b| b == byte)? as u64;
+        *pos += 1;
+        value |= (digit & 0x1f).checked_shl(shift).filter(|_| shift < 64)?;
+        shift += 5;
+        if digit & 0x20 == 0 {
+            break;
+        }
+    }
+    let magnitude = (value >> 1) as i64;
+    Some(if value & 1 == 1 { -magnitude } else { magnitude })
+}
+
+/// A parsed JSON value.
+enum Json {
+    Null,
+    Bool,
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// A parser for the JSON a source map is written in.
+struct JsonParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl JsonParser<'_> {
+    fn error(&self) -> SourceMapError {
+        SourceMapError::InvalidJson { at: self.pos }
+    }
+
+    fn whitespace(&mut self) {
+        while matches!(self.bytes.get(self.pos), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), SourceMapError> {
+        self.whitespace();
+        if self.bytes.get(self.pos) == Some(&byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn value(&mut self) -> Result<Json, SourceMapError> {
+        self.whitespace();
+        match self.bytes.get(self.pos) {
+            Some(b'{') => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                self.whitespace();
+                if self.bytes.get(self.pos) == Some(&b'}') {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.whitespace();
+                    let key = self.string()?;
+                    self.expect(b':')?;
+                    fields.push((key, self.value()?));
+                    self.whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b'}') => {
+                            self.pos += 1;
+                            return Ok(Json::Object(fields));
+                        }
+                        _ => return Err(self.error()),
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                self.whitespace();
+                if self.bytes.get(self.pos) == Some(&b']') {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value()?);
+                    self.whitespace();
+                    match self.bytes.get(self.pos) {
+                        Some(b',') => self.pos += 1,
+                        Some(b']') => {
+                            self.pos += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err(self.error()),
+                    }
+                }
+            }
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.keyword("true", Json::Bool),
+            Some(b'f') => self.keyword("false", Json::Bool),
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b'-' | b'0'..=b'9') => {
+                let start = self.pos;
+                while matches!(self.bytes.get(self.pos), Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')) {
+                    self.pos += 1;
+                }
+                let number = core::str::from_utf8(&self.bytes[start..self.pos]).ok().and_then(|number| number.parse().ok());
+                number.map(Json::Number).ok_or(SourceMapError::InvalidJson { at: start })
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, SourceMapError> {
+        if self.bytes[self.pos..].starts_with(keyword.as_bytes()) {
+            self.pos += keyword.len();
+            Ok(value)
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn string(&mut self) -> Result<String, SourceMapError> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.bytes.get(self.pos), Some(b'"' | b'\\') | None) {
+                self.pos += 1;
+            }
+            // The input is a `str`, and runs between ASCII delimiters are whole characters
+            out.push_str(core::str::from_utf8(&self.bytes[start..self.pos]).map_err(|_| self.error())?);
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let escape = *self.bytes.get(self.pos).ok_or_else(|| self.error())?;
+                    self.pos += 1;
+                    out.push(match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let high = self.hex4()?;
+                            let code = if (0xd800..0xdc00).contains(&high) && self.bytes[self.pos..].starts_with(b"\\u") {
+                                let at = self.pos;
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(SourceMapError::InvalidJson { at });
+                                }
+                                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                            } else {
+                                high
+                            };
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(SourceMapError::InvalidJson { at: self.pos - 1 }),
+                    });
+                }
+                _ => return Err(self.error()),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, SourceMapError> {
+        let digits = self.bytes.get(self.pos..self.pos + 4);
+        let digits = digits.filter(|digits| digits.iter().all(u8::is_ascii_hexdigit)).ok_or_else(|| self.error())?;
+        // `from_str_radix` alone would also accept a leading `+`
+        let code = digits.iter().fold(0, |code, &digit| code << 4 | (digit as char).to_digit(16).unwrap());
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+fn push_json_array<'a>(json: &mut String, items: impl Iterator<Item = Option<&'a String>>) {
+    json.push('[');
+    for (i, item) in items.enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        match item {
+            Some(item) => push_json_string(json, item),
+            None => json.push_str("null"),
+        }
+    }
+    json.push(']');
+}
+
+fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn vlq_encoding() {
+        let mut out = String::new();
+        for value in [0, 1, -1, 15, 16, -17, 1000] {
+            push_vlq(&mut out, value);
+            out.push(' ');
+        }
+        assert_eq!(out, "A C D e gB jB w+B ");
+    }
+
+    #[test]
+    fn parsing_round_trip() {
+        let mut builder = JsSourceMapBuilder::new().file("a\u{1F600}.js").source_root("/src");
+        let a = builder.add_source("a.ts", Some("let x\n\t\"y\""));
+        let name = builder.add_name("x");
+        builder.add_mapping((1, 1), Some(OriginalPosition { source: a, line: 2, col: 3, name: Some(name) }));
+        builder.add_mapping((1, 9), None);
+        builder.add_mapping((4, 20), Some(OriginalPosition { source: a, line: 1, col: 1, name: None }));
+        let map = JsSourceMap::parse(&builder.to_json()).unwrap();
+        assert_eq!((map.file(), map.source_root()), (Some("a\u{1F600}.js"), Some("/src")));
+        assert_eq!(map.source_content(0), Some("let x\n\t\"y\""));
+        let location = map.original_location((1, 5)).unwrap();
+        assert_eq!((location.source, location.line, location.col, location.name), (Some("a.ts"), 2, 3, Some("x")));
+        assert_eq!(map.original_location((1, 9)), None);
+        assert_eq!(map.original_location((2, 1)), None);
+        assert_eq!(map.original_location((4, 30)).unwrap().line, 1);
+        assert_eq!(map.sources().collect::<Vec<_>>(), [Some("a.ts")]);
+    }
+
+    #[test]
+    fn rejecting_invalid_maps() {
+        assert_eq!(JsSourceMap::parse("{\"version\":2}").err(), Some(SourceMapError::UnsupportedVersion));
+        assert_eq!(JsSourceMap::parse("{\"version\":3,").err(), Some(SourceMapError::InvalidJson { at: 13 }));
+        assert_eq!(
+            JsSourceMap::parse(r#"{"version":3,"mappings":""}"#).err(),
+            Some(SourceMapError::InvalidField { field: "sources" })
+        );
+        let map = |mappings: &str| JsSourceMap::parse(&format!(r#"{{"version":3,"sources":["a"],"names":[],"mappings":"{}"}}"#, mappings));
+        assert_eq!(map("AAAA;AA").err(), Some(SourceMapError::InvalidMappings { at: 5 }));
+        assert_eq!(map("ACAA").err(), Some(SourceMapError::InvalidMappings { at: 0 }));
+        assert_eq!(map("AAAAA").err(), Some(SourceMapError::InvalidMappings { at: 0 }));
+        assert_eq!(map("A!").err(), Some(SourceMapError::InvalidMappings { at: 1 }));
+        assert!(map(";;AAAA,CAAC").is_ok());
+        assert_eq!(map("ggggggggggggI,ggggggggggggI").err(), Some(SourceMapError::InvalidMappings { at: 14 }));
+        assert_eq!(JsSourceMap::parse(r#"{"version":3,"file":"\u+abc"}"#).err(), Some(SourceMapError::InvalidJson { at: 23 }));
+        assert_eq!(JsSourceMap::parse(r#"{"version":3,"file":"\ud800\u0041"}"#).err(), Some(SourceMapError::InvalidJson { at: 27 }));
+    }
+
+    #[test]
+    fn mappings_across_lines() {
+        let mut builder = JsSourceMapBuilder::new();
+        let a = builder.add_source("a.js", None);
+        let b = builder.add_source("b\"\n.js", None);
+        assert_eq!(builder.add_source("a.js", Some("x")), a);
+        builder.add_mapping((3, 5), Some(OriginalPosition { source: b, line: 1, col: 1, name: None }));
+        builder.add_mapping((1, 1), Some(OriginalPosition { source: a, line: 2, col: 3, name: None }));
+        builder.add_mapping((3, 2), None);
+        assert_eq!(
+            builder.to_json(),
+            r#"{"version":3,"sources":["a.js","b\"\n.js"],"sourcesContent":["x",null],"names":[],"mappings":"AACE;;C,GCDF"}"#
+        );
+    }
+}
@@ -1,35 +1,431 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+// Tests use `std` even when the crate doesn't, so that the `no_std` code paths get tested too
+#[cfg(test)]
+extern crate std;
+
+use heads::{HeadsConfig, LineHeads};
+use alloc::{borrow::Cow, string::String, sync::Arc, vec::Vec};
+use core::{marker::PhantomData, sync::atomic::{AtomicUsize, Ordering}};
+use sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+#[cfg(all(test, not(feature = "std")))]
+use alloc::{boxed::Box, format, string::ToString, vec};
+
+mod anchor;
+mod append;
+#[cfg(feature = "std")]
+mod background;
+mod batch;
+mod boundary;
+mod builder;
+mod bytes;
+#[cfg(feature = "grapheme-lite")]
+mod clusters;
+mod columns;
+mod concat;
+mod context;
+mod crlf;
+mod cursor;
+mod diff;
+mod edit;
+mod edit_log;
+mod embedded;
+mod escape;
+mod ext;
+mod fixed;
+mod heads;
+mod highlight;
+mod interner;
+mod js_source_map;
+mod line_directives;
+mod line_index;
+mod line_lookup;
+mod line_program;
+mod line_starts;
+mod lines;
+mod logical;
+mod lsp;
+mod macros;
+mod oneshot;
+mod position_map;
+#[cfg(feature = "std")]
+mod read;
+#[cfg(feature = "std")]
+mod registry;
+mod search;
+mod selection;
+mod sharded;
+#[cfg(feature = "std")]
+mod sidecar;
+mod source_map;
+mod sync;
+mod table;
+mod text_source;
+mod transcode;
+mod transform;
+mod undo;
+mod utf16;
+mod window;
+#[cfg(feature = "allocator-api")]
+mod alloc_in;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "tokio")]
+mod async_read;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "ropey")]
+mod rope;
+#[cfg(feature = "unicode-linebreak")]
+mod line_break;
+#[cfg(feature = "unicode-width")]
+mod width;
+#[cfg(feature = "unicode-bidi")]
+mod bidi;
+#[cfg(feature = "regex")]
+mod regex_search;
+#[cfg(feature = "aho-corasick")]
+mod multi_pattern;
 #[cfg(feature = "grapheme-clusters")]
-use unicode_segmentation::UnicodeSegmentation;
-use std::{cell::{Ref, RefCell}};
+mod segmentation;
+
+pub use anchor::{Anchor, AnchorSet};
+pub use batch::Positions;
+pub use boundary::IndexError;
+pub use builder::{BuildError, LookupBuilder};
+pub use bytes::{ByteLineColLookup, ByteMode};
+pub use concat::{ConcatenatedSource, PieceLocation};
+pub use context::{context_lines, ContextLine, ContextLines};
+pub use crlf::CrlfNormalizedLookup;
+pub use cursor::LookupCursor;
+pub use diff::{DiffPosition, MappedSpan, TextDiff};
+pub use edit::{Bias, Edit, InverseEdit, LineEdit};
+pub use edit_log::EditLog;
+pub use embedded::{EmbeddedLookup, TemplatePosition};
+pub use escape::EscapedLookup;
+pub use ext::LineColExt;
+pub use fixed::{CapacityError, FixedLineColLookup};
+pub use highlight::{highlight_runs, HighlightRun};
+pub use interner::{SpanId, SpanInterner};
+pub use js_source_map::{JsSourceMap, JsSourceMapBuilder, OriginalLocation, OriginalPosition, SourceMapError};
+pub use line_directives::{DirectiveLocation, LineDirectiveLookup};
+pub use line_index::LineIndex;
+pub use line_lookup::{LineLookup, StrLookup};
+pub use line_program::LineProgramError;
+pub use line_starts::LineStartsError;
+pub use lines::Line;
+pub use logical::{CharId, LogicalPosition, LogicalPositions};
+pub use lsp::{convert_col, line_lengths, lines_longer_than, lsp_position_index, ColumnUnit, LineLength, LspPosition, PositionEncoding};
+pub use oneshot::line_col;
+pub use position_map::PositionMap;
+pub use search::{MatchPattern, TextSegment};
+pub use selection::{Selection, SelectionDirection};
+#[cfg(feature = "grapheme-lite")]
+pub use clusters::{LiteSegmenter, Segmenter};
+#[cfg(feature = "grapheme-clusters")]
+pub use clusters::UnicodeSegmenter;
+#[cfg(feature = "grapheme-clusters")]
+pub use oneshot::line_col_by_cluster;
+#[cfg(feature = "grapheme-lite")]
+pub use oneshot::line_col_by_cluster_with;
+#[cfg(feature = "std")]
+pub use registry::LookupRegistry;
+pub use sharded::ShardedLineColLookup;
+pub use source_map::{FileId, Location, SourceMap};
+pub use table::LineTableFormat;
+pub use text_source::{ChunkedLineColLookup, TextSource};
+pub use transcode::TranscodedLookup;
+pub use transform::{SegmentError, TransformMap};
+pub use undo::UndoStack;
+pub use utf16::Utf16LineColLookup;
+pub use window::LookupWindow;
+#[cfg(feature = "allocator-api")]
+pub use alloc_in::LineColLookupIn;
+#[cfg(feature = "mmap")]
+pub use mmap::{MappedSource, MmapLineColLookup};
+#[cfg(feature = "ropey")]
+pub use rope::RopeLineColLookup;
+#[cfg(feature = "unicode-width")]
+pub use width::EmojiWidth;
+
+/// A 1-based line and column number pair, in that order.
+pub type Position = (usize, usize);
 
 /// Pre-cached line/column lookup table for a string slice.
-pub struct LineColLookup<'source> {
-    src: &'source str,
-    line_heads: RefCell<Option<Vec<usize>>>,
+///
+/// The source type `S` defaults to a borrowed `&str`, but any `S: AsRef<str>` works, such as `String`, `Box<str>`, `Rc<str>` or `Cow<str>`.
+/// Each source type gets its own monomorphized code, so the borrowed case pays nothing for the generality.
+/// See [`OwnedLineColLookup`] and [`SharedLineColLookup`] for the common owned cases.
+///
+/// The line head table is built lazily, and only as far into the source as lookups have needed so far.
+/// Clones share the table, including any part of it computed after the clone was made.
+///
+/// The lazily built caches sit behind locks, so a lookup is `Send + Sync` whenever its source is,
+/// and one `&LineColLookup` can serve lookups from many threads at once.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = LineColLookup::new("One\nTwo\nThree");
+/// std::thread::scope(|scope| {
+///     scope.spawn(|| assert_eq!(lookup.get(5), (2, 2)));
+///     scope.spawn(|| assert_eq!(lookup.get(9), (3, 2)));
+/// });
+/// ```
+pub struct LineColLookup<'source, S = &'source str> {
+    src: S,
+    line_heads: Arc<RwLock<LineHeads>>,
+    /// The 0-based line found by the previous lookup, where the next lookup starts its search.
+    last_line: AtomicUsize,
+    /// Grapheme cluster boundaries of the lines `get_by_cluster` has looked at.
+    #[cfg(feature = "grapheme-lite")]
+    clusters: Arc<clusters::ClusterCache>,
+    _source: PhantomData<&'source str>,
 }
 
-impl<'source> LineColLookup<'source> {
+impl<S: Clone> Clone for LineColLookup<'_, S> {
+    fn clone(&self) -> Self {
+        self.with_source(self.src.clone())
+    }
+}
+
+impl<S> LineColLookup<'_, S> {
+    /// Creates a lookup table over `src` that shares this one's caches, which must have been built for the same text.
+    fn with_source<'other, T>(&self, src: T) -> LineColLookup<'other, T> {
+        LineColLookup {
+            src,
+            line_heads: Arc::clone(&self.line_heads),
+            last_line: AtomicUsize::new(self.last_line.load(Ordering::Relaxed)),
+            #[cfg(feature = "grapheme-lite")]
+            clusters: Arc::clone(&self.clusters),
+            _source: PhantomData,
+        }
+    }
+}
+
+/// A line/column lookup table that owns its source `String`.
+///
+/// Because it borrows nothing, it can be stored alongside other long-lived data without lifetime parameters.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// struct Document {
+///     lookup: OwnedLineColLookup,
+/// }
+///
+/// let doc = Document { lookup: OwnedLineColLookup::new("One\nTwo".to_string()) };
+/// assert_eq!(doc.lookup.get(5), (2, 2));
+/// assert_eq!(doc.lookup.into_src(), "One\nTwo");
+/// ```
+pub type OwnedLineColLookup = LineColLookup<'static, String>;
+
+/// A line/column lookup table over a reference-counted `str`.
+///
+/// Clones are cheap, share both the text and the line head table, and can be sent to and used from other threads.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// use std::sync::Arc;
+/// let lookup = SharedLineColLookup::from(Arc::<str>::from("One\nTwo"));
+/// let worker = lookup.clone();
+/// assert_eq!(std::thread::spawn(move || worker.get(5)).join().unwrap(), (2, 2));
+/// ```
+pub type SharedLineColLookup = LineColLookup<'static, Arc<str>>;
+
+/// A line/column lookup table over a source that may or may not be owned.
+///
+/// Useful when a loader only sometimes rewrites its input: both cases produce the same type, and the borrowed case doesn't allocate.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// use std::borrow::Cow;
+/// fn load(text: &str) -> CowLineColLookup<'_> {
+///     if text.contains('\r') {
+///         CowLineColLookup::from(Cow::Owned(text.replace("\r\n", "\n")))
+///     } else {
+///         CowLineColLookup::from(Cow::Borrowed(text))
+///     }
+/// }
+/// assert_eq!(load("One\r\nTwo").get(5), (2, 2));
+/// assert_eq!(load("One\nTwo").get(5), (2, 2));
+/// ```
+pub type CowLineColLookup<'source> = LineColLookup<'source, Cow<'source, str>>;
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
     /// Creates a new line/col lookup table. The `src` parameter provides the input string used to calculate lines and columns.
     ///
-    /// Internally, this scans `src` and caches the starting positions of all lines. This means this is an O(n) operation.
-    pub fn new(src: &'source str) -> Self {
+    /// The starting positions of all lines are scanned and cached on the first lookup, which is an O(n) operation.
+    /// Use [`LineColLookup::new_eager`] or [`LineColLookup::precompute`] to pay that cost up front instead.
+    pub fn new(src: S) -> Self {
+        Self::with_config(src, HeadsConfig::default())
+    }
+
+    /// Creates a new, lazily built lookup table with the given line head table configuration.
+    fn with_config(src: S, config: HeadsConfig) -> Self {
         Self {
             src,
-            line_heads: RefCell::new(None),
+            line_heads: Arc::new(RwLock::new(LineHeads::new(config))),
+            last_line: AtomicUsize::new(0),
+            #[cfg(feature = "grapheme-lite")]
+            clusters: Default::default(),
+            _source: PhantomData,
+        }
+    }
+
+    /// Creates a new line/col lookup table and immediately builds its line head table.
+    ///
+    /// This is an O(n) operation, but it means no later lookup will have to scan the source.
+    pub fn new_eager(src: S) -> Self {
+        let lookup = Self::new(src);
+        lookup.precompute();
+        lookup
+    }
+
+    /// Builds the line head table now if it has not been built yet.
+    ///
+    /// Calling this at load time keeps the O(n) scan off latency-sensitive lookup paths.
+    pub fn precompute(&self) {
+        drop(self.heads_through(self.src().len()));
+    }
+
+    /// Returns the number of heap bytes used by the cached line head table and any other cached per-line data.
+    ///
+    /// This does not include the source itself.
+    pub fn memory_usage(&self) -> usize {
+        #[cfg(feature = "grapheme-lite")]
+        let clusters = self.clusters.memory_usage();
+        #[cfg(not(feature = "grapheme-lite"))]
+        let clusters = 0;
+        self.read_heads().memory_usage() + clusters
+    }
+
+    /// Releases any excess capacity held by the cached line head table.
+    ///
+    /// Tables grow as they are scanned, so calling this once the table is complete can reclaim some memory.
+    pub fn shrink_to_fit(&self) {
+        self.write_heads().shrink_to_fit();
+    }
+
+    /// Discards the cached line head table and any other cached per-line data.
+    /// They will be rebuilt, with the same configuration, when next needed.
+    ///
+    /// Clones share these caches, so this clears them for them as well.
+    pub fn clear_cache(&self) {
+        let mut heads = self.write_heads();
+        *heads = LineHeads::new(heads.config);
+        #[cfg(feature = "grapheme-lite")]
+        self.clusters.clear();
+    }
+
+    /// Creates a lookup table from a source and an already-built line head table.
+    fn with_heads(src: S, line_heads: Vec<usize>) -> Self {
+        let line_heads = LineHeads::complete(HeadsConfig::default(), src.as_ref(), line_heads.into_iter().skip(1));
+        Self {
+            src,
+            line_heads: Arc::new(RwLock::new(line_heads)),
+            last_line: AtomicUsize::new(0),
+            #[cfg(feature = "grapheme-lite")]
+            clusters: Default::default(),
+            _source: PhantomData,
+        }
+    }
+
+    /// Creates a lookup table that borrows this one's source and shares its cached line head table and other per-line data.
+    ///
+    /// Unlike [`Clone`], this works for any source type without copying the source,
+    /// so helpers can be handed a `LineColLookup<'_>` without redoing or duplicating any work.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let owned = OwnedLineColLookup::new_eager("One\nTwo".to_string());
+    /// let shared: LineColLookup<'_> = owned.share();
+    /// assert_eq!(shared.get(5), (2, 2));
+    /// ```
+    pub fn share(&self) -> LineColLookup<'_> {
+        self.with_source(self.src())
+    }
+
+    /// Gets a reference to the source string.
+    pub fn src(&self) -> &str {
+        self.src.as_ref()
+    }
+
+    /// Consumes the lookup table and returns its source.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// use std::rc::Rc;
+    /// let lookup = LineColLookup::new(Rc::<str>::from("One\nTwo"));
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// assert_eq!(&*lookup.into_src(), "One\nTwo");
+    /// ```
+    pub fn into_src(self) -> S {
+        self.src
+    }
+
+    fn read_heads(&self) -> RwLockReadGuard<'_, LineHeads> {
+        sync::read(&self.line_heads)
+    }
+
+    fn write_heads(&self) -> RwLockWriteGuard<'_, LineHeads> {
+        sync::write(&self.line_heads)
+    }
+
+    /// Gets exclusive access to the line head table for modification,
+    /// first detaching this lookup's caches from any clones or views sharing them.
+    fn heads_mut(&mut self) -> &mut LineHeads {
+        if Arc::get_mut(&mut self.line_heads).is_none() {
+            let heads = self.read_heads().clone();
+            self.line_heads = Arc::new(RwLock::new(heads));
+        }
+        #[cfg(feature = "grapheme-lite")]
+        if Arc::get_mut(&mut self.clusters).is_none() {
+            self.clusters = Arc::new(self.clusters.detached());
+        }
+        sync::get_mut(Arc::get_mut(&mut self.line_heads).expect("the table was just detached"))
+    }
+
+    /// Gets the line head table, first extending it if needed so that it covers `index`.
+    fn heads_through(&self, index: usize) -> RwLockReadGuard<'_, LineHeads> {
+        {
+            let heads = self.read_heads();
+            if heads.covers(index) {
+                return heads;
+            }
         }
+        self.write_heads().scan_through(self.src(), index);
+        self.read_heads()
+    }
+
+    /// Finds the 0-based line on which `index` resides, along with the byte index at which that line starts.
+    fn line_of(&self, index: usize) -> (usize, usize) {
+        let (line, line_start, _) = self.line_info(index);
+        (line, line_start)
     }
 
-    fn heads(&self) -> Ref<'_, Option<Vec<usize>>> {
-        if self.line_heads.borrow().is_none() {
-            let line_heads: Vec<usize> = std::iter::once(0)
-            .chain(self.src
-                .char_indices()
-                .filter_map(|(i, c)| Some(i + 1).filter(|_| c == '\n')))
-            .collect();
-            self.line_heads.replace(Some(line_heads));
+    /// Like [`LineColLookup::line_of`], but also returns whether the line up to `index` is known to be pure ASCII.
+    fn line_info(&self, index: usize) -> (usize, usize, bool) {
+        if index > self.src().len() {
+            panic!("Index cannot be greater than the length of the input slice.");
         }
 
-        self.line_heads.borrow()
+        self.line_info_in(&self.heads_through(index), index)
+    }
+
+    /// Like [`LineColLookup::line_info`], but within a line head table that has already been locked and covers `index`,
+    /// so that other information about the line can be read from it consistently.
+    fn line_info_in(&self, heads: &LineHeads, index: usize) -> (usize, usize, bool) {
+        let line = heads.line_of_near(self.last_line.load(Ordering::Relaxed), index);
+        self.last_line.store(line, Ordering::Relaxed);
+        (line, heads.starts.get(line), heads.is_ascii_line(line))
     }
 
     /// Looks up the 1-based line and column numbers of the specified byte index.
@@ -57,75 +453,28 @@ impl<'source> LineColLookup<'source> {
     /// # Notes
     /// This function uses a binary search to locate the line on which `index` resides.
     /// This means that it runs in approximately O(log n) time.
+    /// The search starts from the line found by the previous lookup, so lookups near each other are faster still.
     pub fn get(&self, index: usize) -> (usize, usize) {
-        if index > self.src.len() {
-            panic!("Index cannot be greater than the length of the input slice.");
-        }
-
-        if let Some(heads) = self.heads().as_ref() {
-            // Perform a binary search to locate the line on which `index` resides
-            let mut line_range = 0..heads.len();
-            while line_range.end - line_range.start > 1 {
-                let range_middle = line_range.start + (line_range.end - line_range.start) / 2;
-                let (left, right) = (line_range.start..range_middle, range_middle..line_range.end);
-                // Check which line window contains our character index
-                if (heads[left.start] .. heads[left.end]).contains(&index) {
-                    line_range = left;
-                } else {
-                    line_range = right;
-                }
-            }
-
-            let line_start_index = heads[line_range.start];
-            let line = line_range.start + 1;
-            let col = index - line_start_index + 1;
-
-            return (line, col)
-        }
-
-        unreachable!()
+        let (line, line_start_index) = self.line_of(index);
+        (line + 1, index - line_start_index + 1)
     }
+}
 
-    /// Looks up the 1-based line and column numbers of the specified byte index.
-    /// The column number correlates to the number of grapheme clusters up to and at the specified index.
-    ///
-    /// Returns a tuple with the line number first, then column number. 
-    ///
-    /// # Panics
-    ///
-    /// Panics if `index` is greater than the length of the input `&str`.
-    ///
-    /// # Notes
-    /// This function uses a binary search to locate the line on which `index` resides.
-    /// This means that it runs in approximately O(log n) time.
-    #[cfg(feature = "grapheme-clusters")]
-    pub fn get_by_cluster(&self, index: usize) -> (usize, usize) {
-        if index > self.src.len() {
-            panic!("Index cannot be greater than the length of the input slice.");
-        }
-
-        if let Some(heads) = self.heads().as_ref() {
-            // Perform a binary search to locate the line on which `index` resides
-            let mut line_range = 0..heads.len();
-            while line_range.end - line_range.start > 1 {
-                let range_middle = line_range.start + (line_range.end - line_range.start) / 2;
-                let (left, right) = (line_range.start..range_middle, range_middle..line_range.end);
-                // Check which line window contains our character index
-                if (heads[left.start] .. heads[left.end]).contains(&index) {
-                    line_range = left;
-                } else {
-                    line_range = right;
-                }
-            }
-
-            let line_start_index = heads[line_range.start];
-            let line = line_range.start + 1;
-            let col = UnicodeSegmentation::graphemes(&self.src[line_start_index..index], true).count() + 1;
+impl From<String> for OwnedLineColLookup {
+    fn from(src: String) -> Self {
+        Self::new(src)
+    }
+}
 
-            return (line, col)
-        }
+impl<'source> From<Cow<'source, str>> for CowLineColLookup<'source> {
+    fn from(src: Cow<'source, str>) -> Self {
+        Self::new(src)
+    }
+}
 
-        unreachable!()
+impl From<Arc<str>> for SharedLineColLookup {
+    fn from(src: Arc<str>) -> Self {
+        Self::new(src)
     }
 }
 
@@ -140,6 +489,98 @@ mod tests {
         assert_eq!(lookup.get(0), (1, 1));
     }
 
+    #[test]
+    fn owned_matches_borrowed() {
+        let text = "a\nab\nabc";
+        let owned = OwnedLineColLookup::from(text.to_string());
+        let borrowed = LineColLookup::new(text);
+        for i in 0..=text.len() {
+            assert_eq!(owned.get(i), borrowed.get(i));
+        }
+        assert_eq!(owned.into_src(), text);
+    }
+
+    #[test]
+    fn shared_clones_share_heads() {
+        fn assert_send_sync<T: Send + Sync + 'static>(_: &T) {}
+        let lookup = SharedLineColLookup::from(std::sync::Arc::<str>::from("a\nab"));
+        assert_send_sync(&lookup);
+        let clone = lookup.clone();
+        assert_eq!(lookup.get(3), (2, 2));
+        assert!(std::sync::Arc::ptr_eq(&lookup.line_heads, &clone.line_heads));
+        assert_eq!(clone.get(3), (2, 2));
+    }
+
+    #[test]
+    fn shared_views_share_heads() {
+        let lookup = OwnedLineColLookup::new("a\nab".to_string());
+        let view = lookup.share();
+        assert_eq!(view.get(3), (2, 2));
+        assert!(std::sync::Arc::ptr_eq(&lookup.line_heads, &view.line_heads));
+        assert!(lookup.read_heads().covers(4));
+        view.clear_cache();
+        assert!(!lookup.read_heads().covers(1));
+    }
+
+    #[test]
+    fn concurrent_lazy_lookups() {
+        let text = "a\nab\nabc\n".repeat(5000);
+        let lookup = LineColLookup::new(text.as_str());
+        let expected = LineColLookup::new_eager(text.as_str());
+        std::thread::scope(|scope| {
+            for t in 0..4 {
+                let (lookup, expected) = (&lookup, &expected);
+                scope.spawn(move || {
+                    for i in (t..=lookup.src().len()).rev().step_by(7) {
+                        assert_eq!(lookup.get(i), expected.get(i));
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn generic_sources() {
+        use std::{borrow::Cow, rc::Rc};
+        let text = "a\nab";
+        assert_eq!(LineColLookup::new(Box::<str>::from(text)).get(3), (2, 2));
+        assert_eq!(LineColLookup::new(Rc::<str>::from(text)).get(3), (2, 2));
+        assert_eq!(LineColLookup::new(Cow::Borrowed(text)).get(3), (2, 2));
+    }
+
+    #[test]
+    fn eager_construction() {
+        let lookup = LineColLookup::new("a\nab");
+        assert!(!lookup.read_heads().covers(4));
+        lookup.precompute();
+        assert_eq!(lookup.read_heads().starts.to_vec(), [0, 2]);
+        assert!(LineColLookup::new_eager("a\nab").read_heads().covers(4));
+    }
+
+    #[test]
+    fn memory_management() {
+        let text = "ab\n".repeat(1000);
+        let lookup = LineColLookup::new(text.as_str());
+        assert_eq!(lookup.memory_usage(), 0);
+        lookup.precompute();
+        lookup.shrink_to_fit();
+        assert_eq!(lookup.memory_usage(), 1001 * core::mem::size_of::<usize>());
+        lookup.clear_cache();
+        assert!(!lookup.read_heads().covers(text.len()));
+        assert_eq!(lookup.get(text.len()), (1001, 1));
+    }
+
+    #[test]
+    fn incremental_scan() {
+        let text = "ab\n".repeat(10_000);
+        let lookup = LineColLookup::new(text.as_str());
+        assert_eq!(lookup.get(4), (2, 2));
+        assert!(!lookup.read_heads().covers(text.len()));
+        assert_eq!(lookup.get(29_999), (10_000, 3));
+        assert_eq!(lookup.get(text.len()), (10_001, 1));
+        assert!(lookup.read_heads().covers(text.len()));
+    }
+
     #[test]
     fn line_col_iter_by_codepoints() {
         let text = "a\nab\nabc";
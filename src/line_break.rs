@@ -0,0 +1,52 @@
+use crate::{LineColLookup, Position};
+use unicode_linebreak::{linebreaks, BreakOpportunity};
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Iterates over the places where the [Unicode line breaking algorithm](https://www.unicode.org/reports/tr14/)
+    /// allows or requires breaking a line of the source, with their byte indices and positions.
+    ///
+    /// A break at an index falls before the character starting there, so wrapping text breaks it into `src[..index]` and `src[index..]`.
+    /// Mandatory breaks follow each line terminator, and the end of a non-empty source is always one.
+    ///
+    /// Breaks are looked up with one [`LookupCursor`](crate::LookupCursor) moving forward through the source,
+    /// and each break is on the same line as the one before it or the next, so none but the first needs a binary search.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// use unicode_linebreak::BreakOpportunity;
+    /// let lookup = LineColLookup::new("a well-known\nfact");
+    /// let breaks: Vec<_> = lookup.break_opportunities().map(|(_, position, _)| position).collect();
+    /// assert_eq!(breaks, [(1, 3), (1, 8), (2, 1), (2, 5)]);
+    /// let (index, _, opportunity) = lookup.break_opportunities().nth(2).unwrap();
+    /// assert_eq!((index, opportunity), (13, BreakOpportunity::Mandatory));
+    /// ```
+    pub fn break_opportunities(&self) -> impl Iterator<Item = (usize, Position, BreakOpportunity)> + '_ {
+        let mut cursor = self.cursor();
+        linebreaks(self.src()).map(move |(index, opportunity)| (index, cursor.get(index), opportunity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use unicode_linebreak::BreakOpportunity;
+
+    #[test]
+    fn positioned_break_opportunities() {
+        let text = "日本語の文章\r\n\nfoo (bar) — baz?\n";
+        let lookup = LineColLookup::new(text);
+        let breaks: Vec<_> = lookup.break_opportunities().collect();
+        for &(index, position, _) in &breaks {
+            assert!(text.is_char_boundary(index));
+            assert_eq!(position, lookup.get(index));
+        }
+        let mandatory: Vec<_> = breaks.iter().filter(|b| b.2 == BreakOpportunity::Mandatory).map(|b| b.1).collect();
+        assert_eq!(mandatory, [(2, 1), (3, 1), (4, 1)]);
+        // Ideographs can be broken between, but not before the closing parenthesis or question mark
+        let allowed: Vec<_> = breaks.iter().filter(|b| b.2 == BreakOpportunity::Allowed).map(|b| b.1).collect();
+        assert_eq!(allowed[..5], [(1, 4), (1, 7), (1, 10), (1, 13), (1, 16)]);
+        assert!(!allowed.contains(&(3, 9)) && !allowed.contains(&(3, 20)));
+        assert_eq!(LineColLookup::new("").break_opportunities().count(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{LineColLookup, Position};
+use alloc::vec::Vec;
+
+/// A lookup table over preprocessed or generated source that reports positions in terms of the original files and lines
+/// named by its line directives, while still giving the physical position on request.
+///
+/// Recognized directives occupy a line of their own, optionally indented, in one of these forms:
+/// - `#line 30 "orig.c"`, as in C and C#; the file name is optional
+/// - `# 30 "orig.c"`, the line markers emitted by GCC's preprocessor, ignoring any flags after the name
+/// - `//# line 30 "orig.c"`, as used in generated files of languages without a preprocessor
+///
+/// A directive says that the line after it is the given line of the named file. Lines before the first directive,
+/// and lines after a directive that doesn't name a file, belong to the last file named, or to the physical file if none was.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let src = "int a;\n#line 30 \"orig.c\"\nint b;\nint c;";
+/// let lookup = LineDirectiveLookup::new(src);
+/// let c = src.find("c;").unwrap();
+/// let location = lookup.get(c);
+/// assert_eq!((location.file, location.line, location.col), (Some("orig.c"), 31, 5));
+/// assert_eq!(lookup.physical(c), (4, 5));
+/// assert_eq!(lookup.get(0).file, None);
+/// ```
+#[derive(Clone)]
+pub struct LineDirectiveLookup<'source> {
+    lookup: LineColLookup<'source>,
+    directives: Vec<Directive<'source>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Directive<'source> {
+    /// The 0-based physical line following the directive.
+    physical_line: usize,
+    /// The original line number given to that line.
+    line: usize,
+    /// The file named by this directive or the last one that named a file.
+    file: Option<&'source str>,
+}
+
+/// A position reported by a [`LineDirectiveLookup`]: the original file, if a directive named one, and the 1-based line and column numbers there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DirectiveLocation<'source> {
+    pub file: Option<&'source str>,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl<'source> LineDirectiveLookup<'source> {
+    /// Creates a lookup table over `src`, finding its line directives.
+    pub fn new(src: &'source str) -> Self {
+        let mut directives: Vec<Directive<'source>> = Vec::new();
+        for (physical_line, text) in src.split('\n').enumerate() {
+            if let Some((line, file)) = parse_directive(text) {
+                let file = file.or_else(|| directives.last().and_then(|directive| directive.file));
+                directives.push(Directive { physical_line: physical_line + 1, line, file });
+            }
+        }
+        Self {
+            lookup: LineColLookup::new(src),
+            directives,
+        }
+    }
+
+    /// Gets the source.
+    pub fn src(&self) -> &str {
+        self.lookup.src()
+    }
+
+    /// Gets the lookup table over the physical source.
+    pub fn lookup(&self) -> &LineColLookup<'source> {
+        &self.lookup
+    }
+
+    /// Looks up the original file, line and column of the specified byte index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn get(&self, index: usize) -> DirectiveLocation<'source> {
+        let (line, col) = self.lookup.get(index);
+        let directive = self.directives.partition_point(|directive| directive.physical_line < line).checked_sub(1);
+        match directive.map(|directive| self.directives[directive]) {
+            Some(directive) => DirectiveLocation {
+                file: directive.file,
+                line: directive.line + (line - 1 - directive.physical_line),
+                col,
+            },
+            None => DirectiveLocation { file: None, line, col },
+        }
+    }
+
+    /// Looks up the 1-based physical line and column numbers of the specified byte index, ignoring directives.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn physical(&self, index: usize) -> Position {
+        self.lookup.get(index)
+    }
+}
+
+/// Parses a line consisting of a line directive into its line number and file name, if any.
+fn parse_directive(line: &str) -> Option<(usize, Option<&str>)> {
+    let line = line.trim_start();
+    let rest = if let Some(rest) = line.strip_prefix("//#") {
+        after_keyword(rest.trim_start())?
+    } else {
+        let rest = line.strip_prefix('#')?.trim_start();
+        // GCC line markers leave the keyword out
+        if rest.starts_with(|c: char| c.is_ascii_digit()) {
+            rest
+        } else {
+            after_keyword(rest)?
+        }
+    };
+    let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let number = rest[..digits].parse().ok()?;
+    let rest = rest[digits..].trim();
+    let file = match rest.strip_prefix('"') {
+        Some(quoted) => {
+            let mut escaped = false;
+            let end = quoted.find(|c| {
+                let end = c == '"' && !escaped;
+                escaped = c == '\\' && !escaped;
+                end
+            })?;
+            Some(&quoted[..end])
+        }
+        None if rest.is_empty() || rest == "\r" => None,
+        None => return None,
+    };
+    Some((number, file))
+}
+
+/// Strips the `line` keyword and the whitespace after it from the start of `rest`.
+fn after_keyword(rest: &str) -> Option<&str> {
+    let rest = rest.strip_prefix("line")?;
+    rest.starts_with(char::is_whitespace).then(|| rest.trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_directives() {
+        assert_eq!(parse_directive("#line 30 \"orig.c\""), Some((30, Some("orig.c"))));
+        assert_eq!(parse_directive("  #  line 7"), Some((7, None)));
+        assert_eq!(parse_directive("# 12 \"a \\\"b\\\".h\" 1 3"), Some((12, Some("a \\\"b\\\".h"))));
+        assert_eq!(parse_directive("//# line 4 \"gen.rs\"\r"), Some((4, Some("gen.rs"))));
+        assert_eq!(parse_directive("#lined 4"), None);
+        assert_eq!(parse_directive("#line x"), None);
+        assert_eq!(parse_directive("#include <a.h>"), None);
+        assert_eq!(parse_directive("#line 3 junk"), None);
+    }
+
+    #[test]
+    fn locations_across_directives() {
+        let src = "A\n# 10 \"x.c\"\nB\nC\n#line 3\nD\n#line 1 \"y.c\"\nE";
+        let lookup = LineDirectiveLookup::new(src);
+        let at = |c: &str| {
+            let location = lookup.get(src.find(c).unwrap());
+            (location.file, location.line)
+        };
+        assert_eq!(at("A"), (None, 1));
+        assert_eq!(at("B"), (Some("x.c"), 10));
+        assert_eq!(at("C"), (Some("x.c"), 11));
+        assert_eq!(at("D"), (Some("x.c"), 3));
+        assert_eq!(at("E"), (Some("y.c"), 1));
+        assert_eq!(lookup.physical(src.len()), (8, 2));
+    }
+}
@@ -0,0 +1,127 @@
+use crate::{heads::push_line_starts, line_lookup::line_range_in, LineLookup, Position};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// A table of line starts over text that is only seen once, a chunk at a time, and never kept.
+///
+/// Since the text itself is gone, only byte columns can be computed.
+/// This suits network streams or any chunked storage without a [`TextSource`](crate::TextSource) implementation.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let index = LineIndex::from_chunks(["On", "e\nT", "wo"]);
+/// assert_eq!(index.get(5), (2, 2));
+/// assert_eq!(index.len(), 7);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    len: usize,
+    starts: Vec<usize>,
+}
+
+impl Default for LineIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LineIndex {
+    /// Creates an index over empty text.
+    pub fn new() -> Self {
+        Self { len: 0, starts: vec![0] }
+    }
+
+    /// Creates an index over the concatenation of `chunks`, tracking the byte offset across chunk boundaries.
+    pub fn from_chunks<'a>(chunks: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut index = Self::new();
+        chunks.into_iter().for_each(|chunk| index.push_chunk(chunk));
+        index
+    }
+
+    /// Extends the indexed text with `chunk`, scanning only the new chunk.
+    pub fn push_chunk(&mut self, chunk: &str) {
+        push_line_starts(&mut self.starts, chunk.as_bytes(), self.len);
+        self.len += chunk.len();
+    }
+
+    /// Returns the length of the indexed text in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the indexed text is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of lines in the indexed text.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Finds the 0-based line on which `index` resides, along with the byte index at which that line starts.
+    pub(crate) fn line_of(&self, index: usize) -> (usize, usize) {
+        if index > self.len {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let line = self.starts.partition_point(|&start| start <= index) - 1;
+        (line, self.starts[line])
+    }
+
+    /// Returns the byte range of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range_in(&self.starts, self.len, line.checked_sub(1)?)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`LineColLookup::get`](crate::LineColLookup::get) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the indexed text.
+    pub fn get(&self, index: usize) -> Position {
+        let (line, line_start) = self.line_of(index);
+        (line + 1, index - line_start + 1)
+    }
+}
+
+impl<'a> Extend<&'a str> for LineIndex {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, chunks: I) {
+        chunks.into_iter().for_each(|chunk| self.push_chunk(chunk));
+    }
+}
+
+impl LineLookup for LineIndex {
+    fn get(&self, index: usize) -> Position {
+        LineIndex::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        LineIndex::line_count(self)
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        LineIndex::line_range(self, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn chunks_match_contiguous() {
+        let chunks = ["a\n", "", "b", "\n\nab", "c\n"];
+        let text = chunks.concat();
+        let index = LineIndex::from_chunks(chunks);
+        let contiguous = LineColLookup::new(text.as_str());
+        assert_eq!(index.line_count(), 5);
+        assert_eq!(index.line_range(4), Some(5..8));
+        for i in 0..=text.len() {
+            assert_eq!(index.get(i), contiguous.get(i));
+        }
+        let mut streamed = LineIndex::new();
+        streamed.extend(text.split_inclusive('b'));
+        assert_eq!(streamed.starts, index.starts);
+    }
+}
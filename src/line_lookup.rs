@@ -0,0 +1,86 @@
+use crate::{LineColLookup, Position};
+use core::ops::Range;
+
+/// The operations shared by this crate's lookup tables, so that code can accept any of them, including as `&dyn LineLookup`.
+///
+/// Lines are 1-based, as in [`Position`]. Indices and columns count bytes, except in a [`Utf16LineColLookup`](crate::Utf16LineColLookup),
+/// where they count UTF-16 code units.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// fn describe(lookup: &dyn LineLookup, index: usize) -> String {
+///     let (line, col) = lookup.get(index);
+///     format!("{}:{} of {}", line, col, lookup.line_count())
+/// }
+/// let text = "One\nTwo";
+/// assert_eq!(describe(&LineColLookup::new(text), 5), "2:2 of 2");
+/// assert_eq!(describe(&FixedLineColLookup::<2>::new(text).unwrap(), 5), "2:2 of 2");
+/// ```
+pub trait LineLookup {
+    /// Looks up the 1-based line and column numbers of the specified index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    fn get(&self, index: usize) -> Position;
+
+    /// Returns the number of lines in the source.
+    fn line_count(&self) -> usize;
+
+    /// Returns the range of indices of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    fn line_range(&self, line: usize) -> Option<Range<usize>>;
+}
+
+/// A [`LineLookup`] over a contiguous `&str`, for code that also needs the text of the lines it finds.
+pub trait StrLookup: LineLookup {
+    /// Gets a reference to the source string.
+    fn src(&self) -> &str;
+}
+
+impl<S: AsRef<str>> LineLookup for LineColLookup<'_, S> {
+    fn get(&self, index: usize) -> Position {
+        LineColLookup::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        LineColLookup::line_count(self)
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        LineColLookup::line_range(self, line)
+    }
+}
+
+impl<S: AsRef<str>> StrLookup for LineColLookup<'_, S> {
+    fn src(&self) -> &str {
+        LineColLookup::src(self)
+    }
+}
+
+/// Returns the byte range of the 0-based `line` from the start of each line in `starts`, for a source of `len` bytes.
+pub(crate) fn line_range_in(starts: &[usize], len: usize, line: usize) -> Option<Range<usize>> {
+    let start = *starts.get(line)?;
+    Some(start..starts.get(line + 1).map_or(len, |next| next - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn implementations_agree() {
+        let text = "a\n\nabc\n";
+        let lookups: [&dyn StrLookup; 2] = [&LineColLookup::new(text), &FixedLineColLookup::<4>::new(text).unwrap()];
+        for lookup in lookups {
+            assert_eq!(lookup.src(), text);
+            assert_eq!(lookup.line_count(), 4);
+            for line in 0..=5 {
+                assert_eq!(lookup.line_range(line), LineColLookup::new(text).line_range(line));
+            }
+            for i in 0..=text.len() {
+                assert_eq!(lookup.get(i), line_col(text, i));
+            }
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::{table::{push_leb128, read_leb128}, LineColLookup, LineStartsError};
+use alloc::{vec, vec::Vec};
+use core::fmt;
+
+/// Starts a new line at a LEB128-encoded distance past the previous line start.
+const OP_ADVANCE: u8 = 0;
+/// Starts a LEB128-encoded number of new lines, each as long as the line before them.
+const OP_REPEAT: u8 = 1;
+/// Opcodes from here up start a new line at a distance of `opcode - OP_SPECIAL + 1` bytes past the previous line start.
+const OP_SPECIAL: u8 = 2;
+
+/// The ways decoding a line program with [`LineColLookup::from_line_program`] can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LineProgramError {
+    /// The program ends in the middle of an operation, at byte `at`.
+    Truncated { at: usize },
+    /// The operation at byte `at` repeats a line length before any line length was given,
+    /// or advances by zero bytes or past the largest representable offset.
+    InvalidOperation { at: usize },
+    /// The program decodes to line starts that don't match the source.
+    LineStarts(LineStartsError),
+}
+
+impl fmt::Display for LineProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { at } => write!(f, "line program is truncated at byte {}", at),
+            Self::InvalidOperation { at } => write!(f, "line program has an invalid operation at byte {}", at),
+            Self::LineStarts(err) => write!(f, "line program does not match the source: {}", err),
+        }
+    }
+}
+
+impl core::error::Error for LineProgramError {}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Encodes the line head table as a compact "line program", in the spirit of DWARF's line number programs.
+    ///
+    /// Each line start after the first is one operation: a single byte for lines of up to 254 bytes,
+    /// or a LEB128-encoded length for longer ones. Runs of lines of equal length, such as blank lines, collapse into a single operation.
+    /// This is typically several times smaller than the table itself, making it suited to embedding in build artifacts.
+    ///
+    /// This builds the whole line head table if it has not been built yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let text = "One\n\n\n\nTwo";
+    /// let program = LineColLookup::new(text).line_program();
+    /// assert_eq!(program.len(), 4);
+    /// let lookup = LineColLookup::from_line_program(text, &program).unwrap();
+    /// assert_eq!(lookup.get(7), (5, 1));
+    /// ```
+    pub fn line_program(&self) -> Vec<u8> {
+        let heads = self.heads_through(self.src().len());
+        let mut program = Vec::new();
+        let (mut last_start, mut last_len, mut repeats) = (0, 0, 0);
+        for line in 1..heads.starts.len() {
+            let start = heads.starts.get(line);
+            let len = start - last_start;
+            last_start = start;
+            if len == last_len {
+                repeats += 1;
+                continue;
+            }
+            push_repeats(&mut program, repeats);
+            repeats = 0;
+            last_len = len;
+            if len <= (u8::MAX - OP_SPECIAL) as usize + 1 {
+                program.push(OP_SPECIAL + (len - 1) as u8);
+            } else {
+                program.push(OP_ADVANCE);
+                push_leb128(&mut program, len);
+            }
+        }
+        push_repeats(&mut program, repeats);
+        program
+    }
+
+    /// Creates a lookup table from a source and a line program produced by [`LineColLookup::line_program`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the program is malformed or its line starts don't match the source,
+    /// as checked by [`LineColLookup::from_line_starts`].
+    pub fn from_line_program(src: S, program: &[u8]) -> Result<Self, LineProgramError> {
+        let mut starts: Vec<usize> = vec![0];
+        let (mut pos, mut last_len) = (0, 0);
+        while pos < program.len() {
+            let at = pos;
+            let opcode = program[pos];
+            pos += 1;
+            let (len, count) = match opcode {
+                OP_ADVANCE | OP_REPEAT => {
+                    let value = read_leb128(program, &mut pos).ok_or(LineProgramError::Truncated { at })?;
+                    if opcode == OP_ADVANCE { (value, 1) } else { (last_len, value) }
+                }
+                _ => ((opcode - OP_SPECIAL) as usize + 1, 1),
+            };
+            if len == 0 {
+                return Err(LineProgramError::InvalidOperation { at });
+            }
+            let mut start = *starts.last().unwrap();
+            for _ in 0..count {
+                start = start.checked_add(len).ok_or(LineProgramError::InvalidOperation { at })?;
+                if start > src.as_ref().len() {
+                    return Err(LineProgramError::LineStarts(LineStartsError::NotLineStart { offset: start }));
+                }
+                starts.push(start);
+            }
+            last_len = len;
+        }
+        Self::from_line_starts(src, starts).map_err(LineProgramError::LineStarts)
+    }
+}
+
+/// Appends an operation repeating the previous line length `repeats` times, if there are any.
+fn push_repeats(program: &mut Vec<u8>, repeats: usize) {
+    if repeats > 0 {
+        program.push(OP_REPEAT);
+        push_leb128(program, repeats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn line_program_round_trip() {
+        let long = "x".repeat(1000);
+        let text = format!("a\nab\n\n\n\nabc\n{}\n{}\nend", long, long);
+        let lookup = LineColLookup::new(text.as_str());
+        let program = lookup.line_program();
+        let decoded = LineColLookup::from_line_program(text.as_str(), &program).unwrap();
+        for i in 0..=text.len() {
+            assert_eq!(decoded.get(i), lookup.get(i));
+        }
+        assert!(LineColLookup::new("").line_program().is_empty());
+    }
+
+    #[test]
+    fn line_program_errors() {
+        assert_eq!(LineColLookup::from_line_program("a\nb", &[1, 3]).err(), Some(LineProgramError::InvalidOperation { at: 0 }));
+        assert_eq!(LineColLookup::from_line_program("a\nb", &[0, 0x80]).err(), Some(LineProgramError::Truncated { at: 0 }));
+        assert!(matches!(LineColLookup::from_line_program("a\nb", &[2]), Err(LineProgramError::LineStarts(_))));
+        assert_eq!(LineColLookup::from_line_program("a\nb", &[3]).unwrap().get(2), (2, 1));
+
+        let mut overflowing = vec![3, 0];
+        crate::table::push_leb128(&mut overflowing, usize::MAX);
+        assert_eq!(LineColLookup::from_line_program("a\nb", &overflowing).err(), Some(LineProgramError::InvalidOperation { at: 1 }));
+        let mut repeating = vec![3, 1];
+        crate::table::push_leb128(&mut repeating, usize::MAX);
+        let past_end = Some(LineProgramError::LineStarts(LineStartsError::NotLineStart { offset: 4 }));
+        assert_eq!(LineColLookup::from_line_program("a\nb", &repeating).err(), past_end);
+    }
+}
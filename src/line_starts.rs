@@ -0,0 +1,134 @@
+use crate::LineColLookup;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The ways a precomputed line start table can fail validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineStartsError {
+    /// The table is empty or does not begin with offset 0.
+    MissingFirstLine,
+    /// The offset at position `at` in the table is not greater than the one before it.
+    Unsorted { at: usize },
+    /// The offset is past the end of the source, or does not directly follow a `'\n'`.
+    NotLineStart { offset: usize },
+    /// The offset directly follows a `'\n'`, but the table has no entry for it.
+    MissingLineStart { offset: usize },
+    /// The line lengths do not add up to the length of the source.
+    LengthMismatch,
+}
+
+impl fmt::Display for LineStartsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingFirstLine => write!(f, "line start table must begin with offset 0"),
+            Self::Unsorted { at } => write!(f, "line start table is not strictly increasing at position {}", at),
+            Self::NotLineStart { offset } => write!(f, "offset {} is not the start of a line", offset),
+            Self::MissingLineStart { offset } => write!(f, "line start table is missing the line starting at offset {}", offset),
+            Self::LengthMismatch => write!(f, "line lengths do not add up to the length of the source"),
+        }
+    }
+}
+
+impl core::error::Error for LineStartsError {}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Creates a lookup table from a source and the byte offsets at which each of its lines start,
+    /// skipping the scan that [`LineColLookup::new`] would otherwise perform.
+    ///
+    /// The table must begin with 0, be strictly increasing, and hold exactly the offsets that directly follow a `'\n'`.
+    /// Checking this reads the source once, but unlike a scan it allocates nothing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::from_line_starts("One\nTwo", vec![0, 4]).unwrap();
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// assert!(LineColLookup::from_line_starts("One\nTwo", vec![0, 3]).is_err());
+    /// assert!(LineColLookup::from_line_starts("One\nTwo", vec![0]).is_err());
+    /// ```
+    pub fn from_line_starts(src: S, line_starts: Vec<usize>) -> Result<Self, LineStartsError> {
+        let bytes = src.as_ref().as_bytes();
+        if line_starts.first() != Some(&0) {
+            return Err(LineStartsError::MissingFirstLine);
+        }
+        for (at, pair) in line_starts.windows(2).enumerate() {
+            if pair[1] <= pair[0] {
+                return Err(LineStartsError::Unsorted { at: at + 1 });
+            }
+            if pair[1] > bytes.len() || bytes[pair[1] - 1] != b'\n' {
+                return Err(LineStartsError::NotLineStart { offset: pair[1] });
+            }
+        }
+        // The only newline on each line may be the one ending it
+        for (line, &start) in line_starts.iter().enumerate() {
+            let end = line_starts.get(line + 1).map_or(bytes.len(), |next| next - 1);
+            if let Some(i) = bytes[start..end].iter().position(|&b| b == b'\n') {
+                return Err(LineStartsError::MissingLineStart { offset: start + i + 1 });
+            }
+        }
+        Ok(Self::with_heads(src, line_starts))
+    }
+
+    /// Creates a lookup table from a source and the byte length of each of its lines, including their `'\n'` terminators.
+    ///
+    /// Every line needs a length, even an empty last one, and the lengths must add up to the length of the source.
+    /// The resulting line starts are then validated as [`LineColLookup::from_line_starts`] does.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::from_line_lengths("One\nTwo", [4, 3]).unwrap();
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// assert_eq!(LineColLookup::from_line_lengths("One\nTwo", [4, 4]).err(), Some(LineStartsError::LengthMismatch));
+    /// ```
+    pub fn from_line_lengths<I: IntoIterator<Item = usize>>(src: S, line_lengths: I) -> Result<Self, LineStartsError> {
+        let src_len = src.as_ref().len();
+        let mut line_starts = Vec::new();
+        let mut end = 0usize;
+        for len in line_lengths {
+            line_starts.push(end);
+            end = end.checked_add(len).filter(|&end| end <= src_len).ok_or(LineStartsError::LengthMismatch)?;
+        }
+        if end != src_len {
+            return Err(LineStartsError::LengthMismatch);
+        }
+        if line_starts.is_empty() {
+            line_starts.push(0);
+        }
+        Self::from_line_starts(src, line_starts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn from_line_starts_validates() {
+        let text = "a\nab\nabc";
+        assert_eq!(LineColLookup::from_line_starts(text, vec![]).err(), Some(LineStartsError::MissingFirstLine));
+        assert_eq!(LineColLookup::from_line_starts(text, vec![0, 5, 2]).err(), Some(LineStartsError::Unsorted { at: 2 }));
+        assert_eq!(LineColLookup::from_line_starts(text, vec![0, 3]).err(), Some(LineStartsError::NotLineStart { offset: 3 }));
+        assert_eq!(LineColLookup::from_line_starts(text, vec![0, 9]).err(), Some(LineStartsError::NotLineStart { offset: 9 }));
+        assert_eq!(LineColLookup::from_line_starts(text, vec![0, 5]).err(), Some(LineStartsError::MissingLineStart { offset: 2 }));
+        assert_eq!(LineColLookup::from_line_starts(text, vec![0, 2]).err(), Some(LineStartsError::MissingLineStart { offset: 5 }));
+
+        let lookup = LineColLookup::from_line_starts(text, vec![0, 2, 5]).unwrap();
+        assert_eq!(lookup.get(3), (2, 2));
+        assert_eq!(lookup.get(8), (3, 4));
+    }
+
+    #[test]
+    fn from_line_lengths_validates() {
+        let text = "a\nab\n";
+        assert_eq!(LineColLookup::from_line_lengths(text, [usize::MAX, 2]).err(), Some(LineStartsError::LengthMismatch));
+        assert_eq!(LineColLookup::from_line_lengths(text, [2, 3, 1]).err(), Some(LineStartsError::LengthMismatch));
+        assert_eq!(LineColLookup::from_line_lengths(text, [2, 2]).err(), Some(LineStartsError::LengthMismatch));
+        assert_eq!(LineColLookup::from_line_lengths(text, [2, 3]).err(), Some(LineStartsError::MissingLineStart { offset: 5 }));
+        assert_eq!(LineColLookup::from_line_lengths("", []).unwrap().get(0), (1, 1));
+
+        let lookup = LineColLookup::from_line_lengths(text, [2, 3, 0]).unwrap();
+        assert_eq!(lookup.get(3), (2, 2));
+        assert_eq!(lookup.get(5), (3, 1));
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{heads::line_hash, LineColLookup};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// A line of the source, as found by [`LineColLookup::lines_matching`] and [`LineColLookup::lines_containing`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Line<'a> {
+    /// The 1-based line number.
+    pub number: usize,
+    /// The byte range of the line, excluding its terminating `'\n'`, as [`LineColLookup::line_range`] returns it.
+    pub range: Range<usize>,
+    /// The text of the line, excluding its terminating `'\n'`.
+    pub text: &'a str,
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Returns the number of lines in the source. An empty source, or one ending in `'\n'`, still has a last, empty line.
+    ///
+    /// This builds the whole line head table if it has not been built yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// assert_eq!(LineColLookup::new("One\nTwo\n").line_count(), 3);
+    /// ```
+    pub fn line_count(&self) -> usize {
+        self.heads_through(self.src().len()).starts.len()
+    }
+
+    /// Returns the byte range of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    ///
+    /// Lines up to the end of the scanned part of the line head table are answered without scanning further.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("One\r\nTwo");
+    /// assert_eq!(lookup.line_range(1), Some(0..4));
+    /// assert_eq!(lookup.line_range(2), Some(5..8));
+    /// assert_eq!(lookup.line_range(3), None);
+    /// ```
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let line = line.checked_sub(1)?;
+        let len = self.src().len();
+        let mut heads = self.read_heads();
+        if line + 1 >= heads.starts.len() && !heads.covers(len) {
+            drop(heads);
+            heads = self.heads_through(len);
+        }
+        if line >= heads.starts.len() {
+            return None;
+        }
+        let end = if line + 1 < heads.starts.len() { heads.starts.get(line + 1) - 1 } else { len };
+        Some(heads.starts.get(line)..end)
+    }
+
+    /// Copies the byte index at which every line starts, in increasing order, starting with 0 for the first line.
+    ///
+    /// These are the starts [`LineColLookup::from_line_starts`] accepts. The table is stored behind a lock
+    /// and possibly compressed, so it is copied rather than borrowed. This builds the whole line head table if it has not been built yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// assert_eq!(LineColLookup::new("One\r\nTwo\n").line_starts(), [0, 5, 9]);
+    /// ```
+    pub fn line_starts(&self) -> Vec<usize> {
+        self.heads_through(self.src().len()).starts.to_vec()
+    }
+
+    /// Returns the byte range of the terminator ending the 1-based `line`, either `"\n"` or `"\r\n"`,
+    /// or `None` if there is no such line or it is the last line, which has no terminator.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("One\r\nTwo\nThree");
+    /// assert_eq!(lookup.line_terminator(1), Some(3..5));
+    /// assert_eq!(lookup.line_terminator(2), Some(8..9));
+    /// assert_eq!(lookup.line_terminator(3), None);
+    /// ```
+    pub fn line_terminator(&self, line: usize) -> Option<Range<usize>> {
+        let end = self.line_range(line)?.end;
+        if end == self.src().len() {
+            return None;
+        }
+        let start = if self.src()[..end].ends_with('\r') { end - 1 } else { end };
+        Some(start..end + 1)
+    }
+
+    /// Returns a 64-bit hash of the content of the 1-based `line`, excluding its terminating `'\n'`,
+    /// or `None` if there is no such line.
+    ///
+    /// The hash is deterministic and doesn't depend on the line's position,
+    /// so equal hashes across two versions of a file point out lines that are likely unchanged.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let old = LineColLookup::builder("a\nb\nc").line_hashes(true).build().unwrap();
+    /// let new = LineColLookup::new("a\nx\nb\nc");
+    /// assert_eq!(old.line_hash(2), new.line_hash(3));
+    /// assert!(old.lines_equal(3, &new, 4));
+    /// assert!(!old.lines_equal(2, &new, 2));
+    /// ```
+    ///
+    /// # Notes
+    /// Tables built with [`LookupBuilder::line_hashes`](crate::LookupBuilder::line_hashes) record the hash of every line
+    /// but the last as they are scanned. Otherwise, the line is hashed on each call.
+    pub fn line_hash(&self, line: usize) -> Option<u64> {
+        let range = self.line_range(line)?;
+        let recorded = self.read_heads().recorded_hash(line - 1);
+        Some(recorded.unwrap_or_else(|| line_hash(&self.src().as_bytes()[range])))
+    }
+
+    /// Returns `true` if the 1-based `line` of this source and `other_line` of `other` have the same content,
+    /// excluding their terminating `'\n'`s. Both must exist.
+    ///
+    /// Lines are compared by [`line_hash`](Self::line_hash) first, so differing lines are usually rejected without reading them.
+    pub fn lines_equal<T: AsRef<str>>(&self, line: usize, other: &LineColLookup<'_, T>, other_line: usize) -> bool {
+        match (self.line_range(line), other.line_range(other_line)) {
+            (Some(range), Some(other_range)) => {
+                range.len() == other_range.len()
+                    && self.line_hash(line) == other.line_hash(other_line)
+                    && self.src()[range] == other.src()[other_range]
+            }
+            _ => false,
+        }
+    }
+
+    fn line(&self, number: usize) -> Option<Line<'_>> {
+        let range = self.line_range(number)?;
+        Some(Line { number, text: &self.src()[range.clone()], range })
+    }
+
+    /// Iterates over the lines whose text, excluding the terminating `'\n'`, satisfies `predicate`.
+    ///
+    /// The line head table is only scanned as far as the iterator has got.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a = 1\n\n// note\nb = 2");
+    /// let blank: Vec<_> = lookup.lines_matching(|text| text.trim().is_empty()).map(|line| line.number).collect();
+    /// assert_eq!(blank, [2]);
+    /// ```
+    pub fn lines_matching<'a>(&'a self, mut predicate: impl FnMut(&str) -> bool + 'a) -> impl Iterator<Item = Line<'a>> + 'a {
+        (1..).map_while(move |number| self.line(number)).filter(move |line| predicate(line.text))
+    }
+
+    /// Iterates over the lines on which a match of `needle` starts, each only once however many matches it has.
+    ///
+    /// Rather than testing each line, the source is searched for `needle` and skips to the next line after each match,
+    /// so lines without a match are never visited.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("ERROR a\nok\nERROR b ERROR c");
+    /// let lines: Vec<_> = lookup.lines_containing("ERROR").map(|line| (line.number, line.text)).collect();
+    /// assert_eq!(lines, [(1, "ERROR a"), (3, "ERROR b ERROR c")]);
+    /// ```
+    pub fn lines_containing<'a>(&'a self, needle: &'a str) -> impl Iterator<Item = Line<'a>> + 'a {
+        let mut cursor = self.cursor();
+        let mut pos = 0;
+        core::iter::from_fn(move || {
+            let found = pos + self.src().get(pos..)?.find(needle)?;
+            let (number, _) = cursor.get(found);
+            let line = self.line(number)?;
+            pos = line.range.end + 1;
+            Some(line)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn line_ranges() {
+        let text = "a\n\nabc\n";
+        let lookup = LineColLookup::new(text);
+        assert_eq!(lookup.line_range(0), None);
+        assert_eq!(lookup.line_range(3), Some(3..6));
+        assert_eq!(lookup.line_count(), 4);
+        let ranges: Vec<_> = (1..=4).map(|line| lookup.line_range(line).unwrap()).collect();
+        assert_eq!(ranges, [0..1, 2..2, 3..6, 7..7]);
+        assert_eq!(lookup.line_range(5), None);
+    }
+
+    #[test]
+    fn line_starts_and_terminators() {
+        let text = "\r\na\n\r\r\n";
+        let lookup = LineColLookup::new(text);
+        assert_eq!(lookup.line_starts(), [0, 2, 4, 7]);
+        let terminators: Vec<_> = (1..=4).map(|line| lookup.line_terminator(line)).collect();
+        assert_eq!(terminators, [Some(0..2), Some(3..4), Some(5..7), None]);
+        assert_eq!(LineColLookup::from_line_starts(text, lookup.line_starts()).unwrap().get(5), lookup.get(5));
+    }
+
+    #[test]
+    fn matching_lines() {
+        let text = "xx\nab\r\n\nbxb\nb";
+        let lookup = LineColLookup::new(text);
+        let containing: Vec<_> = lookup.lines_containing("b").map(|line| line.number).collect();
+        let matching: Vec<_> = lookup.lines_matching(|text| text.contains('b')).map(|line| line.number).collect();
+        assert_eq!(containing, [2, 4, 5]);
+        assert_eq!(matching, containing);
+        let second = lookup.lines_containing("b").next().unwrap();
+        assert_eq!((second.range, second.text), (3..6, "ab\r"));
+        assert_eq!(lookup.lines_containing("").count(), 5);
+        assert_eq!(lookup.lines_containing("\n\n").map(|line| line.number).collect::<Vec<_>>(), [2]);
+    }
+}
@@ -0,0 +1,196 @@
+use crate::{Bias, Edit};
+use alloc::vec::Vec;
+
+/// The identifier of a `char` in a [`LogicalPositions`] layer: the replica that inserted it, and a sequence number unique to that replica.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CharId {
+    pub replica: u32,
+    pub seq: u64,
+}
+
+/// A position identified by the `char`s around it rather than by a byte offset, as produced by [`LogicalPositions::position_at`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogicalPosition {
+    /// The start of the text, before everything ever inserted at it.
+    Start,
+    /// Just after the identified `char`, before any text later inserted there.
+    After(CharId),
+    /// Just before the identified `char`, after any text later inserted there.
+    Before(CharId),
+    /// The end of the text, after everything ever inserted at it.
+    End,
+}
+
+/// A layer assigning a stable [`CharId`] to every `char` of a source, so that positions can be named in a way
+/// that survives edits, including edits made concurrently by other peers of a collaborative editor.
+///
+/// Byte offsets recorded by one peer are meaningless to another whose copy has been edited differently,
+/// but a [`LogicalPosition`] names the `char` it is next to, which every peer that has seen that `char` can resolve.
+/// Deleted `char`s are kept as tombstones so positions next to them still resolve, to where they used to be.
+///
+/// All peers must start from the same text, whose `char`s get IDs from replica 0. Local edits get IDs from this layer's replica;
+/// edits received from another peer should be applied with [`LogicalPositions::apply_as`] and the IDs that peer assigned,
+/// so that every copy agrees on them.
+///
+/// Byte offsets of edits are taken as given, so ordering concurrent insertions is left to the CRDT supplying them.
+/// Positions next to deleted `char`s may resolve slightly differently on peers that applied the deletions in different orders.
+///
+/// The layer stores an entry per `char` ever inserted, and resolves positions in time linear in that number.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut lookup = OwnedLineColLookup::new("let x = 1;".to_string());
+/// let mut logical = LogicalPositions::new(1, lookup.src());
+/// let x = logical.position_at(4, Bias::After);
+/// let new_text = "const ";
+/// let edit = lookup.apply_edit(0..4, new_text);
+/// logical.apply(&edit, new_text);
+/// assert_eq!(logical.resolve(x), 6);
+/// assert_eq!(lookup.get(logical.resolve(x)), (1, 7));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LogicalPositions {
+    replica: u32,
+    next_seq: u64,
+    /// Every `char` ever inserted, in text order.
+    chars: Vec<CharEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct CharEntry {
+    id: CharId,
+    /// The `char`'s length in bytes.
+    len: u8,
+    /// Whether the `char` is still part of the text, rather than a tombstone.
+    live: bool,
+}
+
+impl LogicalPositions {
+    /// Creates a layer over the initial text `src` for the peer `replica`, which must not be 0 or shared with another peer.
+    pub fn new(replica: u32, src: &str) -> Self {
+        Self {
+            replica,
+            next_seq: 0,
+            chars: entries(src, 0, 0),
+        }
+    }
+
+    /// Returns the replica this layer assigns IDs for.
+    pub fn replica(&self) -> u32 {
+        self.replica
+    }
+
+    /// Names the byte `index` of the current text. With [`Bias::Before`] the position follows the `char` before it,
+    /// and with [`Bias::After`] the `char` after it, which decides where it ends up when text is inserted there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the text or doesn't lie on a `char` boundary.
+    pub fn position_at(&self, index: usize, bias: Bias) -> LogicalPosition {
+        let entry = self.entry_at(index);
+        match bias {
+            Bias::Before => self.chars[..entry].iter().rev().find(|c| c.live).map_or(LogicalPosition::Start, |c| LogicalPosition::After(c.id)),
+            Bias::After => self.chars[entry..].iter().find(|c| c.live).map_or(LogicalPosition::End, |c| LogicalPosition::Before(c.id)),
+        }
+    }
+
+    /// Finds the byte index of `position` in the current text.
+    ///
+    /// A position next to a deleted `char` resolves to where that `char` was, and one next to a `char` this layer
+    /// has never seen resolves to the end of the text.
+    pub fn resolve(&self, position: LogicalPosition) -> usize {
+        let entry = match position {
+            LogicalPosition::Start => 0,
+            LogicalPosition::End => self.chars.len(),
+            LogicalPosition::After(id) => self.chars.iter().position(|c| c.id == id).map_or(self.chars.len(), |entry| entry + 1),
+            LogicalPosition::Before(id) => self.chars.iter().position(|c| c.id == id).unwrap_or(self.chars.len()),
+        };
+        self.chars[..entry].iter().filter(|c| c.live).map(|c| c.len as usize).sum()
+    }
+
+    /// Updates the layer for a local `edit` that replaced text with `new_text`, giving the inserted `char`s new IDs from this replica.
+    pub fn apply(&mut self, edit: &Edit, new_text: &str) {
+        let seq = self.next_seq;
+        self.next_seq += new_text.chars().count() as u64;
+        self.apply_as(edit, new_text, self.replica, seq);
+    }
+
+    /// Updates the layer for an `edit` that replaced text with `new_text`, giving the inserted `char`s the IDs
+    /// from `replica` numbered upwards from `first_seq`, as assigned by the peer that made the edit.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the edit doesn't fit the current text.
+    pub fn apply_as(&mut self, edit: &Edit, new_text: &str, replica: u32, first_seq: u64) {
+        let start = self.entry_at(edit.replaced.start);
+        let end = self.entry_at(edit.replaced.end);
+        self.chars[start..end].iter_mut().for_each(|c| c.live = false);
+        // Inserting after the removed text keeps positions that followed it after the new text too
+        self.chars.splice(end..end, entries(new_text, replica, first_seq));
+    }
+
+    /// Returns the position in `chars` of the first entry preceded by exactly `index` bytes of live text.
+    fn entry_at(&self, index: usize) -> usize {
+        let mut pos = 0;
+        for (entry, c) in self.chars.iter().enumerate() {
+            if pos == index {
+                return entry;
+            }
+            if c.live {
+                pos += c.len as usize;
+                assert!(pos <= index, "index {} is not on a char boundary", index);
+            }
+        }
+        assert_eq!(pos, index, "index {} is out of bounds", index);
+        self.chars.len()
+    }
+}
+
+fn entries(text: &str, replica: u32, first_seq: u64) -> Vec<CharEntry> {
+    text.chars()
+        .zip(first_seq..)
+        .map(|(c, seq)| CharEntry { id: CharId { replica, seq }, len: c.len_utf8() as u8, live: true })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn positions_survive_concurrent_edits() {
+        let base = "ab\ncd";
+        let mut alice = OwnedLineColLookup::new(base.to_string());
+        let mut bob = OwnedLineColLookup::new(base.to_string());
+        let mut alice_ids = LogicalPositions::new(1, base);
+        let mut bob_ids = LogicalPositions::new(2, base);
+        // Alice pins a diagnostic to the `c`, then deletes the `b` while Bob inserts a line
+        let pinned = alice_ids.position_at(3, Bias::After);
+        let deletion = alice.apply_edit(1..2, "");
+        alice_ids.apply(&deletion, "");
+        let insertion = bob.apply_edit(0..0, "é\n");
+        bob_ids.apply(&insertion, "é\n");
+        // Each applies the other's edit, rebased onto their own text, with the other's IDs
+        let rebased = alice.apply_edit(0..0, "é\n");
+        alice_ids.apply_as(&rebased, "é\n", 2, 0);
+        let rebased = bob.apply_edit(4..5, "");
+        bob_ids.apply_as(&rebased, "", 1, 0);
+        assert_eq!(alice.src(), bob.src());
+        assert_eq!(alice_ids.resolve(pinned), 5);
+        assert_eq!(bob_ids.resolve(pinned), 5);
+        assert_eq!(bob.get(bob_ids.resolve(pinned)), (3, 1));
+    }
+
+    #[test]
+    fn positions_around_deleted_text() {
+        let mut ids = LogicalPositions::new(1, "abc");
+        let after_a = ids.position_at(1, Bias::Before);
+        let before_c = ids.position_at(2, Bias::After);
+        ids.apply(&Edit { replaced: 0..3, new_len: 1, lines: None }, "x");
+        assert_eq!(ids.resolve(after_a), 0);
+        assert_eq!(ids.resolve(before_c), 0);
+        assert_eq!(ids.resolve(LogicalPosition::End), 1);
+        assert_eq!(ids.position_at(1, Bias::Before), LogicalPosition::After(CharId { replica: 1, seq: 0 }));
+    }
+}
@@ -0,0 +1,410 @@
+use crate::{Edit, LineColLookup, OwnedLineColLookup, StrLookup};
+#[cfg(feature = "grapheme-lite")]
+use crate::clusters::{cluster_col, DEFAULT_SEGMENTER};
+#[cfg(feature = "grapheme-lite")]
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// The units in which the Language Server Protocol counts the characters of a line, as negotiated by client and server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PositionEncoding {
+    /// Characters are UTF-8 code units, i.e. bytes.
+    Utf8,
+    /// Characters are UTF-16 code units. This is the protocol's default, and the only encoding every client supports.
+    #[default]
+    Utf16,
+    /// Characters are Unicode scalar values, i.e. `char`s.
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Parses the name the protocol uses for the encoding, such as `"utf-16"`.
+    pub fn from_lsp_name(name: &str) -> Option<Self> {
+        match name {
+            "utf-8" => Some(Self::Utf8),
+            "utf-16" => Some(Self::Utf16),
+            "utf-32" => Some(Self::Utf32),
+            _ => None,
+        }
+    }
+
+    /// Returns the name the protocol uses for the encoding.
+    pub fn lsp_name(self) -> &'static str {
+        match self {
+            Self::Utf8 => "utf-8",
+            Self::Utf16 => "utf-16",
+            Self::Utf32 => "utf-32",
+        }
+    }
+
+    /// Returns the number of units of the encoding in `text`.
+    pub(crate) fn len_of(self, text: &str) -> usize {
+        match self {
+            Self::Utf8 => text.len(),
+            _ if text.is_ascii() => text.len(),
+            Self::Utf16 => text.encode_utf16().count(),
+            Self::Utf32 => text.chars().count(),
+        }
+    }
+}
+
+/// The units a column can be counted in by [`LineColLookup::convert_col`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColumnUnit {
+    /// The units of one of the [`PositionEncoding`]s.
+    Encoding(PositionEncoding),
+    /// Grapheme clusters, as [`LineColLookup::get_by_cluster`] counts them.
+    #[cfg(feature = "grapheme-lite")]
+    Cluster,
+}
+
+impl From<PositionEncoding> for ColumnUnit {
+    fn from(encoding: PositionEncoding) -> Self {
+        Self::Encoding(encoding)
+    }
+}
+
+/// A position as sent by the Language Server Protocol: a 0-based line, and a 0-based character offset into it
+/// counted in the negotiated [`PositionEncoding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+/// The length of a line as the Language Server Protocol measures it, excluding its line terminator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct LineLength {
+    /// The length in bytes.
+    pub bytes: usize,
+    /// The length in the units of the [`PositionEncoding`] it was measured in.
+    pub units: usize,
+}
+
+/// Converts an LSP position into a byte index into the source of any [`StrLookup`], as [`LineColLookup::lsp_position_index`] does.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = ShardedLineColLookup::new("a\n😀b");
+/// assert_eq!(lsp_position_index(&lookup, LspPosition { line: 1, character: 2 }, PositionEncoding::Utf16), 6);
+/// ```
+pub fn lsp_position_index<L: StrLookup + ?Sized>(lookup: &L, position: LspPosition, encoding: PositionEncoding) -> usize {
+    index_in_line(lookup, position.line as usize + 1, position.character as usize, encoding).unwrap_or_else(|| lookup.src().len())
+}
+
+/// Finds the byte index `character` units of `encoding` into the 1-based `line`, as [`lsp_position_index`] does,
+/// or `None` if there is no such line.
+fn index_in_line<L: StrLookup + ?Sized>(lookup: &L, line: usize, character: usize, encoding: PositionEncoding) -> Option<usize> {
+    let Range { start, end } = lookup.line_range(line)?;
+    let line = &lookup.src()[start..end];
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let offset = match encoding {
+        PositionEncoding::Utf8 => {
+            let mut offset = character.min(line.len());
+            while !line.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        }
+        PositionEncoding::Utf16 | PositionEncoding::Utf32 => {
+            let mut units = 0;
+            let mut offset = line.len();
+            for (i, c) in line.char_indices() {
+                units += if encoding == PositionEncoding::Utf16 { c.len_utf16() } else { 1 };
+                if units > character {
+                    offset = i;
+                    break;
+                }
+            }
+            offset
+        }
+    };
+    Some(start + offset)
+}
+
+/// Converts a 1-based column on the 1-based `line` of any [`StrLookup`] from one [`ColumnUnit`] to another,
+/// as [`LineColLookup::convert_col`] does.
+///
+/// Lines are segmented into grapheme clusters with the default [`Segmenter`](crate::Segmenter) each time they are needed.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let lookup = ShardedLineColLookup::new("a😀b\nc");
+/// assert_eq!(convert_col(&lookup, 1, 4, PositionEncoding::Utf16, PositionEncoding::Utf32), Some(3));
+/// ```
+pub fn convert_col<L>(lookup: &L, line: usize, col: usize, from: impl Into<ColumnUnit>, to: impl Into<ColumnUnit>) -> Option<usize>
+where
+    L: StrLookup + ?Sized,
+{
+    let index = match from.into() {
+        ColumnUnit::Encoding(encoding) => index_in_line(lookup, line, col.checked_sub(1)?, encoding)?,
+        #[cfg(feature = "grapheme-lite")]
+        ColumnUnit::Cluster => {
+            let Range { start, end } = lookup.line_range(line)?;
+            let text = &lookup.src()[start..end];
+            let mut boundaries = Vec::new();
+            DEFAULT_SEGMENTER.cluster_starts(text, &mut boundaries);
+            start + cluster_offset(text, &boundaries, col.checked_sub(1)?)
+        }
+    };
+    match to.into() {
+        ColumnUnit::Encoding(encoding) => col_in_line(lookup, line, index, encoding),
+        #[cfg(feature = "grapheme-lite")]
+        ColumnUnit::Cluster => {
+            let Range { start, end } = lookup.line_range(line)?;
+            Some(cluster_col(DEFAULT_SEGMENTER, &lookup.src()[start..end], index - start))
+        }
+    }
+}
+
+/// Returns the 1-based column of `index` on the 1-based `line`, counted in the units of `encoding`.
+fn col_in_line<L: StrLookup + ?Sized>(lookup: &L, line: usize, index: usize, encoding: PositionEncoding) -> Option<usize> {
+    Some(encoding.len_of(&lookup.src()[lookup.line_range(line)?.start..index]) + 1)
+}
+
+/// Returns the offset into the line `text` at which the 0-based `cluster` starts, given the start of each of its clusters,
+/// or the end of the line, excluding any line terminator, if it has fewer clusters.
+#[cfg(feature = "grapheme-lite")]
+fn cluster_offset(text: &str, boundaries: &[usize], cluster: usize) -> usize {
+    let len = text.strip_suffix('\r').unwrap_or(text).len();
+    boundaries.get(cluster).map_or(len, |&offset| offset.min(len))
+}
+
+/// Iterates over the lengths of every line of any [`StrLookup`], as [`LineColLookup::line_lengths`] does.
+pub fn line_lengths<L: StrLookup + ?Sized>(lookup: &L, encoding: PositionEncoding) -> impl Iterator<Item = LineLength> + '_ {
+    (1..=lookup.line_count()).map(move |line| {
+        let line = &lookup.src()[lookup.line_range(line).expect("the line exists")];
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        LineLength { bytes: line.len(), units: encoding.len_of(line) }
+    })
+}
+
+/// Iterates over the lines of any [`StrLookup`] longer than `limit` units of `encoding`, as [`LineColLookup::lines_longer_than`] does.
+pub fn lines_longer_than<L>(lookup: &L, limit: usize, encoding: PositionEncoding) -> impl Iterator<Item = (usize, usize, Range<usize>)> + '_
+where
+    L: StrLookup + ?Sized,
+{
+    line_lengths(lookup, encoding).enumerate().filter(move |(_, length)| length.units > limit).map(move |(line, length)| {
+        let start = lookup.line_range(line + 1).expect("the line exists").start;
+        let tail = index_in_line(lookup, line + 1, limit, encoding).expect("the line exists");
+        (line + 1, length.units, tail..start + length.bytes)
+    })
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Converts an LSP position into a byte index into the source.
+    ///
+    /// As the protocol requires, a character offset past the end of its line means the end of the line,
+    /// excluding any line terminator, and a line past the end of the source means the end of the source.
+    /// An offset into the middle of a character is moved back to the start of that character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a\n😀b");
+    /// let position = LspPosition { line: 1, character: 2 };
+    /// assert_eq!(lookup.lsp_position_index(position, PositionEncoding::Utf16), 6);
+    /// assert_eq!(lookup.lsp_position_index(position, PositionEncoding::Utf32), 7);
+    /// ```
+    pub fn lsp_position_index(&self, position: LspPosition, encoding: PositionEncoding) -> usize {
+        lsp_position_index(self, position, encoding)
+    }
+
+    /// Converts a 1-based column on the 1-based `line` from one [`ColumnUnit`] to another,
+    /// for example a UTF-16 column from an LSP client to a `char` or grapheme cluster column, walking the line once.
+    ///
+    /// A column inside a character is moved back to the start of that character,
+    /// and one inside a grapheme cluster counts that cluster as [`get_by_cluster`](Self::get_by_cluster) does.
+    /// A column past the end of the line means the end of the line, excluding any line terminator.
+    /// Returns `None` if there is no such line, or `col` is 0.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a😀b\nc");
+    /// assert_eq!(lookup.convert_col(1, 4, PositionEncoding::Utf16, PositionEncoding::Utf32), Some(3));
+    /// assert_eq!(lookup.convert_col(1, 3, PositionEncoding::Utf32, PositionEncoding::Utf8), Some(6));
+    /// assert_eq!(lookup.convert_col(3, 1, PositionEncoding::Utf8, PositionEncoding::Utf16), None);
+    /// ```
+    pub fn convert_col(&self, line: usize, col: usize, from: impl Into<ColumnUnit>, to: impl Into<ColumnUnit>) -> Option<usize> {
+        let index = match from.into() {
+            ColumnUnit::Encoding(encoding) => index_in_line(self, line, col.checked_sub(1)?, encoding)?,
+            #[cfg(feature = "grapheme-lite")]
+            ColumnUnit::Cluster => self.cluster_index_in_line(line, col.checked_sub(1)?)?,
+        };
+        match to.into() {
+            ColumnUnit::Encoding(encoding) => col_in_line(self, line, index, encoding),
+            #[cfg(feature = "grapheme-lite")]
+            ColumnUnit::Cluster => Some(self.get_by_cluster(index).1),
+        }
+    }
+
+    /// Finds the byte index at which the 0-based `cluster` of the 1-based `line` starts,
+    /// or the end of the line, excluding any line terminator, if it has fewer clusters.
+    #[cfg(feature = "grapheme-lite")]
+    fn cluster_index_in_line(&self, line: usize, cluster: usize) -> Option<usize> {
+        let Range { start, end } = self.line_range(line)?;
+        // The same text `get_by_cluster` segments, so that both share the cached boundaries
+        let text = &self.src()[start..end];
+        Some(start + cluster_offset(text, &self.clusters.boundaries(line - 1, text), cluster))
+    }
+
+    /// Iterates over the lengths of every line, excluding line terminators, in bytes and in the units of `encoding`.
+    ///
+    /// These are the largest character offsets a client may send on each line,
+    /// and what a server needs for the end of a range covering the whole document.
+    /// This builds the whole line head table if it has not been built yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a😀\r\n\nb");
+    /// let lengths: Vec<_> = lookup.line_lengths(PositionEncoding::Utf16).map(|length| (length.bytes, length.units)).collect();
+    /// assert_eq!(lengths, [(5, 3), (0, 0), (1, 1)]);
+    /// ```
+    pub fn line_lengths(&self, encoding: PositionEncoding) -> impl Iterator<Item = LineLength> + '_ {
+        line_lengths(self, encoding)
+    }
+
+    /// Iterates over the lines longer than `limit` units of `encoding`, excluding line terminators, as a linter reports them:
+    /// each with its 1-based line number, its length in units of `encoding`, and the byte range of the part past the limit.
+    ///
+    /// A limit falling inside a character puts the whole character in the overflowing part.
+    /// This builds the whole line head table if it has not been built yet.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("short\nwaaaaay too long\nok");
+    /// let long: Vec<_> = lookup.lines_longer_than(8, PositionEncoding::Utf32).collect();
+    /// assert_eq!(long, [(2, 16, 14..22)]);
+    /// assert_eq!(&lookup.src()[14..22], "too long");
+    /// ```
+    pub fn lines_longer_than(&self, limit: usize, encoding: PositionEncoding) -> impl Iterator<Item = (usize, usize, Range<usize>)> + '_ {
+        lines_longer_than(self, limit, encoding)
+    }
+}
+
+impl OwnedLineColLookup {
+    /// Applies an LSP `TextDocumentContentChangeEvent`, updating both the source and the line head table.
+    ///
+    /// A `range` of `None` replaces the whole document, as the protocol does for full-text changes.
+    /// Positions are converted with [`LineColLookup::lsp_position_index`], and the edit applied with [`LineColLookup::apply_edit`],
+    /// so an incremental change doesn't rescan the document. Returns the applied [`Edit`].
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let mut lookup = OwnedLineColLookup::new("let 😀 = 1;\nx".to_string());
+    /// let start = LspPosition { line: 0, character: 4 };
+    /// let end = LspPosition { line: 0, character: 6 };
+    /// lookup.apply_lsp_change(Some(start..end), "y", PositionEncoding::Utf16);
+    /// assert_eq!(lookup.src(), "let y = 1;\nx");
+    /// ```
+    pub fn apply_lsp_change(&mut self, range: Option<Range<LspPosition>>, text: &str, encoding: PositionEncoding) -> Edit {
+        let replaced = match range {
+            Some(range) => {
+                let start = self.lsp_position_index(range.start, encoding);
+                start..self.lsp_position_index(range.end, encoding).max(start)
+            }
+            None => 0..self.src.len(),
+        };
+        self.apply_edit(replaced, text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    fn at(line: u32, character: u32) -> LspPosition {
+        LspPosition { line, character }
+    }
+
+    #[test]
+    fn positions_in_each_encoding() {
+        let lookup = LineColLookup::new("aé😀b\r\nc");
+        let fixed = FixedLineColLookup::<2>::new(lookup.src()).unwrap();
+        let utf16 = [0, 1, 3, 3, 7, 8, 8];
+        let utf32 = [0, 1, 3, 7, 8, 8];
+        let utf8 = [0, 1, 1, 3, 3, 3, 3, 7, 8, 8];
+        for (encoding, expected) in [
+            (PositionEncoding::Utf16, &utf16[..]),
+            (PositionEncoding::Utf32, &utf32[..]),
+            (PositionEncoding::Utf8, &utf8[..]),
+        ] {
+            for (character, &index) in expected.iter().enumerate() {
+                assert_eq!(lookup.lsp_position_index(at(0, character as u32), encoding), index);
+                assert_eq!(lsp_position_index(&fixed as &dyn StrLookup, at(0, character as u32), encoding), index);
+            }
+            assert_eq!(lookup.lsp_position_index(at(1, 5), encoding), 11);
+            assert_eq!(lookup.lsp_position_index(at(9, 0), encoding), 11);
+        }
+        assert_eq!(PositionEncoding::from_lsp_name("utf-32"), Some(PositionEncoding::Utf32));
+        assert_eq!(PositionEncoding::Utf8.lsp_name(), "utf-8");
+    }
+
+    #[test]
+    fn line_lengths_and_columns() {
+        let lookup = LineColLookup::new("aé😀b\r\nc\n");
+        for encoding in [PositionEncoding::Utf8, PositionEncoding::Utf16, PositionEncoding::Utf32] {
+            for (line, length) in lookup.line_lengths(encoding).enumerate() {
+                let end = lookup.lsp_position_index(at(line as u32, length.units as u32), encoding);
+                assert_eq!(end, lookup.line_range(line + 1).unwrap().start + length.bytes);
+            }
+        }
+        let units: Vec<_> = lookup.line_lengths(PositionEncoding::Utf32).map(|length| length.units).collect();
+        // Every column from 1 through one past the end of the line converts to the same position in each encoding
+        let line = "aé😀b";
+        for (col, (index, _)) in line.char_indices().chain(Some((line.len(), ' '))).enumerate() {
+            let utf16 = line[..index].encode_utf16().count() + 1;
+            assert_eq!(lookup.convert_col(1, col + 1, PositionEncoding::Utf32, PositionEncoding::Utf16), Some(utf16));
+            assert_eq!(lookup.convert_col(1, utf16, PositionEncoding::Utf16, PositionEncoding::Utf8), Some(index + 1));
+            assert_eq!(lookup.convert_col(1, index + 1, PositionEncoding::Utf8, PositionEncoding::Utf32), Some(col + 1));
+        }
+        assert_eq!(lookup.convert_col(1, 9, PositionEncoding::Utf16, PositionEncoding::Utf32), Some(5));
+        assert_eq!(lookup.convert_col(1, 0, PositionEncoding::Utf16, PositionEncoding::Utf32), None);
+        let long: Vec<_> = lookup.lines_longer_than(3, PositionEncoding::Utf16).collect();
+        assert_eq!(long, [(1, 5, 3..8)]);
+        assert_eq!(lookup.lines_longer_than(4, PositionEncoding::Utf32).count(), 0);
+        assert_eq!(lookup.lines_longer_than(0, PositionEncoding::Utf8).map(|(line, _, _)| line).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(units, [4, 1, 0]);
+        let sharded = ShardedLineColLookup::with_shard_len(lookup.src(), 3);
+        assert!(line_lengths(&sharded, PositionEncoding::Utf16).eq(lookup.line_lengths(PositionEncoding::Utf16)));
+        assert!(lines_longer_than(&sharded, 3, PositionEncoding::Utf16).eq(long));
+    }
+
+    #[test]
+    #[cfg(feature = "grapheme-lite")]
+    fn cluster_columns() {
+        let lookup = LineColLookup::new("e\u{301}😀b\r\nx");
+        assert_eq!(lookup.convert_col(1, 3, ColumnUnit::Cluster, PositionEncoding::Utf16), Some(5));
+        assert_eq!(lookup.convert_col(1, 5, PositionEncoding::Utf16, ColumnUnit::Cluster), Some(3));
+        // The combining accent is inside the first cluster, which then counts as passed
+        assert_eq!(lookup.convert_col(1, 2, PositionEncoding::Utf32, ColumnUnit::Cluster), Some(2));
+        assert_eq!(lookup.convert_col(1, 9, ColumnUnit::Cluster, PositionEncoding::Utf8), Some(9));
+        assert_eq!(lookup.convert_col(2, 1, ColumnUnit::Cluster, ColumnUnit::Cluster), Some(1));
+        assert_eq!(lookup.convert_col(3, 1, ColumnUnit::Cluster, PositionEncoding::Utf8), None);
+        // Segmenting each line anew finds the same clusters as the lookup's cache
+        let sharded = ShardedLineColLookup::new(lookup.src());
+        for col in 1..=9 {
+            for (from, to) in [(ColumnUnit::Cluster, PositionEncoding::Utf16.into()), (PositionEncoding::Utf8.into(), ColumnUnit::Cluster)] {
+                assert_eq!(convert_col(&sharded, 1, col, from, to), lookup.convert_col(1, col, from, to));
+            }
+        }
+    }
+
+    #[test]
+    fn applying_changes() {
+        let mut lookup = OwnedLineColLookup::new_eager("fn 😀() {\n}\n".to_string());
+        lookup.apply_lsp_change(Some(at(0, 3)..at(0, 5)), "main", PositionEncoding::Utf16);
+        lookup.apply_lsp_change(Some(at(1, 0)..at(1, 0)), "  x\n", PositionEncoding::Utf16);
+        assert_eq!(lookup.src(), "fn main() {\n  x\n}\n");
+        assert_eq!(lookup.get(14), (2, 3));
+        lookup.apply_lsp_change(None, "é\n", PositionEncoding::Utf8);
+        assert_eq!(lookup.src(), "é\n");
+        assert_eq!(lookup.line_count(), 2);
+    }
+}
@@ -0,0 +1,66 @@
+/// Looks up the 1-based line and column numbers of one or more byte indices in a string.
+///
+/// With one index, this expands to a call to [`line_col`](crate::line_col()), which builds no table.
+/// With several, one [`LineColLookup`](crate::LineColLookup) is built over the string and shared by every lookup,
+/// and the positions are returned as an array in argument order.
+///
+/// # Example
+/// ```rust
+/// use line_col::line_col;
+/// let text = "One\nTwo";
+/// assert_eq!(line_col!(text, 5), (2, 2));
+/// assert_eq!(line_col!(text, 0, 5, 7), [(1, 1), (2, 2), (2, 4)]);
+/// ```
+///
+/// # Panics
+///
+/// Panics if any index is greater than the length of the string.
+#[macro_export]
+macro_rules! line_col {
+    ($src:expr, $index:expr $(,)?) => {
+        $crate::line_col(::core::convert::AsRef::<str>::as_ref(&$src), $index)
+    };
+    ($src:expr, $($index:expr),+ $(,)?) => {{
+        let src = &$src;
+        let lookup = $crate::LineColLookup::new(::core::convert::AsRef::<str>::as_ref(src));
+        [$(lookup.get($index)),+]
+    }};
+}
+
+/// Looks up the 1-based line and column numbers of the start and end of a byte range in a string.
+///
+/// Expands to a pair of positions, start first, computed with one shared [`LineColLookup`](crate::LineColLookup).
+///
+/// # Example
+/// ```rust
+/// use line_col::span;
+/// let text = "One\nTwo";
+/// assert_eq!(span!(text, 1..6), ((1, 2), (2, 3)));
+/// ```
+///
+/// # Panics
+///
+/// Panics if either end of the range is greater than the length of the string.
+#[macro_export]
+macro_rules! span {
+    ($src:expr, $range:expr $(,)?) => {{
+        let range: ::core::ops::Range<usize> = $range;
+        let src = &$src;
+        let lookup = $crate::LineColLookup::new(::core::convert::AsRef::<str>::as_ref(src));
+        (lookup.get(range.start), lookup.get(range.end))
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::String;
+
+    #[test]
+    fn macros_match_lookup() {
+        let text = String::from("a\nab\n");
+        let lookup = crate::LineColLookup::new(text.as_str());
+        assert_eq!(line_col!(text, 3), lookup.get(3));
+        assert_eq!(line_col!(&text, 1, 4,), [lookup.get(1), lookup.get(4)]);
+        assert_eq!(span!(text, 1..5), (lookup.get(1), lookup.get(5)));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::LineColLookup;
+use memmap2::Mmap;
+use std::{fs::File, io, path::Path};
+
+/// A memory-mapped file whose contents have been validated as UTF-8.
+pub struct MappedSource {
+    map: Mmap,
+}
+
+impl AsRef<str> for MappedSource {
+    fn as_ref(&self) -> &str {
+        // SAFETY: the mapping was validated as UTF-8 when it was created.
+        unsafe { std::str::from_utf8_unchecked(&self.map) }
+    }
+}
+
+/// A line/column lookup table over a memory-mapped file.
+pub type MmapLineColLookup = LineColLookup<'static, MappedSource>;
+
+impl MmapLineColLookup {
+    /// Memory-maps the file at `path` and creates a lookup table over its contents.
+    ///
+    /// The file is never copied into memory; only the line head table is allocated.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised while opening or mapping the file, or an error of kind
+    /// [`io::ErrorKind::InvalidData`] if the file is not valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while the lookup is alive.
+    /// Doing so may invalidate the UTF-8 check performed here.
+    pub unsafe fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let map = Mmap::map(&file)?;
+        std::str::from_utf8(&map).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        Ok(Self::new(MappedSource { map }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::io::Write;
+
+    #[test]
+    fn from_path_maps_file() {
+        let path = std::env::temp_dir().join(format!("line-col-mmap-{}.txt", std::process::id()));
+        std::fs::File::create(&path).unwrap().write_all(b"a\nab\nabc").unwrap();
+        let lookup = unsafe { MmapLineColLookup::from_path(&path) }.unwrap();
+        assert_eq!(lookup.src(), "a\nab\nabc");
+        assert_eq!(lookup.get(4), (2, 3));
+        assert_eq!(lookup.get(8), (3, 4));
+        drop(lookup);
+        std::fs::remove_file(path).unwrap();
+    }
+}
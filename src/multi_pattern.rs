@@ -0,0 +1,47 @@
+use crate::{LineColLookup, TextSegment};
+use aho_corasick::{AhoCorasick, PatternID};
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Iterates over the successive non-overlapping matches of any of the patterns of `searcher` in the source,
+    /// as [`AhoCorasick::find_iter`] finds them, with the ID of the pattern matched and the positions of the match's ends.
+    ///
+    /// Matches are looked up with one [`LookupCursor`](crate::LookupCursor) moving forward through the source,
+    /// so a match on the same line as the one before it, or the next, is found without a binary search.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `searcher` only supports anchored searches, as [`AhoCorasick::find_iter`] does.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("INFO ok\nWARN disk\nERROR disk");
+    /// let searcher = aho_corasick::AhoCorasick::new(["WARN", "ERROR"]).unwrap();
+    /// let found: Vec<_> = lookup.find_patterns(&searcher).map(|(id, m)| (id.as_usize(), m.start)).collect();
+    /// assert_eq!(found, [(0, (2, 1)), (1, (3, 1))]);
+    /// ```
+    pub fn find_patterns<'a>(&'a self, searcher: &'a AhoCorasick) -> impl Iterator<Item = (PatternID, TextSegment<'a>)> + 'a {
+        let mut cursor = self.cursor();
+        searcher.find_iter(self.src()).map(move |m| (m.pattern(), cursor.segment(m.range())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use aho_corasick::AhoCorasick;
+
+    #[test]
+    fn positioned_pattern_matches() {
+        let text = "über timeout\n\nretry timeout über\n".repeat(50);
+        let lookup = LineColLookup::new(text.as_str());
+        let searcher = AhoCorasick::new(["timeout", "über", "retry"]).unwrap();
+        let mut count = 0;
+        for (id, m) in lookup.find_patterns(&searcher) {
+            assert_eq!(m.text, ["timeout", "über", "retry"][id.as_usize()]);
+            assert_eq!((m.start, m.end), (lookup.get(m.range.start), lookup.get(m.range.end)));
+            count += 1;
+        }
+        assert_eq!(count, 250);
+    }
+}
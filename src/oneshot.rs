@@ -0,0 +1,100 @@
+use crate::Position;
+
+/// Finds the 0-based line containing `index` and the byte index at which that line starts, without building a table.
+const fn line_of(src: &str, index: usize) -> (usize, usize) {
+    if index > src.len() {
+        panic!("Index cannot be greater than the length of the input slice.");
+    }
+    let bytes = src.as_bytes();
+    let (mut line, mut line_start, mut i) = (0, 0, 0);
+    // Iterators aren't usable in a `const fn`
+    while i < index {
+        if bytes[i] == b'\n' {
+            line += 1;
+            line_start = i + 1;
+        }
+        i += 1;
+    }
+    (line, line_start)
+}
+
+/// Looks up the 1-based line and column numbers of the specified byte index in `src`, as [`LineColLookup::get`](crate::LineColLookup::get) does.
+///
+/// This scans `src` up to `index` and allocates nothing, which beats building a [`LineColLookup`](crate::LineColLookup)
+/// when only one position is needed. It can also be evaluated at compile time.
+///
+/// # Example
+/// ```rust
+/// assert_eq!(line_col::line_col("One\nTwo", 5), (2, 2));
+/// const POSITION: (usize, usize) = line_col::line_col("One\nTwo", 7);
+/// assert_eq!(POSITION, (2, 4));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `index` is greater than the length of `src`.
+pub const fn line_col(src: &str, index: usize) -> Position {
+    let (line, line_start) = line_of(src, index);
+    (line + 1, index - line_start + 1)
+}
+
+/// Looks up the 1-based line and column numbers of the specified byte index in `src`,
+/// as [`LineColLookup::get_by_cluster`](crate::LineColLookup::get_by_cluster) does.
+///
+/// Like [`line_col`], this builds no table; only the line containing `index` is segmented, with [`UnicodeSegmenter`](crate::UnicodeSegmenter).
+/// [`line_col_by_cluster_with`] takes another [`Segmenter`](crate::Segmenter).
+///
+/// # Example
+/// ```rust
+/// assert_eq!(line_col::line_col_by_cluster("a\ne\u{301}x", 5), (2, 2));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `index` is greater than the length of `src`.
+#[cfg(feature = "grapheme-clusters")]
+pub fn line_col_by_cluster(src: &str, index: usize) -> Position {
+    line_col_by_cluster_with(src, index, &crate::UnicodeSegmenter)
+}
+
+/// Looks up the 1-based line and column numbers of the specified byte index in `src`,
+/// as [`LineColLookup::get_by_cluster`](crate::LineColLookup::get_by_cluster) does on a lookup built with `segmenter`.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// assert_eq!(line_col_by_cluster_with("a\ne\u{301}x", 5, &LiteSegmenter), (2, 2));
+/// ```
+///
+/// # Panics
+///
+/// Panics if `index` is greater than the length of `src`.
+#[cfg(feature = "grapheme-lite")]
+pub fn line_col_by_cluster_with(src: &str, index: usize, segmenter: &dyn crate::Segmenter) -> Position {
+    let (line, line_start) = line_of(src, index);
+    let rest = &src[line_start..];
+    let text = &rest[..rest.find('\n').unwrap_or(rest.len())];
+    (line + 1, crate::clusters::cluster_col(segmenter, text, index - line_start))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn one_shot_matches_lookup() {
+        let text = "a\nab\n\ne\u{301}👨‍👩‍👦x\r\nok";
+        let lookup = LineColLookup::new(text);
+        for i in 0..=text.len() {
+            assert_eq!(line_col(text, i), lookup.get(i));
+            #[cfg(feature = "grapheme-clusters")]
+            if text.is_char_boundary(i) {
+                assert_eq!(line_col_by_cluster(text, i), lookup.get_by_cluster(i));
+            }
+            #[cfg(feature = "grapheme-lite")]
+            if text.is_char_boundary(i) {
+                assert_eq!(line_col_by_cluster_with(text, i, &LiteSegmenter), lookup.get_by_cluster(i));
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{batch::get_sorted, heads::{HeadsConfig, LineHeads}, LineColLookup, LineTableFormat, Position};
+use rayon::prelude::*;
+
+/// The number of bytes each parallel task scans for newlines.
+const CHUNK_LEN: usize = 1 << 20;
+
+/// The number of indices each parallel task converts in [`LineColLookup::par_get_many`].
+const INDEX_CHUNK_LEN: usize = 1 << 14;
+
+impl<'source, S: AsRef<str> + Sync> LineColLookup<'source, S> {
+    /// Creates a new line/col lookup table, building its line head table on the rayon thread pool.
+    ///
+    /// See [`LineColLookup::precompute_parallel`].
+    pub fn new_parallel(src: S) -> Self {
+        let lookup = Self::new(src);
+        lookup.precompute_parallel();
+        lookup
+    }
+
+    /// Builds the whole line head table now, splitting the source into chunks that are scanned in parallel.
+    ///
+    /// This only pays off for sources of several megabytes or more; chunks are 1 MiB each.
+    pub fn precompute_parallel(&self) {
+        let src = self.src();
+        let config = {
+            let heads = self.read_heads();
+            if heads.covers(src.len()) {
+                return;
+            }
+            heads.config
+        };
+        // Each chunk is scanned as if it were a source of its own, then stitched onto the chunks before it
+        let chunks: Vec<LineHeads> = src.as_bytes()
+            .par_chunks(CHUNK_LEN)
+            .map(|chunk| LineHeads::scan_chunk(HeadsConfig { format: LineTableFormat::Usize, dense: false, ..config }, chunk))
+            .collect();
+        let mut heads = LineHeads::new(config);
+        chunks.iter().for_each(|chunk| heads.append(src.as_bytes(), chunk));
+        *self.write_heads() = heads;
+    }
+
+    /// Looks up the 1-based line and column numbers of each of the specified byte indices on the rayon thread pool.
+    ///
+    /// The indices are split into chunks, each converted by its own task as in [`LineColLookup::get_many`],
+    /// so `sorted_indices` should likewise be in ascending order. Positions are returned in input order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is greater than the length of the input `&str`.
+    pub fn par_get_many(&self, sorted_indices: &[usize]) -> Vec<Position> {
+        let max = match self.max_index(sorted_indices) {
+            Some(max) => max,
+            None => return Vec::new(),
+        };
+        let heads = self.heads_through(max);
+        let chunks: Vec<Vec<Position>> = sorted_indices
+            .par_chunks(INDEX_CHUNK_LEN)
+            .map(|chunk| get_sorted(&heads.starts, chunk))
+            .collect();
+        chunks.concat()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn parallel_matches_sequential() {
+        let mut text = "abc\n\n".repeat(1 << 19);
+        // A character split across the first chunk boundary, and another in a line of its own
+        text.replace_range(super::CHUNK_LEN - 1..super::CHUNK_LEN + 1, "ü");
+        text.replace_range(2 * super::CHUNK_LEN + 1..2 * super::CHUNK_LEN + 3, "é");
+        let parallel = LineColLookup::new_parallel(text.as_str());
+        let sequential = LineColLookup::new_eager(text.as_str());
+        let (parallel, sequential) = (parallel.read_heads(), sequential.read_heads());
+        assert_eq!(parallel.starts.to_vec(), sequential.starts.to_vec());
+        assert_eq!(parallel.scanned, sequential.scanned);
+        for line in 0..sequential.starts.len() {
+            assert_eq!(parallel.is_ascii_line(line), sequential.is_ascii_line(line));
+        }
+        assert!(!parallel.is_ascii_line(sequential.starts.line_of(super::CHUNK_LEN)));
+    }
+
+    #[test]
+    fn parallel_unit_counts() {
+        let mut text = "abc\n\n".repeat(1 << 19);
+        text.replace_range(super::CHUNK_LEN - 1..super::CHUNK_LEN + 1, "ü");
+        text.replace_range(2 * super::CHUNK_LEN + 1..2 * super::CHUNK_LEN + 5, "😀");
+        let lookup = LineColLookup::builder(text.as_str()).char_counts(true).utf16_counts(true).build().unwrap();
+        lookup.precompute_parallel();
+        let mut utf16 = 0;
+        for (chars, (index, c)) in text.char_indices().enumerate() {
+            if chars % 997 == 0 {
+                assert_eq!(lookup.byte_to_char(index), chars);
+                assert_eq!(lookup.byte_to_utf16(index), utf16);
+            }
+            utf16 += c.len_utf16();
+        }
+        assert_eq!(lookup.byte_to_char(text.len()), text.chars().count());
+        assert_eq!(lookup.byte_to_utf16(text.len()), utf16);
+    }
+
+    #[test]
+    fn parallel_line_hashes() {
+        // One line spans a whole chunk without a newline in it
+        let text = "abc\n\n".repeat(1 << 18) + &"x".repeat(super::CHUNK_LEN * 3 / 2) + "\nab\nc";
+        let parallel = LineColLookup::builder(text.as_str()).line_hashes(true).build().unwrap();
+        parallel.precompute_parallel();
+        let sequential = LineColLookup::builder(text.as_str()).line_hashes(true).build().unwrap();
+        sequential.precompute();
+        let (parallel, sequential) = (parallel.read_heads(), sequential.read_heads());
+        for line in 0..sequential.starts.len() {
+            assert_eq!(parallel.recorded_hash(line), sequential.recorded_hash(line));
+        }
+    }
+
+    #[test]
+    fn par_get_many_matches_get_many() {
+        let text = "abc\n\n".repeat(20_000);
+        let lookup = LineColLookup::new(text.as_str());
+        let indices: Vec<usize> = (0..=text.len()).step_by(3).collect();
+        assert_eq!(lookup.par_get_many(&indices), lookup.get_many(&indices));
+    }
+}
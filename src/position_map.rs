@@ -0,0 +1,109 @@
+use crate::{Bias, TransformMap};
+use alloc::vec::Vec;
+use core::{iter::FromIterator, ops::Range};
+
+/// A chain of [`TransformMap`] layers, for tracing positions through pipelines of expansions such as
+/// generated code produced from a template that was itself produced from an original file.
+///
+/// Layers are added in the order their transforms ran, so the first layer maps the original text and the last produces the final output.
+/// Queries map through every layer in turn, keeping each layer's precision; [`PositionMap::flatten`]
+/// trades some of that precision for a single layer that answers in one step.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// // An include inserting "int x;\n" at the start of the original, then a macro expanding "N" to "42"
+/// let mut map = PositionMap::new();
+/// map.push_layer(TransformMap::from_segments([(0..0, 7)]).unwrap());
+/// map.push_layer(TransformMap::from_segments([(15..16, 2)]).unwrap());
+/// // "int y = N;" -> "int x;\nint y = 42;"
+/// assert_eq!(map.to_original(18, Bias::Before), 10);
+/// assert_eq!(map.span_to_original(15..17), 8..9);
+/// assert_eq!(map.to_output(4, Bias::Before), 11);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PositionMap {
+    layers: Vec<TransformMap>,
+}
+
+impl PositionMap {
+    /// Creates an empty chain, which maps every position to itself.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer mapping the current output to a new one.
+    pub fn push_layer(&mut self, layer: TransformMap) {
+        self.layers.push(layer);
+    }
+
+    /// Returns the layers of the chain, from the original text to the final output.
+    pub fn layers(&self) -> &[TransformMap] {
+        &self.layers
+    }
+
+    /// Maps a byte index into the original text through every layer to the final output.
+    pub fn to_output(&self, index: usize, bias: Bias) -> usize {
+        self.layers.iter().fold(index, |index, layer| layer.to_output(index, bias))
+    }
+
+    /// Maps a byte index into the final output back through every layer to the original text.
+    pub fn to_original(&self, index: usize, bias: Bias) -> usize {
+        self.layers.iter().rev().fold(index, |index, layer| layer.to_original(index, bias))
+    }
+
+    /// Maps a byte range of the original text to the final output, widening it at each layer to cover any segment either end fell within.
+    pub fn span_to_output(&self, span: Range<usize>) -> Range<usize> {
+        self.layers.iter().fold(span, |span, layer| layer.span_to_output(span))
+    }
+
+    /// Maps a byte range of the final output back to the original text, widening it at each layer to cover any segment either end fell within.
+    pub fn span_to_original(&self, span: Range<usize>) -> Range<usize> {
+        self.layers.iter().rev().fold(span, |span, layer| layer.span_to_original(span))
+    }
+
+    /// Composes every layer into one with [`TransformMap::compose`].
+    pub fn flatten(&self) -> TransformMap {
+        self.layers.iter().fold(TransformMap::new(), |composed, layer| composed.compose(layer))
+    }
+}
+
+impl Extend<TransformMap> for PositionMap {
+    fn extend<I: IntoIterator<Item = TransformMap>>(&mut self, layers: I) {
+        self.layers.extend(layers);
+    }
+}
+
+impl FromIterator<TransformMap> for PositionMap {
+    fn from_iter<I: IntoIterator<Item = TransformMap>>(layers: I) -> Self {
+        Self { layers: layers.into_iter().collect() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn flattening_agrees_with_chain() {
+        let layers = [
+            TransformMap::from_segments([(2..4, 0), (6..6, 3), (9..12, 1)]).unwrap(),
+            TransformMap::from_segments([(0..1, 2), (5..9, 1)]).unwrap(),
+            TransformMap::from_segments([(3..3, 4)]).unwrap(),
+        ];
+        let map: PositionMap = layers.iter().cloned().collect();
+        let flat = map.flatten();
+        for index in 0..20 {
+            let forward = map.to_output(index, Bias::Before);
+            match flat.segments().find(|(original, _)| original.start <= index && index <= original.end) {
+                Some((_, output)) => assert!(output.contains(&forward) || output.end == forward),
+                None => assert_eq!(flat.to_output(index, Bias::Before), forward),
+            }
+            let back = map.to_original(index, Bias::Before);
+            match flat.segments().find(|(_, output)| output.start <= index && index <= output.end) {
+                Some((original, _)) => assert!(original.contains(&back) || original.end == back),
+                None => assert_eq!(flat.to_original(index, Bias::Before), back),
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::OwnedLineColLookup;
+use std::io::{self, BufRead};
+
+impl OwnedLineColLookup {
+    /// Reads all of `reader` into a new owned lookup table.
+    ///
+    /// The line head table is built while the input is read, so no second scan over the text is needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised by `reader`, or an error of kind [`io::ErrorKind::InvalidData`] if the input is not valid UTF-8.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::from_reader("One\nTwo".as_bytes()).unwrap();
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// ```
+    pub fn from_reader(mut reader: impl BufRead) -> io::Result<Self> {
+        let mut src = String::new();
+        let mut line_heads = vec![0];
+        while reader.read_line(&mut src)? > 0 {
+            if src.ends_with('\n') {
+                line_heads.push(src.len());
+            }
+        }
+        Ok(Self::with_heads(src, line_heads))
+    }
+
+    /// Creates an owned lookup table over the document formed by joining `lines` with `'\n'`.
+    ///
+    /// This suits editor buffers stored as one string per line; the line head table is built while the lines are joined.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let buffer = vec!["One".to_string(), "Two".to_string()];
+    /// let lookup = LineColLookup::from_lines(&buffer);
+    /// assert_eq!(lookup.src(), "One\nTwo");
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// ```
+    pub fn from_lines<I>(lines: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let mut src = String::new();
+        let mut line_heads = vec![0];
+        for (i, line) in lines.into_iter().enumerate() {
+            if i > 0 {
+                src.push('\n');
+                line_heads.push(src.len());
+            }
+            let line = line.as_ref();
+            // Lines may themselves contain newlines; those still start new lines in the joined document
+            line_heads.extend(line.match_indices('\n').map(|(j, _)| src.len() + j + 1));
+            src.push_str(line);
+        }
+        Self::with_heads(src, line_heads)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn from_reader_matches_new() {
+        let text = "a\nab\n\nabc\n";
+        let read = LineColLookup::from_reader(text.as_bytes()).unwrap();
+        let scanned = LineColLookup::new(text);
+        assert_eq!(read.src(), text);
+        for i in 0..=text.len() {
+            assert_eq!(read.get(i), scanned.get(i));
+        }
+    }
+
+    #[test]
+    fn from_reader_rejects_invalid_utf8() {
+        let bytes: &[u8] = b"ok\n\xff\n";
+        let err = LineColLookup::from_reader(bytes).err().unwrap();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn from_lines_joins_lines() {
+        let lookup = LineColLookup::from_lines(["a", "", "b\nc"]);
+        assert_eq!(lookup.src(), "a\n\nb\nc");
+        assert_eq!(lookup.get(2), (2, 1));
+        assert_eq!(lookup.get(5), (4, 1));
+        assert_eq!(LineColLookup::from_lines(Vec::<String>::new()).get(0), (1, 1));
+    }
+}
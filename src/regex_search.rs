@@ -0,0 +1,67 @@
+use crate::{LineColLookup, TextSegment};
+use alloc::vec::Vec;
+use regex::Regex;
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Iterates over the successive non-overlapping matches of `regex` in the source, as [`Regex::find_iter`] finds them,
+    /// with the positions of their ends.
+    ///
+    /// Matches are looked up with one [`LookupCursor`](crate::LookupCursor) moving forward through the source,
+    /// so a match on the same line as the one before it, or the next, is found without a binary search.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("let a = 1;\nlet bc = 22;");
+    /// let regex = regex::Regex::new(r"\d+").unwrap();
+    /// let matches: Vec<_> = lookup.find_positions(&regex).map(|m| (m.text, m.start, m.end)).collect();
+    /// assert_eq!(matches, [("1", (1, 9), (1, 10)), ("22", (2, 10), (2, 12))]);
+    /// ```
+    pub fn find_positions<'a>(&'a self, regex: &'a Regex) -> impl Iterator<Item = TextSegment<'a>> + 'a {
+        let mut cursor = self.cursor();
+        regex.find_iter(self.src()).map(move |m| cursor.segment(m.range()))
+    }
+
+    /// Iterates over the successive non-overlapping matches of `regex` in the source, as [`Regex::captures_iter`] finds them,
+    /// with the span of each capture group, or `None` for a group that didn't participate in the match.
+    ///
+    /// The first group is always the whole match. Like [`find_positions`](Self::find_positions), all lookups share one cursor.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("x = 1\ny = 2");
+    /// let regex = regex::Regex::new(r"(\w) = (\d)").unwrap();
+    /// let names: Vec<_> = lookup.capture_positions(&regex).map(|groups| groups[1].clone().unwrap().start).collect();
+    /// assert_eq!(names, [(1, 1), (2, 1)]);
+    /// ```
+    pub fn capture_positions<'a>(&'a self, regex: &'a Regex) -> impl Iterator<Item = Vec<Option<TextSegment<'a>>>> + 'a {
+        let mut cursor = self.cursor();
+        regex.captures_iter(self.src()).map(move |captures| {
+            captures.iter().map(|group| group.map(|group| cursor.segment(group.range()))).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use regex::Regex;
+
+    #[test]
+    fn positioned_matches() {
+        let text = "fn a() {}\n\nfn bé() {\n}";
+        let lookup = LineColLookup::new(text);
+        let regex = Regex::new(r"fn (\w+)(\(\))?").unwrap();
+        let matches: Vec<_> = lookup.find_positions(&regex).collect();
+        assert_eq!(matches.len(), 2);
+        for m in &matches {
+            assert_eq!((m.start, m.end), (lookup.get(m.range.start), lookup.get(m.range.end)));
+            assert_eq!(m.text, &text[m.range.clone()]);
+        }
+        let names: Vec<_> = lookup.capture_positions(&regex).map(|groups| groups[1].clone().unwrap()).collect();
+        assert_eq!((names[1].text, names[1].start, names[1].end), ("bé", (3, 4), (3, 7)));
+        let optional = Regex::new(r"(x)?b").unwrap();
+        assert_eq!(lookup.capture_positions(&optional).next().unwrap()[1], None);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{LineColLookup, SharedLineColLookup};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock, PoisonError, Weak},
+};
+
+/// A cache of lookup tables keyed by the reference-counted source they were built for.
+///
+/// Independent parts of a program that each need positions in the same `Arc<str>` can ask a registry for its lookup,
+/// and all of them will share one line head table instead of each scanning the source.
+///
+/// Entries only hold weak references to their sources, so a registry never keeps a source alive.
+/// Entries whose sources have been dropped are discarded as new sources are registered.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// use std::sync::Arc;
+/// let src = Arc::<str>::from("One\nTwo");
+/// let lexer = LookupRegistry::global().get(&src);
+/// let checker = LookupRegistry::global().get(&src);
+/// assert_eq!(lexer.get(5), (2, 2));
+/// assert_eq!(checker.get(5), (2, 2));
+/// ```
+#[derive(Default)]
+pub struct LookupRegistry {
+    entries: Mutex<HashMap<usize, Entry>>,
+}
+
+struct Entry {
+    src: Weak<str>,
+    caches: LineColLookup<'static, ()>,
+}
+
+impl LookupRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the registry shared by the whole process.
+    pub fn global() -> &'static Self {
+        static GLOBAL: OnceLock<LookupRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(Self::new)
+    }
+
+    /// Gets a lookup table over `src`, sharing its caches with every other lookup this registry has returned for the same `Arc`.
+    ///
+    /// Sources are identified by address, so equal text in separate allocations gets separate tables.
+    pub fn get(&self, src: &Arc<str>) -> SharedLineColLookup {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        // The weak reference keeps the allocation, and therefore the key, from being reused while the entry exists
+        let key = Arc::as_ptr(src) as *const u8 as usize;
+        if let Some(entry) = entries.get(&key) {
+            if entry.src.strong_count() > 0 {
+                return entry.caches.with_source(Arc::clone(src));
+            }
+        }
+        entries.retain(|_, entry| entry.src.strong_count() > 0);
+        let lookup = SharedLineColLookup::new(Arc::clone(src));
+        entries.insert(key, Entry { src: Arc::downgrade(src), caches: lookup.with_source(()) });
+        lookup
+    }
+
+    /// Returns the number of sources registered, including any that have been dropped but not yet discarded.
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner).len()
+    }
+
+    /// Returns `true` if no sources are registered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn registry_shares_tables() {
+        let registry = LookupRegistry::new();
+        let src = Arc::<str>::from("a\nab");
+        let first = registry.get(&src);
+        assert_eq!(first.get(3), (2, 2));
+        let second = registry.get(&src);
+        assert!(Arc::ptr_eq(&first.line_heads, &second.line_heads));
+        let other = registry.get(&Arc::<str>::from("a\nab"));
+        assert!(!Arc::ptr_eq(&first.line_heads, &other.line_heads));
+        assert_eq!(registry.len(), 2);
+        drop((src, first, second));
+        registry.get(&Arc::<str>::from("b"));
+        // `other` is still alive, and the entry for "b" is only discarded by the next registration
+        assert_eq!(registry.len(), 2);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::{ChunkedLineColLookup, TextSource};
+use core::ops::Range;
+use ropey::{Rope, RopeSlice};
+
+/// A line/column lookup table over a [`ropey::Rope`], built from its chunks without materializing the whole text.
+///
+/// Char columns use the rope's own char index instead of walking the line.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let rope = ropey::Rope::from_str("One\nTwö");
+/// let lookup = RopeLineColLookup::new(&rope);
+/// assert_eq!(lookup.get(5), (2, 2));
+/// assert_eq!(lookup.get_by_char(8), (2, 4));
+/// ```
+pub type RopeLineColLookup<'rope> = ChunkedLineColLookup<&'rope Rope>;
+
+/// Counts the chars beginning within `range` from the rope's char index, for [`TextSource::char_count`].
+fn rope_char_count(byte_to_char: impl Fn(usize) -> usize, char_to_byte: impl Fn(usize) -> usize, range: Range<usize>) -> usize {
+    let (start, end) = (byte_to_char(range.start), byte_to_char(range.end));
+    // A range ending inside a char still contains that char's first byte
+    end - start + (char_to_byte(end) != range.end) as usize
+}
+
+impl TextSource for Rope {
+    fn len(&self) -> usize {
+        self.len_bytes()
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        Rope::chunks(self)
+    }
+
+    fn chunks_in(&self, range: Range<usize>) -> impl Iterator<Item = &str> {
+        self.byte_slice(range).chunks()
+    }
+
+    fn char_count(&self, range: Range<usize>) -> usize {
+        rope_char_count(|i| self.byte_to_char(i), |i| self.char_to_byte(i), range)
+    }
+}
+
+impl TextSource for RopeSlice<'_> {
+    fn len(&self) -> usize {
+        self.len_bytes()
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        RopeSlice::chunks(self)
+    }
+
+    fn chunks_in(&self, range: Range<usize>) -> impl Iterator<Item = &str> {
+        self.byte_slice(range).chunks()
+    }
+
+    fn char_count(&self, range: Range<usize>) -> usize {
+        rope_char_count(|i| self.byte_to_char(i), |i| self.char_to_byte(i), range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use ropey::Rope;
+
+    #[test]
+    fn rope_matches_contiguous() {
+        let text = "a\nä€b\n\ne\u{301}👨‍👩‍👦x\r\nok\n".repeat(200);
+        let rope = Rope::from_str(&text);
+        assert!(rope.chunks().count() > 1);
+        let lookup = RopeLineColLookup::new(&rope);
+        let contiguous = LineColLookup::new(text.as_str());
+        let slice = ChunkedLineColLookup::new(rope.slice(..));
+        for i in 0..=text.len() {
+            assert_eq!(lookup.get(i), contiguous.get(i));
+            assert_eq!(lookup.get_by_char(i), contiguous.get_by_char(i));
+            assert_eq!(slice.get_by_char(i), contiguous.get_by_char(i));
+            #[cfg(feature = "grapheme-clusters")]
+            assert_eq!(lookup.get_by_cluster(i), contiguous.get_by_cluster(i));
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{LineColLookup, Position};
+use alloc::string::String;
+use core::{ops::Range, str::{MatchIndices, RMatchIndices}};
+
+/// A span of the source found by one of the search or segmentation queries, such as [`LineColLookup::match_positions`],
+/// with the 1-based line and byte column numbers of its ends as [`LineColLookup::get`] reports them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextSegment<'a> {
+    pub text: &'a str,
+    pub range: Range<usize>,
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A pattern that [`LineColLookup::match_positions`] can search for, standing in for the unstable [`core::str::pattern::Pattern`].
+///
+/// It is implemented for the same types as the standard library's patterns: string slices, `char`s,
+/// slices and arrays of `char`s, and `char` predicates.
+pub trait MatchPattern<'a> {
+    /// The iterator over the pattern's matches in a haystack.
+    type Matches: Iterator<Item = (usize, &'a str)>;
+
+    /// The iterator over the pattern's matches in a haystack, from last to first.
+    type RMatches: Iterator<Item = (usize, &'a str)>;
+
+    /// Finds the pattern's successive non-overlapping matches in `haystack`, as [`str::match_indices`] does.
+    fn match_indices(self, haystack: &'a str) -> Self::Matches;
+
+    /// Finds the pattern's successive non-overlapping matches in `haystack` in reverse, as [`str::rmatch_indices`] does.
+    fn rmatch_indices(self, haystack: &'a str) -> Self::RMatches;
+}
+
+macro_rules! impl_match_pattern {
+    ($([$($generics:tt)*] $ty:ty;)*) => {
+        $(
+            impl<'a, $($generics)*> MatchPattern<'a> for $ty {
+                type Matches = MatchIndices<'a, $ty>;
+                type RMatches = RMatchIndices<'a, $ty>;
+
+                fn match_indices(self, haystack: &'a str) -> Self::Matches {
+                    haystack.match_indices(self)
+                }
+
+                fn rmatch_indices(self, haystack: &'a str) -> Self::RMatches {
+                    haystack.rmatch_indices(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_match_pattern! {
+    ['b] &'b str;
+    ['b] &'b String;
+    [] char;
+    ['b] &'b [char];
+    ['b, const N: usize] &'b [char; N];
+    [const N: usize] [char; N];
+}
+
+impl<'a, F: FnMut(char) -> bool> MatchPattern<'a> for F {
+    type Matches = MatchIndices<'a, F>;
+    type RMatches = RMatchIndices<'a, F>;
+
+    fn match_indices(self, haystack: &'a str) -> Self::Matches {
+        haystack.match_indices(self)
+    }
+
+    fn rmatch_indices(self, haystack: &'a str) -> Self::RMatches {
+        haystack.rmatch_indices(self)
+    }
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Iterates over the successive non-overlapping matches of `pattern` in the source, as [`str::match_indices`] finds them,
+    /// with the positions of their ends.
+    ///
+    /// Matches are looked up with one [`LookupCursor`](crate::LookupCursor) moving forward through the source,
+    /// so a match on the same line as the one before it, or the next, is found without a binary search.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a, b,\nc");
+    /// let commas: Vec<_> = lookup.match_positions(',').map(|m| m.start).collect();
+    /// assert_eq!(commas, [(1, 2), (1, 5)]);
+    /// let letters: Vec<_> = lookup.match_positions(char::is_alphabetic).map(|m| m.text).collect();
+    /// assert_eq!(letters, ["a", "b", "c"]);
+    /// ```
+    pub fn match_positions<'a, P: MatchPattern<'a>>(&'a self, pattern: P) -> impl Iterator<Item = TextSegment<'a>> + 'a
+    where
+        P::Matches: 'a,
+    {
+        let mut cursor = self.cursor();
+        pattern
+            .match_indices(self.src())
+            .map(move |(start, text)| cursor.segment(start..start + text.len()))
+    }
+
+    /// Finds the first match of `pattern` in the source, as [`str::find`] does, returning its byte index and position.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("fn main() {\n    todo!();\n    todo!();\n}");
+    /// assert_eq!(lookup.find_pos("todo!"), Some((16, (2, 5))));
+    /// assert_eq!(lookup.rfind_pos("todo!"), Some((29, (3, 5))));
+    /// assert_eq!(lookup.find_pos('x'), None);
+    /// ```
+    pub fn find_pos<'a, P: MatchPattern<'a>>(&'a self, pattern: P) -> Option<(usize, Position)> {
+        let (index, _) = pattern.match_indices(self.src()).next()?;
+        Some((index, self.get(index)))
+    }
+
+    /// Finds the last match of `pattern` in the source, as [`str::rfind`] does, returning its byte index and position.
+    pub fn rfind_pos<'a, P: MatchPattern<'a>>(&'a self, pattern: P) -> Option<(usize, Position)> {
+        let (index, _) = pattern.rmatch_indices(self.src()).next()?;
+        Some((index, self.get(index)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn positioned_pattern_matches() {
+        let text = "ab\nbé\r\nb";
+        let lookup = LineColLookup::new(text);
+        let expected: Vec<_> = text.match_indices('b').map(|(index, _)| lookup.get(index)).collect();
+        let starts: Vec<_> = lookup.match_positions('b').map(|m| m.start).collect();
+        assert_eq!(starts, expected);
+        let ends: Vec<_> = lookup.match_positions(&['é', '\r'][..]).map(|m| m.end).collect();
+        assert_eq!(ends, [(2, 4), (2, 5)]);
+        let needle = String::from("\r\n");
+        assert_eq!(lookup.match_positions(&needle).next().unwrap().range, 6..8);
+        assert_eq!(lookup.match_positions("x").next(), None);
+        assert_eq!(lookup.find_pos('b'), Some((1, (1, 2))));
+        assert_eq!(lookup.rfind_pos('b'), Some((8, (3, 1))));
+        assert_eq!(lookup.rfind_pos(|c: char| !c.is_ascii()), Some((4, (2, 2))));
+    }
+}
@@ -0,0 +1,145 @@
+use crate::{LineColLookup, TextSegment};
+use core::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    fn segment(&self, range: Range<usize>) -> TextSegment<'_> {
+        self.cursor().segment(range)
+    }
+
+    /// Finds the grapheme cluster containing a byte index, or `None` at the end of the source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let text = "x = 👨‍👩‍👦;";
+    /// let lookup = LineColLookup::new(text);
+    /// let cluster = lookup.grapheme_at(text.find('\u{200d}').unwrap()).unwrap();
+    /// assert_eq!(cluster.text, "👨‍👩‍👦");
+    /// assert_eq!((cluster.range.start, cluster.start), (4, (1, 5)));
+    /// ```
+    pub fn grapheme_at(&self, index: usize) -> Option<TextSegment<'_>> {
+        let range = self.cluster_range(index);
+        (!range.is_empty()).then(|| self.segment(range))
+    }
+
+    /// Finds the Unicode word containing a byte index, as split by UAX #29 word boundaries,
+    /// or ending at it if the index is just past a word, as a cursor after typing one is.
+    ///
+    /// A word is a segment containing at least one alphanumeric character, as with [`UnicodeSegmentation::unicode_words`],
+    /// so nothing is found in whitespace or punctuation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let text = "let user_name = 1;";
+    /// let lookup = LineColLookup::new(text);
+    /// let word = lookup.word_at(6).unwrap();
+    /// assert_eq!((word.text, word.range, word.end), ("user_name", 4..13, (1, 14)));
+    /// assert_eq!(lookup.word_at(13).unwrap().text, "user_name");
+    /// assert_eq!(lookup.word_at(15), None);
+    /// ```
+    pub fn word_at(&self, index: usize) -> Option<TextSegment<'_>> {
+        let (line, _) = self.line_of(index);
+        let line = self.line_range(line + 1)?;
+        let mut previous = None;
+        for (start, word) in self.src()[line.clone()].split_word_bound_indices() {
+            let range = line.start + start..line.start + start + word.len();
+            let is_word = word.chars().any(char::is_alphanumeric);
+            if range.contains(&index) {
+                return if is_word { Some(range) } else { previous }.map(|range| self.segment(range));
+            }
+            previous = is_word.then_some(range);
+        }
+        previous.filter(|range| range.end == index).map(|range| self.segment(range))
+    }
+
+    /// Iterates over the sentences of the source, as split by UAX #29 sentence boundaries.
+    ///
+    /// Each sentence includes the whitespace following it, so together the sentences cover the whole source.
+    /// Line breaks always end sentences under UAX #29, so a sentence never spans lines.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("It works. Mostly!\nSee below.");
+    /// let sentences: Vec<_> = lookup.sentences().map(|sentence| (sentence.text, sentence.start)).collect();
+    /// assert_eq!(sentences, [("It works. ", (1, 1)), ("Mostly!\n", (1, 11)), ("See below.", (2, 1))]);
+    /// ```
+    pub fn sentences(&self) -> impl Iterator<Item = TextSegment<'_>> + '_ {
+        self.src()
+            .split_sentence_bound_indices()
+            .map(move |(start, sentence)| self.segment(start..start + sentence.len()))
+    }
+
+    /// Finds the sentence containing a byte index, or `None` at the end of the source.
+    ///
+    /// Only the line containing the index is segmented, since sentences never span lines.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    pub fn sentence_at(&self, index: usize) -> Option<TextSegment<'_>> {
+        let (line, _) = self.line_of(index);
+        let line = self.line_range(line + 1)?;
+        // The terminating '\n' belongs to the line's last sentence
+        let end = (line.end + 1).min(self.src().len());
+        self.src()[line.start..end]
+            .split_sentence_bound_indices()
+            .map(|(start, sentence)| line.start + start..line.start + start + sentence.len())
+            .find(|range| range.contains(&index))
+            .map(|range| self.segment(range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn finding_graphemes() {
+        let text = "ae\u{301}\r\nb";
+        let lookup = LineColLookup::new(text);
+        let grapheme = lookup.grapheme_at(2).unwrap();
+        assert_eq!((grapheme.text, grapheme.range, grapheme.start, grapheme.end), ("e\u{301}", 1..4, (1, 2), (1, 5)));
+        assert_eq!(lookup.grapheme_at(5).unwrap().text, "\r\n");
+        assert_eq!(lookup.grapheme_at(6).unwrap().start, (2, 1));
+        assert_eq!(lookup.grapheme_at(7), None);
+    }
+
+    #[test]
+    fn finding_words() {
+        let text = "foo,bär(x)\nnext";
+        let lookup = LineColLookup::new(text);
+        let words: Vec<_> = (0..=text.len()).map(|index| lookup.word_at(index).map(|word| word.text)).collect();
+        let expected = [
+            Some("foo"), Some("foo"), Some("foo"), Some("foo"),
+            Some("bär"), Some("bär"), Some("bär"), Some("bär"), Some("bär"),
+            Some("x"), Some("x"), None,
+            Some("next"), Some("next"), Some("next"), Some("next"), Some("next"),
+        ];
+        assert_eq!(words, expected);
+        assert_eq!(lookup.word_at(12).unwrap().start, (2, 1));
+    }
+
+    #[test]
+    fn finding_sentences() {
+        let text = "One. Two\nlines? Three";
+        let lookup = LineColLookup::new(text);
+        let ranges: Vec<_> = lookup.sentences().map(|sentence| sentence.range).collect();
+        assert_eq!(ranges, [0..5, 5..9, 9..16, 16..21]);
+        let second = lookup.sentence_at(8).unwrap();
+        assert_eq!((second.text, second.start, second.end), ("Two\n", (1, 6), (2, 1)));
+        assert_eq!(lookup.sentence_at(12).unwrap().range, 9..16);
+        assert_eq!(lookup.sentence_at(text.len()), None);
+        assert_eq!(lookup.sentences().map(|sentence| sentence.text).collect::<String>(), text);
+    }
+}
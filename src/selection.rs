@@ -0,0 +1,119 @@
+use crate::{LineColLookup, Position};
+use core::ops::Range;
+
+/// Which end of a [`Selection`] its head, the end that moves as the selection is extended, is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SelectionDirection {
+    /// The head is after the anchor, as when selecting by dragging forwards.
+    #[default]
+    Forward,
+    /// The head is before the anchor, as when selecting by dragging backwards.
+    Backward,
+}
+
+/// An editor selection: a fixed anchor byte index and a head byte index that moves as the selection is extended.
+///
+/// The head may be before or after the anchor, so the selection also has a direction;
+/// [`range`](Self::range) gives the selected bytes in increasing order either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Selection {
+    pub anchor: usize,
+    pub head: usize,
+}
+
+impl Selection {
+    /// Creates a selection of the bytes in `range`, with its head at the end of `range` given by `direction`.
+    ///
+    /// A reversed `range` is taken to mean the same bytes as its normalized form.
+    pub fn from_range(range: Range<usize>, direction: SelectionDirection) -> Self {
+        let (start, end) = (range.start.min(range.end), range.start.max(range.end));
+        match direction {
+            SelectionDirection::Forward => Self { anchor: start, head: end },
+            SelectionDirection::Backward => Self { anchor: end, head: start },
+        }
+    }
+
+    /// Gets the selected bytes, from the earlier end of the selection to the later.
+    pub fn range(&self) -> Range<usize> {
+        self.anchor.min(self.head)..self.anchor.max(self.head)
+    }
+
+    /// Gets which end of the selection its head is at. An empty selection is [`SelectionDirection::Forward`].
+    pub fn direction(&self) -> SelectionDirection {
+        if self.head < self.anchor {
+            SelectionDirection::Backward
+        } else {
+            SelectionDirection::Forward
+        }
+    }
+
+    /// Returns `true` if the selection is a bare cursor, selecting nothing.
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Looks up the 1-based line and column numbers of a selection's anchor and head, in that order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either end of the selection is greater than the length of the input `&str`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("One\nTwo");
+    /// let selection = Selection::from_range(1..5, SelectionDirection::Backward);
+    /// assert_eq!(lookup.selection_positions(selection), ((2, 2), (1, 2)));
+    /// assert_eq!(lookup.extend_to_full_lines(selection.range()), 0..7);
+    /// ```
+    pub fn selection_positions(&self, selection: Selection) -> (Position, Position) {
+        let mut cursor = self.cursor();
+        (cursor.get(selection.anchor), cursor.get(selection.head))
+    }
+
+    /// Extends a byte range to cover every line it touches in full, including their terminating `'\n'`s,
+    /// as an editor does before cutting or moving whole lines.
+    ///
+    /// A non-empty range ending at the start of a line doesn't touch that line, so selecting a line by dragging
+    /// to the start of the next one extends to that line alone. An empty range touches the line it is on,
+    /// and a reversed range is normalized first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either end of `range` is greater than the length of the input `&str`.
+    pub fn extend_to_full_lines(&self, range: Range<usize>) -> Range<usize> {
+        let (start, end) = (range.start.min(range.end), range.start.max(range.end));
+        let mut cursor = self.cursor();
+        let (first, _) = cursor.get(start);
+        let (last, _) = if end > start { cursor.get(end - 1) } else { (first, 0) };
+        let line_start = self.line_range(first).expect("the line exists").start;
+        let line_end = self.line_range(last).expect("the line exists").end;
+        // Take in the terminating '\n', unless the last line is the source's final line, which has none
+        line_start..(line_end + 1).min(self.src().len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn normalizing_selections() {
+        let backward = Selection { anchor: 7, head: 2 };
+        assert_eq!((backward.range(), backward.direction()), (2..7, SelectionDirection::Backward));
+        let (start, end) = (backward.anchor, backward.head);
+        assert_eq!(Selection::from_range(start..end, SelectionDirection::Backward), backward);
+        assert_eq!(Selection::from_range(2..7, SelectionDirection::Forward), Selection { anchor: 2, head: 7 });
+        assert!(Selection { anchor: 3, head: 3 }.is_empty());
+
+        let lookup = LineColLookup::new("ab\ncd\r\nef\n");
+        assert_eq!(lookup.extend_to_full_lines(1..1), 0..3);
+        assert_eq!(lookup.extend_to_full_lines(1..3), 0..3);
+        assert_eq!(lookup.extend_to_full_lines(Selection { anchor: 3, head: 1 }.range()), 0..3);
+        assert_eq!(lookup.extend_to_full_lines(1..4), 0..7);
+        assert_eq!(lookup.extend_to_full_lines(8..10), 7..10);
+        assert_eq!(lookup.extend_to_full_lines(10..10), 10..10);
+    }
+}
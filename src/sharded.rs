@@ -0,0 +1,223 @@
+use crate::{heads::push_line_starts, sync, sync::RwLock, LineLookup, Position, StrLookup};
+use alloc::{vec, vec::Vec};
+use core::{marker::PhantomData, ops::Range};
+
+/// The default number of source bytes in each shard of a [`ShardedLineColLookup`].
+const DEFAULT_SHARD_LEN: usize = 16 << 20;
+
+/// A line/column lookup table for very large sources that only builds line head tables for the shards it is queried in.
+///
+/// The source is split into fixed-size shards. A coarse table records the number of the first line of each shard,
+/// which only requires counting newlines, and the line starts of a shard are only recorded once a lookup lands in it.
+/// Memory use is therefore bounded by the shards actually touched, rather than the size of the source.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let text = "One\nTwo\nThree";
+/// let lookup = ShardedLineColLookup::with_shard_len(text, 4);
+/// assert_eq!(lookup.get(10), (3, 3));
+/// assert_eq!(lookup.get(5), (2, 2));
+/// ```
+pub struct ShardedLineColLookup<'source, S = &'source str> {
+    src: S,
+    shard_len: usize,
+    shards: RwLock<Shards>,
+    _source: PhantomData<&'source str>,
+}
+
+struct Shards {
+    /// The 0-based line on which each shard starts, for every shard counted so far.
+    first_lines: Vec<usize>,
+    /// The byte index at which the line each shard starts on begins, for every shard counted so far.
+    first_line_starts: Vec<usize>,
+    /// The line starts within each shard that has been queried.
+    starts: Vec<Option<Vec<usize>>>,
+}
+
+impl<'source, S: AsRef<str>> ShardedLineColLookup<'source, S> {
+    /// Creates a sharded lookup table over `src` with 16 MiB shards.
+    pub fn new(src: S) -> Self {
+        Self::with_shard_len(src, DEFAULT_SHARD_LEN)
+    }
+
+    /// Creates a sharded lookup table over `src` whose shards are `shard_len` bytes long.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard_len` is 0.
+    pub fn with_shard_len(src: S, shard_len: usize) -> Self {
+        assert!(shard_len > 0, "Shards cannot be empty.");
+        let count = src.as_ref().len().div_ceil(shard_len).max(1);
+        Self {
+            src,
+            shard_len,
+            shards: RwLock::new(Shards { first_lines: vec![0], first_line_starts: vec![0], starts: vec![None; count] }),
+            _source: PhantomData,
+        }
+    }
+
+    /// Gets a reference to the source string.
+    pub fn src(&self) -> &str {
+        self.src.as_ref()
+    }
+
+    /// Returns the number of shards whose line starts have been recorded.
+    pub fn indexed_shards(&self) -> usize {
+        sync::read(&self.shards).starts.iter().filter(|starts| starts.is_some()).count()
+    }
+
+    /// Returns the byte range covered by `shard`.
+    fn shard_range(&self, shard: usize) -> (usize, usize) {
+        let len = self.src().len();
+        ((shard * self.shard_len).min(len), ((shard + 1) * self.shard_len).min(len))
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`LineColLookup::get`](crate::LineColLookup::get) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the input `&str`.
+    ///
+    /// # Notes
+    /// The first lookup in a shard counts the newlines in every earlier shard not yet counted, then records the shard's line starts.
+    pub fn get(&self, index: usize) -> Position {
+        let bytes = self.src().as_bytes();
+        if index > bytes.len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let shard = (index / self.shard_len).min(bytes.len().saturating_sub(1) / self.shard_len);
+        {
+            let shards = sync::read(&self.shards);
+            if shards.first_lines.len() > shard && shards.starts[shard].is_some() {
+                return self.locate(&shards, shard, index);
+            }
+        }
+        let mut shards = sync::write(&self.shards);
+        while shards.first_lines.len() <= shard {
+            self.count_next(&mut shards);
+        }
+        self.index_shard(&mut shards, shard);
+        self.locate(&shards, shard, index)
+    }
+
+    /// Returns the number of lines in the source.
+    ///
+    /// This counts the newlines in every shard not yet counted, and records the line starts of the last shard.
+    pub fn line_count(&self) -> usize {
+        self.get(self.src().len()).0
+    }
+
+    /// Returns the byte range of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    ///
+    /// Like [`get`](Self::get), this counts the shards up to the one the line starts in, then records that shard's line starts.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let line = line.checked_sub(1)?;
+        let start = self.line_start(line)?;
+        let end = self.line_start(line + 1).map_or(self.src().len(), |next| next - 1);
+        Some(start..end)
+    }
+
+    /// Finds the byte index at which the 0-based `line` starts, or `None` if there is no such line.
+    fn line_start(&self, line: usize) -> Option<usize> {
+        let mut shards = sync::write(&self.shards);
+        let count = shards.starts.len();
+        while shards.first_lines.len() <= count && shards.first_lines.last().is_some_and(|&first| first <= line) {
+            self.count_next(&mut shards);
+        }
+        let shard = (shards.first_lines.partition_point(|&first| first <= line) - 1).min(count - 1);
+        if line == shards.first_lines[shard] {
+            return Some(shards.first_line_starts[shard]);
+        }
+        self.index_shard(&mut shards, shard);
+        let starts = shards.starts[shard].as_deref().unwrap_or_default();
+        starts.get(line - shards.first_lines[shard] - 1).copied()
+    }
+
+    /// Counts the newlines in the first shard not yet counted, recording the line the shard after it starts on.
+    fn count_next(&self, shards: &mut Shards) {
+        let bytes = self.src().as_bytes();
+        let counted = shards.first_lines.len() - 1;
+        let (start, end) = self.shard_range(counted);
+        let newlines = bytes[start..end].iter().filter(|&&b| b == b'\n').count();
+        let first_line = shards.first_lines[counted] + newlines;
+        // Without a newline in this shard, the next one carries on the line this one started on
+        let first_line_start = bytes[start..end]
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(shards.first_line_starts[counted], |i| start + i + 1);
+        shards.first_lines.push(first_line);
+        shards.first_line_starts.push(first_line_start);
+    }
+
+    /// Records the line starts within `shard`, if they haven't been already.
+    fn index_shard(&self, shards: &mut Shards, shard: usize) {
+        let (start, end) = self.shard_range(shard);
+        shards.starts[shard].get_or_insert_with(|| {
+            let mut starts = Vec::new();
+            push_line_starts(&mut starts, &self.src().as_bytes()[start..end], start);
+            starts
+        });
+    }
+
+    /// Finds the position of `index` within `shard`, which must have been counted and indexed.
+    fn locate(&self, shards: &Shards, shard: usize, index: usize) -> Position {
+        let starts = shards.starts[shard].as_deref().unwrap_or_default();
+        let in_shard = starts.partition_point(|&line_start| line_start <= index);
+        let line_start = match in_shard {
+            // The line began in an earlier shard
+            0 => shards.first_line_starts[shard],
+            _ => starts[in_shard - 1],
+        };
+        (shards.first_lines[shard] + in_shard + 1, index - line_start + 1)
+    }
+}
+
+impl<S: AsRef<str>> LineLookup for ShardedLineColLookup<'_, S> {
+    fn get(&self, index: usize) -> Position {
+        ShardedLineColLookup::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        ShardedLineColLookup::line_count(self)
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        ShardedLineColLookup::line_range(self, line)
+    }
+}
+
+impl<S: AsRef<str>> StrLookup for ShardedLineColLookup<'_, S> {
+    fn src(&self) -> &str {
+        ShardedLineColLookup::src(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn sharded_matches_default() {
+        let text = "a\nab\n\nabcdefgh\nxyz".repeat(20);
+        let lookup = LineColLookup::new(text.as_str());
+        for shard_len in [1, 3, 7, 64, 1000] {
+            let sharded = ShardedLineColLookup::with_shard_len(text.as_str(), shard_len);
+            for i in (0..=text.len()).rev() {
+                assert_eq!(sharded.get(i), lookup.get(i));
+            }
+            // A fresh table, so that lines are found before their shards are counted
+            let sharded = ShardedLineColLookup::with_shard_len(text.as_str(), shard_len);
+            for line in [3, 0, 1, 41, 40, 80, 81, 82, 100] {
+                assert_eq!(sharded.line_range(line), lookup.line_range(line));
+            }
+            assert_eq!(sharded.line_count(), lookup.line_count());
+        }
+        let sharded = ShardedLineColLookup::with_shard_len(text.as_str(), 16);
+        sharded.get(40);
+        assert_eq!(sharded.indexed_shards(), 1);
+        assert_eq!(ShardedLineColLookup::new("").get(0), (1, 1));
+        assert_eq!(ShardedLineColLookup::new("").line_range(1), Some(0..0));
+        assert_eq!(ShardedLineColLookup::new("a\n").line_range(2), Some(2..2));
+    }
+}
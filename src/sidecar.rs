@@ -0,0 +1,162 @@
+use crate::{heads::fnv1a, LineColLookup};
+use std::{
+    convert::TryInto,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::UNIX_EPOCH,
+};
+
+/// Identifies a sidecar index file and the version of its layout.
+const MAGIC: &[u8; 8] = b"LINECOL1";
+
+/// How many bytes from each end of the source are hashed into a sidecar's fingerprint.
+const FINGERPRINT_LEN: usize = 64 << 10;
+
+/// The size of a sidecar's header: the magic number, then the source length, modification time, fingerprint and line count.
+const HEADER_LEN: usize = MAGIC.len() + 4 * 8;
+
+/// Hashes the first and last [`FINGERPRINT_LEN`] bytes of `src` with 64-bit FNV-1a.
+///
+/// Hashing the whole of a multi-gigabyte source would cost as much as rebuilding the index,
+/// so the fingerprint only guards against edits near its ends that keep its length and modification time.
+fn fingerprint(src: &[u8]) -> u64 {
+    let head = &src[..src.len().min(FINGERPRINT_LEN)];
+    let tail = &src[src.len().saturating_sub(FINGERPRINT_LEN)..];
+    fnv1a(head.iter().chain(tail))
+}
+
+/// Returns the modification time of the file at `path` in nanoseconds since the Unix epoch, or 0 if it is unavailable.
+fn modified_nanos(path: &Path) -> u64 {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |since| since.as_nanos() as u64)
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Builds the whole line head table and writes it to a sidecar file at `sidecar_path`,
+    /// along with the length, modification time and a fingerprint of the source, which was read from `source_path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised while writing the sidecar.
+    pub fn write_sidecar(&self, source_path: impl AsRef<Path>, sidecar_path: impl AsRef<Path>) -> io::Result<()> {
+        let src = self.src().as_bytes();
+        let heads = self.heads_through(src.len());
+        let mut out = BufWriter::new(File::create(sidecar_path)?);
+        out.write_all(MAGIC)?;
+        let header = [src.len() as u64, modified_nanos(source_path.as_ref()), fingerprint(src), heads.starts.len() as u64];
+        for field in header {
+            out.write_all(&field.to_le_bytes())?;
+        }
+        for line in 0..heads.starts.len() {
+            out.write_all(&(heads.starts.get(line) as u64).to_le_bytes())?;
+        }
+        out.flush()
+    }
+
+    /// Creates a lookup table over `src`, which was read from `source_path`, reusing the line head table
+    /// stored in the sidecar file at `sidecar_path` if it is still up to date.
+    ///
+    /// A missing, unreadable or stale sidecar is replaced by one built from scratch.
+    /// Reusing a sidecar costs one read of the file and a pass over the source checking the stored line starts,
+    /// instead of recording them from scratch.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error raised while writing a replacement sidecar.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// # let dir = std::env::temp_dir();
+    /// # let (log, sidecar) = (dir.join("line-col-doc.log"), dir.join("line-col-doc.log.idx"));
+    /// std::fs::write(&log, "One\nTwo").unwrap();
+    /// let text = std::fs::read_to_string(&log).unwrap();
+    /// let lookup = LineColLookup::with_sidecar(text.as_str(), &log, &sidecar).unwrap();
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// // Later runs read the table back instead of scanning the log
+    /// let lookup = LineColLookup::with_sidecar(text.as_str(), &log, &sidecar).unwrap();
+    /// assert_eq!(lookup.get(5), (2, 2));
+    /// # std::fs::remove_file(log).unwrap();
+    /// # std::fs::remove_file(sidecar).unwrap();
+    /// ```
+    pub fn with_sidecar(src: S, source_path: impl AsRef<Path>, sidecar_path: impl AsRef<Path>) -> io::Result<Self> {
+        let source_path = source_path.as_ref();
+        if let Some(line_heads) = read_sidecar(src.as_ref().as_bytes(), source_path, sidecar_path.as_ref()) {
+            return Ok(Self::with_heads(src, line_heads));
+        }
+        let lookup = Self::new(src);
+        lookup.write_sidecar(source_path, sidecar_path)?;
+        Ok(lookup)
+    }
+}
+
+/// Reads the line starts stored in the sidecar at `sidecar_path`, if it exists and matches `src` and the file at `source_path`.
+fn read_sidecar(src: &[u8], source_path: &Path, sidecar_path: &Path) -> Option<Vec<usize>> {
+    let bytes = fs::read(sidecar_path).ok()?;
+    let (header, body) = (bytes.get(..HEADER_LEN)?, &bytes[HEADER_LEN..]);
+    if &header[..MAGIC.len()] != MAGIC {
+        return None;
+    }
+    let field = |i: usize| u64::from_le_bytes(header[MAGIC.len() + i * 8..][..8].try_into().unwrap());
+    let expected = [src.len() as u64, modified_nanos(source_path), fingerprint(src)];
+    if (0..3).any(|i| field(i) != expected[i]) || body.len() as u64 != field(3) * 8 {
+        return None;
+    }
+    let starts: Vec<usize> = body.chunks_exact(8).map(|start| u64::from_le_bytes(start.try_into().unwrap()) as usize).collect();
+    // A corrupt table could otherwise make lookups panic or return nonsense
+    let valid = starts.first() == Some(&0)
+        && starts.windows(2).all(|pair| pair[0] < pair[1] && pair[1] <= src.len() && src[pair[1] - 1] == b'\n')
+        // Every newline must also start a line, which holds exactly when there are as many of them
+        && src.iter().filter(|&&b| b == b'\n').count() == starts.len() - 1;
+    valid.then_some(starts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn sidecar_round_trip_and_staleness() {
+        let dir = std::env::temp_dir();
+        let log = dir.join(format!("line-col-sidecar-{}.log", std::process::id()));
+        let sidecar = log.with_extension("idx");
+        std::fs::write(&log, "a\nab\nabc").unwrap();
+        let text = std::fs::read_to_string(&log).unwrap();
+        let built = LineColLookup::with_sidecar(text.as_str(), &log, &sidecar).unwrap();
+        let reused = LineColLookup::with_sidecar(text.as_str(), &log, &sidecar).unwrap();
+        assert!(reused.read_heads().covers(text.len()));
+        for i in 0..=text.len() {
+            assert_eq!(reused.get(i), built.get(i));
+        }
+        // Same length and sidecar, different contents: the fingerprint no longer matches
+        let changed = "abc\nab\na";
+        let rebuilt = LineColLookup::with_sidecar(changed, &log, &sidecar).unwrap();
+        assert_eq!(rebuilt.get(5), (2, 2));
+        assert_eq!(LineColLookup::with_sidecar(changed, &log, &sidecar).unwrap().get(5), (2, 2));
+        std::fs::remove_file(log).unwrap();
+        std::fs::remove_file(sidecar).unwrap();
+    }
+
+    #[test]
+    fn sidecar_missing_line_start() {
+        let dir = std::env::temp_dir();
+        let log = dir.join(format!("line-col-sidecar-dropped-{}.log", std::process::id()));
+        let sidecar = log.with_extension("idx");
+        std::fs::write(&log, "a\nab\nabc").unwrap();
+        let text = std::fs::read_to_string(&log).unwrap();
+        LineColLookup::new(text.as_str()).write_sidecar(&log, &sidecar).unwrap();
+        // Drop the start of the second line, keeping the header's line count consistent
+        let mut bytes = std::fs::read(&sidecar).unwrap();
+        bytes.drain(super::HEADER_LEN + 8..super::HEADER_LEN + 16);
+        bytes[super::HEADER_LEN - 8..super::HEADER_LEN].copy_from_slice(&2u64.to_le_bytes());
+        std::fs::write(&sidecar, bytes).unwrap();
+        let lookup = LineColLookup::with_sidecar(text.as_str(), &log, &sidecar).unwrap();
+        assert_eq!(lookup.get(3), (2, 2));
+        std::fs::remove_file(log).unwrap();
+        std::fs::remove_file(sidecar).unwrap();
+    }
+}
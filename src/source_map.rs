@@ -0,0 +1,372 @@
+use crate::{LineColLookup, OwnedLineColLookup};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, ops::Range};
+
+/// A handle to a file added to a [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FileId(u32);
+
+impl FileId {
+    /// Returns the index of the file in the order files were added to its map.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// A collection of named sources, each given a [`FileId`] and a range of global byte offsets,
+/// so that a single `usize` can identify a position in any of them.
+///
+/// Global offsets let spans from many files share one compact representation, as in compilers.
+/// Each file's range is one byte longer than its text, so that offsets at the end of a file are never confused
+/// with the start of the next.
+///
+/// A file can be covered by an overlay, such as the unsaved contents of an editor buffer, with [`open_overlay`](Self::open_overlay).
+/// While it is open, the file's text, lookup and locations come from the overlay instead of the text the file was added with.
+/// The overlay is allocated its own range of global offsets, so offsets taken from either text keep resolving against that text.
+///
+/// Like rustc's `--remap-path-prefix`, prefixes of file names can be rewritten in reported [`Location`]s with
+/// [`remap_path_prefix`](Self::remap_path_prefix), so diagnostics from a build machine show paths relative to the workspace.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut map = SourceMap::new();
+/// let main = map.add_file("main.rs", "mod util;\nfn main() {}");
+/// let util = map.add_file("util.rs", "pub fn f() {}");
+/// let global = map.global_offset(util, 4);
+/// let location = map.resolve(global).unwrap();
+/// assert_eq!(location.file, util);
+/// assert_eq!(location.to_string(), "util.rs:1:5");
+/// assert_eq!(map.resolve(map.global_offset(main, 13)).unwrap().to_string(), "main.rs:2:4");
+/// ```
+#[derive(Clone, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    /// The global offset each text starts at, with its file and whether it is the file's overlay, in order of offset.
+    ranges: Vec<(usize, FileId, bool)>,
+    /// The first global offset not yet allocated.
+    end: usize,
+    /// The path prefixes to rewrite and their replacements, in the order they were added.
+    remaps: Vec<(String, String)>,
+}
+
+#[derive(Clone)]
+struct SourceFile {
+    name: String,
+    /// The name after path prefix remapping.
+    display_name: String,
+    disk: SourceText,
+    overlay: Option<SourceText>,
+}
+
+#[derive(Clone)]
+struct SourceText {
+    lookup: OwnedLineColLookup,
+    /// The global offset of the text's first byte.
+    start: usize,
+}
+
+impl SourceFile {
+    fn current(&self) -> &SourceText {
+        self.overlay.as_ref().unwrap_or(&self.disk)
+    }
+}
+
+/// A position resolved by a [`SourceMap`]: the file it lies in and its 1-based line and column numbers there.
+///
+/// Displays as `name:line:column`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Location<'map> {
+    pub file: FileId,
+    /// The name of the file, after any path prefix remapping.
+    pub name: &'map str,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for Location<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.name, self.line, self.col)
+    }
+}
+
+impl SourceMap {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a file, allocating it the next range of global offsets.
+    pub fn add_file(&mut self, name: impl Into<String>, src: impl Into<String>) -> FileId {
+        let file = FileId(self.files.len() as u32);
+        let disk = self.allocate(file, false, src.into());
+        let name = name.into();
+        let display_name = remap(&self.remaps, &name);
+        self.files.push(SourceFile { name, display_name, disk, overlay: None });
+        file
+    }
+
+    /// Rewrites file names starting with the path `from` to start with `to` instead, in the names of locations this map reports.
+    ///
+    /// The prefix must match whole path components, so `/build/src` matches `/build/src/lib.rs` but not `/build/src2/lib.rs`.
+    /// When several prefixes match a name, the one added last is used, as rustc does.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let mut map = SourceMap::new();
+    /// let file = map.add_file("/home/ci/work/src/lib.rs", "fn f() {}");
+    /// map.remap_path_prefix("/home/ci/work", ".");
+    /// assert_eq!(map.location(file, 3).to_string(), "./src/lib.rs:1:4");
+    /// ```
+    pub fn remap_path_prefix(&mut self, from: impl Into<String>, to: impl Into<String>) {
+        self.remaps.push((from.into(), to.into()));
+        for file in &mut self.files {
+            file.display_name = remap(&self.remaps, &file.name);
+        }
+    }
+
+    /// Gets the name of a file after path prefix remapping, as it appears in locations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn display_name(&self, file: FileId) -> &str {
+        &self.files[file.index()].display_name
+    }
+
+    fn allocate(&mut self, file: FileId, overlay: bool, src: String) -> SourceText {
+        let start = self.end;
+        self.end += src.len() + 1;
+        self.ranges.push((start, file, overlay));
+        SourceText { lookup: LineColLookup::new(src), start }
+    }
+
+    /// Finds the first file added with the given name.
+    pub fn file_by_name(&self, name: &str) -> Option<FileId> {
+        self.files.iter().position(|file| file.name == name).map(|file| FileId(file as u32))
+    }
+
+    /// Covers a file with an in-memory text, replacing any overlay already open, and allocates the text its own range of global offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn open_overlay(&mut self, file: FileId, src: impl Into<String>) {
+        self.close_overlay(file);
+        let overlay = self.allocate(file, true, src.into());
+        self.files[file.index()].overlay = Some(overlay);
+    }
+
+    /// Removes a file's overlay, so the file's text is once again the one it was added with, returning the overlay's text.
+    ///
+    /// Global offsets into the overlay no longer resolve, and are never allocated again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn close_overlay(&mut self, file: FileId) -> Option<String> {
+        let overlay = self.files[file.index()].overlay.take()?;
+        let range = self.ranges.partition_point(|&(start, ..)| start < overlay.start);
+        self.ranges.remove(range);
+        Some(overlay.lookup.into_src())
+    }
+
+    /// Returns `true` if a file is covered by an overlay.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn has_overlay(&self, file: FileId) -> bool {
+        self.files[file.index()].overlay.is_some()
+    }
+
+    /// Gets the text a file was added with, even while an overlay covers it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn disk_src(&self, file: FileId) -> &str {
+        self.files[file.index()].disk.lookup.src()
+    }
+
+    /// Returns the number of files in the map.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Returns `true` if the map holds no files.
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Iterates over the IDs of the files in the map, in the order they were added.
+    pub fn files(&self) -> impl Iterator<Item = FileId> {
+        (0..self.files.len() as u32).map(FileId)
+    }
+
+    /// Gets the name of a file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn name(&self, file: FileId) -> &str {
+        &self.files[file.index()].name
+    }
+
+    /// Gets the text of a file, from its overlay if one is open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn src(&self, file: FileId) -> &str {
+        self.files[file.index()].current().lookup.src()
+    }
+
+    /// Gets the lookup table over a file's text, from its overlay if one is open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn lookup(&self, file: FileId) -> &OwnedLineColLookup {
+        &self.files[file.index()].current().lookup
+    }
+
+    /// Returns the range of global offsets allocated to a file's text, which includes its end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map.
+    pub fn global_range(&self, file: FileId) -> Range<usize> {
+        let text = self.files[file.index()].current();
+        text.start..text.start + text.lookup.src().len() + 1
+    }
+
+    /// Converts a byte `offset` into a file's text to a global offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map, or `offset` is greater than the length of the file.
+    pub fn global_offset(&self, file: FileId, offset: usize) -> usize {
+        let file = &self.files[file.index()];
+        let text = file.current();
+        assert!(offset <= text.lookup.src().len(), "offset {} is past the end of {}", offset, file.name);
+        text.start + offset
+    }
+
+    /// Finds the text containing a global offset, and its file and the byte offset into it,
+    /// or `None` if the offset was never allocated or belonged to a closed overlay.
+    fn text_at(&self, global: usize) -> Option<(FileId, &SourceText, usize)> {
+        let &(start, file, overlay) = self.ranges[..self.ranges.partition_point(|&(start, ..)| start <= global)].last()?;
+        let source = &self.files[file.index()];
+        let text = if overlay { source.overlay.as_ref()? } else { &source.disk };
+        let offset = global - start;
+        (offset <= text.lookup.src().len()).then_some((file, text, offset))
+    }
+
+    /// Finds the file containing a global offset and the byte offset into it, or `None` if no file was allocated it.
+    ///
+    /// The offset is into whichever of the file's texts the global offset was allocated from,
+    /// which is not the file's current text if it was taken before an overlay was opened.
+    pub fn local_offset(&self, global: usize) -> Option<(FileId, usize)> {
+        self.text_at(global).map(|(file, _, offset)| (file, offset))
+    }
+
+    /// Resolves a global offset to its file, line and column, or `None` if no file was allocated it.
+    ///
+    /// The position is found in the text the offset was allocated from, whether the file's overlay or the text it was added with.
+    pub fn resolve(&self, global: usize) -> Option<Location<'_>> {
+        let (file, text, offset) = self.text_at(global)?;
+        let (line, col) = text.lookup.get(offset);
+        Some(Location { file, name: &self.files[file.index()].display_name, line, col })
+    }
+
+    /// Resolves a byte `offset` into a file's text to its line and column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `file` belongs to another map, or `offset` is greater than the length of the file.
+    pub fn location(&self, file: FileId, offset: usize) -> Location<'_> {
+        let source = &self.files[file.index()];
+        let (line, col) = source.current().lookup.get(offset);
+        Location { file, name: &source.display_name, line, col }
+    }
+}
+
+/// Applies the last of `remaps` whose prefix matches `name` on a path component boundary.
+fn remap(remaps: &[(String, String)], name: &str) -> String {
+    for (from, to) in remaps.iter().rev() {
+        if let Some(rest) = name.strip_prefix(from.as_str()) {
+            let boundary = rest.is_empty() || from.ends_with(['/', '\\']) || rest.starts_with(['/', '\\']);
+            if boundary {
+                let mut remapped = to.clone();
+                remapped.push_str(rest);
+                return remapped;
+            }
+        }
+    }
+    name.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn resolving_global_offsets() {
+        let mut map = SourceMap::new();
+        let a = map.add_file("a", "x\ny");
+        let empty = map.add_file("empty", "");
+        let b = map.add_file("b", "z");
+        assert_eq!((map.global_range(a), map.global_range(empty), map.global_range(b)), (0..4, 4..5, 5..7));
+        assert_eq!(map.local_offset(3), Some((a, 3)));
+        assert_eq!(map.local_offset(4), Some((empty, 0)));
+        assert_eq!(map.local_offset(6), Some((b, 1)));
+        assert_eq!(map.local_offset(7), None);
+        let location = map.resolve(2).unwrap();
+        assert_eq!((location.name, location.line, location.col), ("a", 2, 1));
+        assert_eq!(map.files().map(|file| map.name(file)).collect::<Vec<_>>(), ["a", "empty", "b"]);
+    }
+
+    #[test]
+    fn overlays() {
+        let mut map = SourceMap::new();
+        let a = map.add_file("a", "one");
+        let b = map.add_file("b", "x");
+        let on_disk = map.global_offset(a, 2);
+        assert_eq!(map.file_by_name("a"), Some(a));
+        map.open_overlay(a, "on\ne");
+        assert!(map.has_overlay(a));
+        assert_eq!((map.src(a), map.disk_src(a)), ("on\ne", "one"));
+        assert_eq!(map.global_range(a), 6..11);
+        assert_eq!(map.location(a, 3).to_string(), "a:2:1");
+        assert_eq!(map.resolve(map.global_offset(a, 3)).unwrap().to_string(), "a:2:1");
+        assert_eq!(map.resolve(on_disk).unwrap().to_string(), "a:1:3");
+        map.open_overlay(a, "new");
+        assert_eq!(map.resolve(8), None);
+        assert_eq!(map.global_range(a), 11..15);
+        assert_eq!(map.close_overlay(a).as_deref(), Some("new"));
+        assert_eq!(map.close_overlay(a), None);
+        assert_eq!((map.src(a), map.global_range(a)), ("one", 0..4));
+        assert_eq!(map.resolve(12), None);
+        assert_eq!(map.resolve(map.global_offset(b, 1)).unwrap().to_string(), "b:1:2");
+    }
+
+    #[test]
+    fn remapping_path_prefixes() {
+        let mut map = SourceMap::new();
+        let lib = map.add_file("/build/src/lib.rs", "");
+        let other = map.add_file("/build/src2/lib.rs", "");
+        map.remap_path_prefix("/build", "/ws");
+        map.remap_path_prefix("/build/src/", "");
+        let windows = map.add_file("C:\\build\\x.rs", "");
+        map.remap_path_prefix("C:\\build", "ws");
+        assert_eq!(map.display_name(lib), "lib.rs");
+        assert_eq!(map.display_name(other), "/ws/src2/lib.rs");
+        assert_eq!(map.location(windows, 0).to_string(), "ws\\x.rs:1:1");
+        assert_eq!(map.name(lib), "/build/src/lib.rs");
+        map.remap_path_prefix("/build/src", "");
+        assert_eq!(map.resolve(0).unwrap().name, "/lib.rs");
+        assert_eq!(map.file_by_name("/build/src2/lib.rs"), Some(other));
+    }
+}
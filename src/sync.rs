@@ -0,0 +1,156 @@
+//! The lock guarding the lazily built caches: `std`'s [`RwLock`] when available, and a spin lock otherwise.
+
+#[cfg(feature = "std")]
+pub(crate) use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Locks `lock` for reading, ignoring poisoning since the caches are always left in a valid state.
+#[cfg(feature = "std")]
+pub(crate) fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Locks `lock` for writing, ignoring poisoning since the caches are always left in a valid state.
+#[cfg(feature = "std")]
+pub(crate) fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Gets mutable access to the value in `lock`, ignoring poisoning since the caches are always left in a valid state.
+#[cfg(feature = "std")]
+pub(crate) fn get_mut<T>(lock: &mut RwLock<T>) -> &mut T {
+    lock.get_mut().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) use spin::{get_mut, read, write, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[cfg(not(feature = "std"))]
+mod spin {
+    use core::{
+        cell::UnsafeCell,
+        hint,
+        ops::{Deref, DerefMut},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    /// The lock state while a writer holds the lock; any other value is the number of readers.
+    const WRITER: usize = usize::MAX;
+
+    /// A minimal readers-writer spin lock for targets without `std`.
+    #[derive(Debug, Default)]
+    pub(crate) struct RwLock<T> {
+        state: AtomicUsize,
+        value: UnsafeCell<T>,
+    }
+
+    // SAFETY: the lock hands out `&T` to many threads or `&mut T` to one, like `std::sync::RwLock`.
+    unsafe impl<T: Send> Send for RwLock<T> {}
+    unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+    impl<T> RwLock<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Self { state: AtomicUsize::new(0), value: UnsafeCell::new(value) }
+        }
+    }
+
+    pub(crate) struct RwLockReadGuard<'a, T> {
+        lock: &'a RwLock<T>,
+    }
+
+    pub(crate) struct RwLockWriteGuard<'a, T> {
+        lock: &'a RwLock<T>,
+    }
+
+    /// Locks `lock` for reading, spinning while a writer holds it.
+    pub(crate) fn read<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+        loop {
+            let state = lock.state.load(Ordering::Relaxed);
+            if state < WRITER - 1
+                && lock.state.compare_exchange_weak(state, state + 1, Ordering::Acquire, Ordering::Relaxed).is_ok()
+            {
+                return RwLockReadGuard { lock };
+            }
+            hint::spin_loop();
+        }
+    }
+
+    /// Gets mutable access to the value in `lock`, which needs no locking since the borrow is exclusive.
+    pub(crate) fn get_mut<T>(lock: &mut RwLock<T>) -> &mut T {
+        lock.value.get_mut()
+    }
+
+    /// Locks `lock` for writing, spinning while anyone else holds it.
+    pub(crate) fn write<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+        while lock.state.compare_exchange_weak(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_err() {
+            hint::spin_loop();
+        }
+        RwLockWriteGuard { lock }
+    }
+
+    impl<T> Deref for RwLockReadGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: no writer can hold the lock while this guard exists.
+            unsafe { &*self.lock.value.get() }
+        }
+    }
+
+    impl<T> Drop for RwLockReadGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lock.state.fetch_sub(1, Ordering::Release);
+        }
+    }
+
+    impl<T> Deref for RwLockWriteGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            // SAFETY: this guard holds the lock exclusively.
+            unsafe { &*self.lock.value.get() }
+        }
+    }
+
+    impl<T> DerefMut for RwLockWriteGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            // SAFETY: this guard holds the lock exclusively.
+            unsafe { &mut *self.lock.value.get() }
+        }
+    }
+
+    impl<T> Drop for RwLockWriteGuard<'_, T> {
+        fn drop(&mut self) {
+            self.lock.state.store(0, Ordering::Release);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::{sync::Arc, thread, vec::Vec};
+
+        #[test]
+        fn spin_lock_excludes_writers() {
+            let lock = Arc::new(RwLock::new(0));
+            let (first, second) = (read(&lock), read(&lock));
+            assert_eq!(lock.state.load(Ordering::Relaxed), 2);
+            drop((first, second));
+            let threads: Vec<_> = (0..4)
+                .map(|_| {
+                    let lock = Arc::clone(&lock);
+                    thread::spawn(move || {
+                        for _ in 0..1000 {
+                            let mut value = write(&lock);
+                            *value += 1;
+                        }
+                    })
+                })
+                .collect();
+            for thread in threads {
+                thread.join().unwrap();
+            }
+            assert_eq!(*read(&lock), 4000);
+            assert_eq!(lock.state.load(Ordering::Relaxed), 0);
+        }
+    }
+}
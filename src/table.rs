@@ -0,0 +1,569 @@
+use alloc::{vec, vec::Vec};
+use core::{convert::TryFrom, ops::Range};
+
+/// The storage formats available for a lookup's line head table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineTableFormat {
+    /// Stores each line start as a `usize`. Works for any source.
+    ///
+    /// The first few line starts are stored inline, so sources with only a handful of lines don't allocate a table at all.
+    #[default]
+    Usize,
+    /// Stores each line start as a `u32`, halving the table's memory on 64-bit targets.
+    /// Only sources shorter than 4 GiB can be built with this format; a table that outgrows it
+    /// is converted to [`LineTableFormat::Usize`].
+    U32,
+    /// Stores the distance between consecutive line starts as a variable-length integer,
+    /// with an absolute offset every 64 lines.
+    ///
+    /// Typical source lines fit in one or two bytes each, at the cost of decoding up to 64 deltas per lookup,
+    /// and of re-encoding every line after an edit to the source.
+    Delta,
+    /// Stores line starts in pages of up to 4096 lines, each holding `u32` offsets from the page's first line start.
+    ///
+    /// Lookups search the small page table first and then a single page, which keeps the search within a few
+    /// cache lines on very large sources. Pages are materialized as the source is scanned, and like
+    /// [`LineTableFormat::U32`] this roughly halves the table's memory on 64-bit targets, but for any source length.
+    Paged,
+}
+
+impl LineTableFormat {
+    /// Returns the length of the longest source this format can index.
+    pub fn max_source_len(self) -> usize {
+        match self {
+            Self::Usize => usize::MAX,
+            Self::U32 => u32::MAX as usize,
+            Self::Delta | Self::Paged => usize::MAX,
+        }
+    }
+}
+
+/// The byte indices at which lines start, in one of the [`LineTableFormat`]s.
+#[derive(Debug, Clone)]
+pub(crate) enum LineTable {
+    /// A [`LineTableFormat::Usize`] table that has not outgrown its inline storage yet.
+    Inline(InlineTable),
+    Usize(Vec<usize>),
+    U32(Vec<u32>),
+    Delta(DeltaTable),
+    Paged(PagedTable),
+}
+
+impl LineTable {
+    /// Creates a table in the given format holding only the first line's start.
+    pub(crate) fn new(format: LineTableFormat) -> Self {
+        match format {
+            LineTableFormat::Usize => Self::Inline(InlineTable { starts: [0; INLINE_LINES], len: 1 }),
+            LineTableFormat::U32 => Self::U32(vec![0]),
+            LineTableFormat::Delta => {
+                let mut table = DeltaTable::default();
+                table.push(0);
+                Self::Delta(table)
+            }
+            LineTableFormat::Paged => {
+                let mut table = PagedTable::default();
+                table.push(0);
+                Self::Paged(table)
+            }
+        }
+    }
+
+    /// Returns the format this table is stored in.
+    pub(crate) fn format(&self) -> LineTableFormat {
+        match self {
+            Self::Inline(_) | Self::Usize(_) => LineTableFormat::Usize,
+            Self::U32(_) => LineTableFormat::U32,
+            Self::Delta(_) => LineTableFormat::Delta,
+            Self::Paged(_) => LineTableFormat::Paged,
+        }
+    }
+
+    /// Returns the number of line starts in the table.
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Inline(starts) => starts.len,
+            Self::Usize(starts) => starts.len(),
+            Self::U32(starts) => starts.len(),
+            Self::Delta(starts) => starts.len,
+            Self::Paged(starts) => starts.len,
+        }
+    }
+
+    /// Returns the start of the 0-based `line`.
+    #[inline]
+    pub(crate) fn get(&self, line: usize) -> usize {
+        match self {
+            Self::Inline(starts) => starts.starts[..starts.len][line],
+            Self::Usize(starts) => starts[line],
+            Self::U32(starts) => starts[line] as usize,
+            Self::Delta(starts) => starts.get(line),
+            Self::Paged(starts) => starts.get(line),
+        }
+    }
+
+    /// Returns the number of heap bytes allocated by the table.
+    pub(crate) fn memory_usage(&self) -> usize {
+        match self {
+            Self::Inline(_) => 0,
+            Self::Usize(starts) => starts.capacity() * core::mem::size_of::<usize>(),
+            Self::U32(starts) => starts.capacity() * core::mem::size_of::<u32>(),
+            Self::Delta(starts) => {
+                (starts.anchors.capacity() + starts.block_offsets.capacity()) * core::mem::size_of::<usize>()
+                    + starts.deltas.capacity()
+            }
+            Self::Paged(starts) => starts.memory_usage(),
+        }
+    }
+
+    /// Releases any excess capacity held by the table.
+    pub(crate) fn shrink_to_fit(&mut self) {
+        match self {
+            Self::Inline(_) => {}
+            Self::Usize(starts) => starts.shrink_to_fit(),
+            Self::U32(starts) => starts.shrink_to_fit(),
+            Self::Delta(starts) => {
+                starts.anchors.shrink_to_fit();
+                starts.block_offsets.shrink_to_fit();
+                starts.deltas.shrink_to_fit();
+            }
+            Self::Paged(starts) => {
+                starts.pages.shrink_to_fit();
+                starts.pages.iter_mut().for_each(|page| page.offsets.shrink_to_fit());
+            }
+        }
+    }
+
+    /// Copies all line starts into a `Vec`.
+    pub(crate) fn to_vec(&self) -> Vec<usize> {
+        (0..self.len()).map(|line| self.get(line)).collect()
+    }
+
+    /// Replaces the starts of the 0-based `lines` with `inserted`, after moving every later start by `delta` bytes.
+    ///
+    /// Every later start moves, so this takes time linear in the number of lines after `lines` in any format.
+    /// [`LineTableFormat::Usize`] and [`LineTableFormat::U32`] tables are updated in place, paged tables only re-encode
+    /// the pages holding `lines`, and delta tables re-encode the blocks from the one holding `lines.start` on.
+    pub(crate) fn splice(&mut self, lines: Range<usize>, inserted: &[usize], delta: isize) {
+        let shift = |start: usize| start.wrapping_add(delta as usize);
+        match self {
+            Self::Usize(starts) => {
+                starts[lines.end..].iter_mut().for_each(|start| *start = shift(*start));
+                starts.splice(lines, inserted.iter().copied());
+            }
+            Self::U32(starts) => {
+                let last = starts[lines.end..].last().map(|&start| shift(start as usize));
+                if inserted.last().copied().max(last).map_or(true, |start| u32::try_from(start).is_ok()) {
+                    starts[lines.end..].iter_mut().for_each(|start| *start = shift(*start as usize) as u32);
+                    starts.splice(lines, inserted.iter().map(|&start| start as u32));
+                } else {
+                    *self = Self::Usize(self.to_vec());
+                    self.splice(lines, inserted, delta);
+                }
+            }
+            Self::Delta(starts) => starts.splice(lines, inserted, shift),
+            Self::Paged(starts) => starts.splice(lines, inserted, shift),
+            Self::Inline(_) => {
+                let mut starts = self.to_vec();
+                starts[lines.end..].iter_mut().for_each(|start| *start = shift(*start));
+                starts.splice(lines, inserted.iter().copied());
+                *self = LineTable::new(self.format());
+                self.extend(starts.into_iter().skip(1));
+            }
+        }
+    }
+
+    /// Finds the 0-based line containing `index`, i.e. the last line starting at or before it.
+    pub(crate) fn line_of(&self, index: usize) -> usize {
+        match self {
+            Self::Inline(starts) => starts.starts[..starts.len].partition_point(|&start| start <= index) - 1,
+            Self::Usize(starts) => starts.partition_point(|&start| start <= index) - 1,
+            Self::U32(starts) => starts.partition_point(|&start| start as usize <= index) - 1,
+            Self::Delta(starts) => starts.line_of(index),
+            Self::Paged(starts) => starts.line_of(index),
+        }
+    }
+
+    /// Finds the 0-based line containing `index`, starting from an exponential probe around the line `hint`.
+    ///
+    /// This takes O(log d) time, where `d` is the number of lines between `hint` and the answer,
+    /// so it beats [`LineTable::line_of`] when lookups cluster together.
+    pub(crate) fn line_of_near(&self, hint: usize, index: usize) -> usize {
+        if matches!(self, Self::Delta(_) | Self::Paged(_)) {
+            // Random access into these formats is too slow for probing to pay off
+            return self.line_of(index);
+        }
+
+        let len = self.len();
+        let hint = hint.min(len - 1);
+        // Bracket the answer so that `get(lo) <= index < get(hi)`, treating `get(len)` as infinite
+        let (mut lo, mut hi);
+        let mut step = 1;
+        if self.get(hint) <= index {
+            lo = hint;
+            loop {
+                let probe = lo + step;
+                if probe >= len {
+                    hi = len;
+                    break;
+                }
+                if self.get(probe) <= index {
+                    lo = probe;
+                    step *= 2;
+                } else {
+                    hi = probe;
+                    break;
+                }
+            }
+        } else {
+            hi = hint;
+            loop {
+                // The first line always starts at 0, so this terminates
+                let probe = hi.saturating_sub(step);
+                if self.get(probe) <= index {
+                    lo = probe;
+                    break;
+                }
+                hi = probe;
+                step *= 2;
+            }
+        }
+
+        // Binary search within the bracket for the last line starting at or before `index`
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if self.get(mid) <= index {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+}
+
+impl Extend<usize> for LineTable {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        match self {
+            Self::Inline(inline) => {
+                let mut iter = iter.into_iter();
+                for start in iter.by_ref() {
+                    if inline.len == INLINE_LINES {
+                        // Spill over onto the heap once the inline storage is full
+                        let mut starts = Vec::with_capacity(INLINE_LINES * 2);
+                        starts.extend_from_slice(&inline.starts);
+                        starts.push(start);
+                        starts.extend(iter);
+                        *self = Self::Usize(starts);
+                        return;
+                    }
+                    inline.starts[inline.len] = start;
+                    inline.len += 1;
+                }
+            }
+            Self::Usize(starts) => starts.extend(iter),
+            Self::U32(starts) => {
+                let mut iter = iter.into_iter();
+                for start in iter.by_ref() {
+                    match u32::try_from(start) {
+                        Ok(start) => starts.push(start),
+                        Err(_) => {
+                            // Widen the table rather than truncating starts past 4 GiB
+                            let mut wide: Vec<usize> = starts.iter().map(|&start| start as usize).collect();
+                            wide.push(start);
+                            wide.extend(iter);
+                            *self = Self::Usize(wide);
+                            return;
+                        }
+                    }
+                }
+            }
+            Self::Delta(starts) => iter.into_iter().for_each(|start| starts.push(start)),
+            Self::Paged(starts) => iter.into_iter().for_each(|start| starts.push(start)),
+        }
+    }
+}
+
+/// The number of line starts an [`InlineTable`] can hold.
+const INLINE_LINES: usize = 8;
+
+/// Line starts stored without a heap allocation.
+#[derive(Debug, Clone)]
+pub(crate) struct InlineTable {
+    starts: [usize; INLINE_LINES],
+    len: usize,
+}
+
+/// Appends `value` to `out` in unsigned LEB128: seven bits per byte, least significant first,
+/// with the high bit set on every byte but the last.
+pub(crate) fn push_leb128(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+/// Reads an unsigned LEB128 value from `bytes` at `pos`, advancing `pos` past it.
+///
+/// Returns `None` if the value is truncated or has more bytes than a `usize` could need.
+pub(crate) fn read_leb128(bytes: &[u8], pos: &mut usize) -> Option<usize> {
+    let (mut value, mut shift) = (0usize, 0);
+    loop {
+        let byte = *bytes.get(*pos)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as usize).checked_shl(shift)?;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+}
+
+/// The number of lines in each block of a [`DeltaTable`].
+const DELTA_BLOCK_LINES: usize = 64;
+
+/// Line starts stored as LEB128-encoded deltas, in blocks that each begin with an absolute anchor.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DeltaTable {
+    /// The start of the first line of each block.
+    anchors: Vec<usize>,
+    /// The index into `deltas` at which each block's encoded deltas begin.
+    block_offsets: Vec<usize>,
+    /// The encoded distances from each line start to the next, excluding the first line of each block.
+    deltas: Vec<u8>,
+    /// The number of line starts in the table.
+    len: usize,
+    /// The last line start pushed.
+    last: usize,
+}
+
+impl DeltaTable {
+    fn push(&mut self, start: usize) {
+        if self.len % DELTA_BLOCK_LINES == 0 {
+            self.anchors.push(start);
+            self.block_offsets.push(self.deltas.len());
+        } else {
+            push_leb128(&mut self.deltas, start - self.last);
+        }
+        self.last = start;
+        self.len += 1;
+    }
+
+    /// Iterates over the starts of the lines in `block`, beginning with its anchor.
+    fn block(&self, block: usize) -> impl Iterator<Item = usize> + '_ {
+        let lines = (self.len - block * DELTA_BLOCK_LINES).min(DELTA_BLOCK_LINES);
+        let mut pos = self.block_offsets[block];
+        let mut start = self.anchors[block];
+        core::iter::once(start).chain((1..lines).map(move |_| {
+            start += read_leb128(&self.deltas, &mut pos).expect("deltas are well-formed");
+            start
+        }))
+    }
+
+    fn get(&self, line: usize) -> usize {
+        assert!(line < self.len, "line {} is out of bounds", line);
+        self.block(line / DELTA_BLOCK_LINES).nth(line % DELTA_BLOCK_LINES).unwrap()
+    }
+
+    /// Like [`LineTable::splice`], re-encoding the blocks from the one holding `lines.start` on.
+    fn splice(&mut self, lines: Range<usize>, inserted: &[usize], shift: impl Fn(usize) -> usize) {
+        let first_block = lines.start.min(self.len - 1) / DELTA_BLOCK_LINES;
+        let first_line = first_block * DELTA_BLOCK_LINES;
+        let kept: Vec<usize> = (first_block..self.anchors.len()).flat_map(|block| self.block(block)).collect();
+        let (len, end) = (self.len, lines.end.min(self.len));
+        self.deltas.truncate(self.block_offsets[first_block]);
+        self.anchors.truncate(first_block);
+        self.block_offsets.truncate(first_block);
+        self.len = first_line;
+        let before = kept[..lines.start - first_line].iter().copied();
+        let after = kept[end - first_line..len - first_line].iter().map(|&start| shift(start));
+        before.chain(inserted.iter().copied()).chain(after).for_each(|start| self.push(start));
+    }
+
+    fn line_of(&self, index: usize) -> usize {
+        let block = self.anchors.partition_point(|&anchor| anchor <= index).saturating_sub(1);
+        let in_block = self.block(block).take_while(|&start| start <= index).count();
+        block * DELTA_BLOCK_LINES + in_block.saturating_sub(1)
+    }
+}
+
+/// The most lines a page of a [`PagedTable`] holds.
+const PAGE_LINES: usize = 4096;
+
+/// Line starts stored as a coarse table of pages, each holding offsets relative to its first line start.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PagedTable {
+    pages: Vec<Page>,
+    /// The number of line starts in the table.
+    len: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Page {
+    /// The 0-based number of the page's first line.
+    first_line: usize,
+    /// The start of the page's first line.
+    base: usize,
+    /// The distance of each of the page's line starts from `base`.
+    offsets: Vec<u32>,
+}
+
+impl PagedTable {
+    fn push(&mut self, start: usize) {
+        match self.pages.last_mut() {
+            // A page also ends early if the next offset wouldn't fit in a `u32`
+            Some(page) if page.offsets.len() < PAGE_LINES && start - page.base <= u32::MAX as usize => {
+                page.offsets.push((start - page.base) as u32);
+            }
+            _ => self.pages.push(Page {
+                first_line: self.len,
+                base: start,
+                offsets: vec![0],
+            }),
+        }
+        self.len += 1;
+    }
+
+    /// Finds the index of the page holding the 0-based `line`.
+    fn page_of(&self, line: usize) -> usize {
+        self.pages.partition_point(|page| page.first_line <= line) - 1
+    }
+
+    /// Like [`LineTable::splice`], re-encoding only the pages holding `lines` and moving the later ones as a whole.
+    fn splice(&mut self, lines: Range<usize>, inserted: &[usize], shift: impl Fn(usize) -> usize) {
+        let (first, last) = (self.page_of(lines.start.min(self.len - 1)), self.page_of(lines.end.min(self.len - 1)));
+        let first_line = self.pages[first].first_line;
+        let end_line = self.pages.get(last + 1).map_or(self.len, |page| page.first_line);
+        let before = (first_line..lines.start).map(|line| self.get(line));
+        let after = (lines.end..end_line).map(|line| shift(self.get(line)));
+        let mut rebuilt = PagedTable { pages: Vec::new(), len: first_line };
+        before.chain(inserted.iter().copied()).chain(after).for_each(|start| rebuilt.push(start));
+        let mut later = self.pages.split_off(last + 1);
+        for page in &mut later {
+            page.first_line = page.first_line - end_line + rebuilt.len;
+            page.base = shift(page.base);
+        }
+        self.len = self.len - end_line + rebuilt.len;
+        self.pages.truncate(first);
+        self.pages.append(&mut rebuilt.pages);
+        self.pages.append(&mut later);
+    }
+
+    fn get(&self, line: usize) -> usize {
+        assert!(line < self.len, "line {} is out of bounds", line);
+        let page = &self.pages[self.page_of(line)];
+        page.base + page.offsets[line - page.first_line] as usize
+    }
+
+    fn line_of(&self, index: usize) -> usize {
+        let page = &self.pages[self.pages.partition_point(|page| page.base <= index).saturating_sub(1)];
+        let relative = index.saturating_sub(page.base).min(u32::MAX as usize) as u32;
+        page.first_line + page.offsets.partition_point(|&offset| offset <= relative).saturating_sub(1)
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.pages.capacity() * core::mem::size_of::<Page>()
+            + self.pages.iter().map(|page| page.offsets.capacity() * core::mem::size_of::<u32>()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_agree() {
+        let mut wide = LineTable::new(LineTableFormat::Usize);
+        let mut narrow = LineTable::new(LineTableFormat::U32);
+        wide.extend([3, 7, 8]);
+        narrow.extend([3, 7, 8]);
+        assert_eq!(wide.to_vec(), narrow.to_vec());
+        for index in 0..10 {
+            assert_eq!(wide.line_of(index), narrow.line_of(index));
+        }
+        assert_eq!(narrow.line_of(7), 2);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn u32_table_widens() {
+        let mut table = LineTable::new(LineTableFormat::U32);
+        table.extend([10, u32::MAX as usize, u32::MAX as usize + 5, u32::MAX as usize + 9]);
+        assert_eq!(table.format(), LineTableFormat::Usize);
+        assert_eq!(table.to_vec(), [0, 10, u32::MAX as usize, u32::MAX as usize + 5, u32::MAX as usize + 9]);
+        assert_eq!(table.line_of(u32::MAX as usize + 6), 3);
+    }
+
+    #[test]
+    fn line_of_near_matches_line_of() {
+        let mut table = LineTable::new(LineTableFormat::Usize);
+        table.extend((1..300).map(|i| i * 5));
+        for hint in [0, 1, 7, 150, 298, 299, 1000] {
+            for index in (0..1600).step_by(3) {
+                assert_eq!(table.line_of_near(hint, index), table.line_of(index));
+            }
+        }
+    }
+
+    #[test]
+    fn inline_table_spills() {
+        let mut table = LineTable::new(LineTableFormat::Usize);
+        table.extend(1..INLINE_LINES);
+        assert!(matches!(table, LineTable::Inline(_)));
+        assert_eq!(table.memory_usage(), 0);
+        table.extend(INLINE_LINES..20);
+        assert!(matches!(table, LineTable::Usize(_)));
+        assert_eq!(table.to_vec(), (0..20).collect::<Vec<_>>());
+        assert_eq!(table.line_of(15), 15);
+    }
+
+    #[test]
+    fn delta_table_round_trips() {
+        let starts: Vec<usize> = (0..1000).map(|i| i * i + 2 * i).collect();
+        let mut delta = LineTable::new(LineTableFormat::Delta);
+        delta.extend(starts[1..].iter().copied());
+        assert_eq!(delta.to_vec(), starts);
+        for (line, &start) in starts.iter().enumerate() {
+            assert_eq!(delta.line_of(start), line);
+            assert_eq!(delta.line_of(start + 1), line);
+        }
+        assert_eq!(delta.line_of(usize::MAX), 999);
+    }
+
+    #[test]
+    fn splice_matches_vec() {
+        let starts: Vec<usize> = (0..10_000).map(|i| i * 2).collect();
+        let edits = [
+            (1..1, vec![1], 1),
+            (70..4200, vec![], -8260),
+            (4095..4097, vec![8190, 8191, 8192, 8193], 2),
+            (9000..10_000, vec![18_000], 0),
+        ];
+        for format in [LineTableFormat::Usize, LineTableFormat::U32, LineTableFormat::Delta, LineTableFormat::Paged] {
+            for (lines, inserted, delta) in edits.iter().cloned() {
+                let mut table = LineTable::new(format);
+                table.extend(starts[1..].iter().copied());
+                table.splice(lines.clone(), &inserted, delta);
+                let mut expected = starts.clone();
+                expected[lines.end..].iter_mut().for_each(|start| *start = (*start as isize + delta) as usize);
+                expected.splice(lines, inserted);
+                assert_eq!(table.to_vec(), expected);
+                for (line, &start) in expected.iter().enumerate() {
+                    assert_eq!(table.line_of(start), line);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn paged_table_round_trips() {
+        let starts: Vec<usize> = (0..10_000).map(|i| i * 3 + i / 100).collect();
+        let mut paged = LineTable::new(LineTableFormat::Paged);
+        paged.extend(starts[1..].iter().copied());
+        assert_eq!(paged.to_vec(), starts);
+        for (line, &start) in starts.iter().enumerate() {
+            assert_eq!(paged.line_of(start), line);
+            assert_eq!(paged.line_of(start + 1), line);
+        }
+    }
+}
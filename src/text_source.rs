@@ -0,0 +1,247 @@
+use crate::{heads::count_chars, LineIndex, LineLookup, Position};
+use alloc::{string::String, vec::Vec};
+use core::ops::Range;
+
+/// Text that may be stored in several pieces rather than one contiguous `str`, such as a rope or gap buffer.
+///
+/// The text is the concatenation of the chunks, in order.
+pub trait TextSource {
+    /// Returns the length of the text in bytes.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the text is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the chunks making up the text.
+    fn chunks(&self) -> impl Iterator<Item = &str>;
+
+    /// Iterates over the pieces of the chunks that fall within the byte `range`, whose ends must lie on `char` boundaries.
+    ///
+    /// The default implementation walks the chunks from the start; sources that can seek should override it.
+    fn chunks_in(&self, range: Range<usize>) -> impl Iterator<Item = &str> {
+        let Range { start, end } = range;
+        self.chunks()
+            .scan(0, |offset, chunk| {
+                let chunk_start = *offset;
+                *offset += chunk.len();
+                Some((chunk_start, chunk))
+            })
+            .take_while(move |&(chunk_start, _)| chunk_start < end)
+            .filter_map(move |(chunk_start, chunk)| {
+                let from = start.max(chunk_start) - chunk_start;
+                let to = end.min(chunk_start + chunk.len()) - chunk_start;
+                (from < to).then(|| &chunk[from..to])
+            })
+    }
+
+    /// Counts the `char`s that begin within the byte `range`.
+    ///
+    /// The default implementation walks the chunks from the start; sources that track char offsets should override it.
+    fn char_count(&self, range: Range<usize>) -> usize {
+        let mut chars = 0;
+        let mut offset = 0;
+        for chunk in self.chunks() {
+            let (start, end) = (range.start.max(offset), range.end.min(offset + chunk.len()));
+            if start < end {
+                chars += count_chars(&chunk.as_bytes()[start - offset..end - offset]);
+            }
+            offset += chunk.len();
+            if offset >= range.end {
+                break;
+            }
+        }
+        chars
+    }
+}
+
+impl TextSource for str {
+    fn len(&self) -> usize {
+        str::len(self)
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        core::iter::once(self)
+    }
+}
+
+impl TextSource for String {
+    fn len(&self) -> usize {
+        String::len(self)
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        core::iter::once(self.as_str())
+    }
+}
+
+impl<S: AsRef<str>> TextSource for [S] {
+    fn len(&self) -> usize {
+        self.iter().map(|chunk| chunk.as_ref().len()).sum()
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(AsRef::as_ref)
+    }
+}
+
+impl<S: AsRef<str>> TextSource for Vec<S> {
+    fn len(&self) -> usize {
+        TextSource::len(self.as_slice())
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        TextSource::chunks(self.as_slice())
+    }
+}
+
+impl<T: TextSource + ?Sized> TextSource for &T {
+    fn len(&self) -> usize {
+        T::len(self)
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &str> {
+        T::chunks(self)
+    }
+
+    fn chunks_in(&self, range: Range<usize>) -> impl Iterator<Item = &str> {
+        T::chunks_in(self, range)
+    }
+
+    fn char_count(&self, range: Range<usize>) -> usize {
+        T::char_count(self, range)
+    }
+}
+
+/// A line/column lookup table over a [`TextSource`], which need not be contiguous.
+///
+/// The line head table is built up front, a chunk at a time, so the text is never copied into one `String`.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let buffer = vec!["On", "e\nT", "wö"];
+/// let lookup = ChunkedLineColLookup::new(&buffer);
+/// assert_eq!(lookup.get(5), (2, 2));
+/// assert_eq!(lookup.get_by_char(8), (2, 4));
+/// ```
+pub struct ChunkedLineColLookup<T> {
+    src: T,
+    index: LineIndex,
+}
+
+impl<T: TextSource> ChunkedLineColLookup<T> {
+    /// Creates a lookup table over `src`, scanning all of its chunks.
+    pub fn new(src: T) -> Self {
+        let index = LineIndex::from_chunks(src.chunks());
+        Self { src, index }
+    }
+
+    /// Gets a reference to the source.
+    pub fn src(&self) -> &T {
+        &self.src
+    }
+
+    /// Returns the number of lines in the source.
+    pub fn line_count(&self) -> usize {
+        self.index.line_count()
+    }
+
+    /// Returns the byte range of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        self.index.line_range(line)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, as [`LineColLookup::get`](crate::LineColLookup::get) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn get(&self, index: usize) -> Position {
+        self.index.get(index)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index,
+    /// as [`LineColLookup::get_by_char`](crate::LineColLookup::get_by_char) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    ///
+    /// # Notes
+    /// The chunks before the line are skipped, and the line is walked up to `index`.
+    pub fn get_by_char(&self, index: usize) -> Position {
+        let (line, line_start) = self.index.line_of(index);
+        (line + 1, self.src.char_count(line_start..index) + 1)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index,
+    /// as [`LineColLookup::get_by_cluster`](crate::LineColLookup::get_by_cluster) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    ///
+    /// # Notes
+    /// The line containing `index` is copied out of its chunks before being segmented with [`UnicodeSegmenter`](crate::UnicodeSegmenter);
+    /// [`ChunkedLineColLookup::get_by_cluster_with`] takes another [`Segmenter`](crate::Segmenter).
+    #[cfg(feature = "grapheme-clusters")]
+    pub fn get_by_cluster(&self, index: usize) -> Position {
+        self.get_by_cluster_with(index, &crate::UnicodeSegmenter)
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index,
+    /// as [`LineColLookup::get_by_cluster`](crate::LineColLookup::get_by_cluster) does on a lookup built with `segmenter`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    #[cfg(feature = "grapheme-lite")]
+    pub fn get_by_cluster_with(&self, index: usize, segmenter: &dyn crate::Segmenter) -> Position {
+        let (line, line_start) = self.index.line_of(index);
+        let line_range = self.index.line_range(line + 1).expect("line_of returns an existing line");
+        let text: String = self.src.chunks_in(line_range).collect();
+        (line + 1, crate::clusters::cluster_col(segmenter, text.strip_suffix('\n').unwrap_or(&text), index - line_start))
+    }
+}
+
+impl<T: TextSource> LineLookup for ChunkedLineColLookup<T> {
+    fn get(&self, index: usize) -> Position {
+        ChunkedLineColLookup::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        ChunkedLineColLookup::line_count(self)
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        ChunkedLineColLookup::line_range(self, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn chunked_matches_contiguous() {
+        let chunks = ["a\nä", "b", "\n\nabc", "", "\nü\n"];
+        let text = chunks.concat();
+        let chunked = ChunkedLineColLookup::new(&chunks[..]);
+        let contiguous = LineColLookup::new(text.as_str());
+        assert_eq!(chunked.line_count(), contiguous.line_count());
+        for line in 0..=chunked.line_count() + 1 {
+            assert_eq!(chunked.line_range(line), contiguous.line_range(line));
+        }
+        assert_eq!(chunks.chunks_in(1..6).collect::<Vec<_>>(), ["\nä", "b", "\n"]);
+        for i in 0..=text.len() {
+            assert_eq!(chunked.get(i), contiguous.get(i));
+            assert_eq!(chunked.get_by_char(i), contiguous.get_by_char(i));
+            #[cfg(feature = "grapheme-clusters")]
+            assert_eq!(chunked.get_by_cluster(i), contiguous.get_by_cluster(i));
+            #[cfg(feature = "grapheme-lite")]
+            assert_eq!(chunked.get_by_cluster_with(i, &LiteSegmenter), contiguous.get_by_cluster(i));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{Bias, LineColLookup, OwnedLineColLookup, Position, TransformMap};
+use alloc::string::String;
+#[cfg(feature = "encoding_rs")]
+use encoding_rs::{CoderResult, Encoding};
+
+/// A lookup table over text transcoded to UTF-8 from a legacy encoding, such as Shift-JIS or Windows-1252,
+/// that maps positions between the decoded text and the original bytes.
+///
+/// The map is built from the length each character had in the original encoding, as given by a callback,
+/// so any encoder can supply it. This requires the decoding to have been lossless: a replacement character stands
+/// for an unknown number of original bytes.
+#[cfg_attr(feature = "encoding_rs", doc = "With the `encoding_rs` feature, [`TranscodedLookup::decode`] instead decodes the original bytes itself,")]
+#[cfg_attr(not(feature = "encoding_rs"), doc = "With the `encoding_rs` feature, `TranscodedLookup::decode` instead decodes the original bytes itself,")]
+/// following which bytes became which characters, which also works for stateful encodings and malformed input.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// // Windows-1252 encodes every character in one byte
+/// let lookup = TranscodedLookup::new("caf\u{e9} \u{20ac}\nx".to_string(), |_| 1);
+/// assert_eq!(lookup.to_original(lookup.src().find('x').unwrap()), 7);
+/// assert_eq!(lookup.get_original(7), (2, 1));
+/// assert_eq!(lookup.to_decoded(5), 6);
+/// ```
+#[derive(Clone)]
+pub struct TranscodedLookup {
+    lookup: OwnedLineColLookup,
+    /// The map from the original bytes to the decoded text.
+    map: TransformMap,
+}
+
+impl TranscodedLookup {
+    /// Creates a lookup table over the `decoded` text, where `original_len` gives the number of bytes each character had in the original encoding.
+    pub fn new(decoded: String, mut original_len: impl FnMut(char) -> usize) -> Self {
+        let mut map = TransformMap::new();
+        let mut original = 0;
+        for c in decoded.chars() {
+            let len = original_len(c);
+            if len != c.len_utf8() {
+                map.push(original..original + len, c.len_utf8()).expect("characters are pushed in order");
+            }
+            original += len;
+        }
+        Self {
+            lookup: LineColLookup::new(decoded),
+            map,
+        }
+    }
+
+    /// Decodes `bytes` with `encoding` and creates a lookup table over the decoded text.
+    ///
+    /// A byte order mark is removed, and switches the encoding to the one it marks, as [`Encoding::decode`] does.
+    /// Malformed sequences decode to the replacement character, which maps back to the bytes it replaced.
+    ///
+    /// The bytes are fed to the decoder one at a time, so each run of output maps to exactly the bytes that produced it.
+    /// Escape sequences that only switch the decoder's state, as in ISO-2022-JP, count towards the character after them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let sjis = TranscodedLookup::decode(b"\x82\xa0\nx", encoding_rs::SHIFT_JIS);
+    /// assert_eq!((sjis.src(), sjis.to_original(4)), ("\u{3042}\nx", 3));
+    /// // "\x1b$B" switches to JIS X 0208, where "$\"" is one character, and "\x1b(B" back to ASCII
+    /// let jis = TranscodedLookup::decode(b"\x1b$B$\"\x1b(B\nx", encoding_rs::ISO_2022_JP);
+    /// assert_eq!((jis.src(), jis.to_original(3)), ("\u{3042}\nx", 5));
+    /// assert_eq!(jis.get_original(9), (2, 1));
+    /// ```
+    #[cfg(feature = "encoding_rs")]
+    pub fn decode(bytes: &[u8], encoding: &'static Encoding) -> Self {
+        let mut decoder = encoding.new_decoder();
+        let mut decoded = String::new();
+        let mut map = TransformMap::new();
+        // The start of the bytes that haven't produced any output yet
+        let mut pending = 0;
+        for i in 0..=bytes.len() {
+            let (input, last) = if i < bytes.len() { (&bytes[i..i + 1], false) } else { (&[][..], true) };
+            let written = decoded.len();
+            decoded.reserve(decoder.max_utf8_buffer_length(input.len()).expect("one byte never overflows"));
+            let (result, _, _) = decoder.decode_to_string(input, &mut decoded, last);
+            debug_assert_eq!(result, CoderResult::InputEmpty, "the reserved capacity fits the output");
+            let end = i + input.len();
+            if decoded.len() > written || last {
+                if end - pending != decoded.len() - written {
+                    map.push(pending..end, decoded.len() - written).expect("bytes are pushed in order");
+                }
+                pending = end;
+            }
+        }
+        Self {
+            lookup: LineColLookup::new(decoded),
+            map,
+        }
+    }
+
+    /// Gets the decoded text.
+    pub fn src(&self) -> &str {
+        self.lookup.src()
+    }
+
+    /// Gets the lookup table over the decoded text.
+    pub fn lookup(&self) -> &OwnedLineColLookup {
+        &self.lookup
+    }
+
+    /// Gets the map from the original bytes to the decoded text.
+    pub fn transform(&self) -> &TransformMap {
+        &self.map
+    }
+
+    /// Maps a byte index of the decoded text to the original bytes. An index inside a character maps to the character's start.
+    pub fn to_original(&self, index: usize) -> usize {
+        self.map.to_original(index, Bias::Before)
+    }
+
+    /// Maps a byte offset of the original bytes to the decoded text. An offset inside a character maps to the character's start.
+    pub fn to_decoded(&self, offset: usize) -> usize {
+        self.map.to_output(offset, Bias::Before)
+    }
+
+    /// Looks up the line and column in the decoded text of a byte offset of the original bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the original bytes.
+    pub fn get_original(&self, offset: usize) -> Position {
+        self.lookup.get(self.to_decoded(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn mapping_transcoded_offsets() {
+        // Shift-JIS encodes ASCII and half-width katakana in one byte, and other Japanese characters in two
+        let sjis_len = |c: char| if c.is_ascii() || ('\u{FF61}'..='\u{FF9F}').contains(&c) { 1 } else { 2 };
+        let decoded = "a日本\nｶb";
+        let lookup = TranscodedLookup::new(decoded.to_string(), sjis_len);
+        let originals = [0, 1, 3, 5, 6, 7, 8];
+        for ((index, _), original) in decoded.char_indices().chain(Some((decoded.len(), ' '))).zip(originals) {
+            assert_eq!(lookup.to_original(index), original);
+            assert_eq!(lookup.to_decoded(original), index);
+        }
+        assert_eq!(lookup.to_decoded(2), 1);
+        assert_eq!(lookup.get_original(7), (2, 4));
+    }
+
+    #[test]
+    #[cfg(feature = "encoding_rs")]
+    fn decoding_with_encoding_rs() {
+        let text = "a\u{3042}b\n\u{30ab}\u{3044}\u{3044}c";
+        for encoding in [encoding_rs::SHIFT_JIS, encoding_rs::EUC_JP, encoding_rs::ISO_2022_JP] {
+            let (bytes, _, _) = encoding.encode(text);
+            let lookup = TranscodedLookup::decode(&bytes, encoding);
+            assert_eq!(lookup.src(), text);
+            for (index, c) in text.char_indices() {
+                let (before, _, _) = encoding.encode(&text[..index]);
+                let (through, _, _) = encoding.encode(&text[..index + c.len_utf8()]);
+                // Encoders close an escape at the end, so the shared prefix is where the character's bytes start
+                let start = before.iter().zip(through.iter()).take_while(|(a, b)| a == b).count();
+                assert_eq!(lookup.to_decoded(start), index, "{} at {}", encoding.name(), index);
+            }
+        }
+        let malformed = TranscodedLookup::decode(b"a\xffb\n\xe3\x81", encoding_rs::UTF_8);
+        assert_eq!(malformed.src(), "a\u{fffd}b\n\u{fffd}");
+        assert_eq!((malformed.to_original(4), malformed.to_original(6)), (2, 4));
+        assert_eq!(malformed.get_original(4), (2, 1));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::Bias;
+use alloc::vec::Vec;
+use core::{fmt, ops::Range};
+
+/// The ways building a [`TransformMap`] from segments can fail.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SegmentError {
+    /// The segment at position `at` starts after it ends.
+    Reversed { at: usize },
+    /// The segment at position `at` starts before the one before it ends.
+    Unordered { at: usize },
+}
+
+impl fmt::Display for SegmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reversed { at } => write!(f, "segment {} starts after it ends", at),
+            Self::Unordered { at } => write!(f, "segment {} starts before the previous segment ends", at),
+        }
+    }
+}
+
+impl core::error::Error for SegmentError {}
+
+/// A record of how a text transform, such as entity decoding, comment stripping or macro substitution,
+/// turned an original text into its output, for mapping positions between the two in either direction.
+///
+/// The transform is described as segments: ranges of the original text, in increasing order,
+/// each replaced by a given number of bytes of output. Text outside the segments is copied through unchanged.
+///
+/// Positions outside the segments map exactly, as do the two ends of each non-empty segment.
+/// Positions strictly within a segment, or where a segment inserted text in front of them, map to the start or end
+/// of the corresponding segment according to a [`Bias`].
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// // Decoding "a &lt; b" into "a < b"
+/// let map = TransformMap::from_segments([(2..6, 1)]).unwrap();
+/// assert_eq!(map.to_original(4, Bias::Before), 7);
+/// assert_eq!(map.to_output(7, Bias::Before), 4);
+/// assert_eq!(map.span_to_original(2..3), 2..6);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TransformMap {
+    /// The range each segment covers in the original text and in the output.
+    segments: Vec<(Range<usize>, Range<usize>)>,
+}
+
+impl TransformMap {
+    /// Creates a map of a transform that left its input unchanged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a map from pairs of original ranges and the length of their replacements.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SegmentError`] if a range is reversed or not in increasing order.
+    pub fn from_segments<I: IntoIterator<Item = (Range<usize>, usize)>>(segments: I) -> Result<Self, SegmentError> {
+        let mut map = Self::new();
+        for (original, len) in segments {
+            map.push(original, len)?;
+        }
+        Ok(map)
+    }
+
+    /// Records that the transform replaced `original` with `len` bytes of output.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SegmentError`] if `original` is reversed or starts before the last segment pushed ends.
+    pub fn push(&mut self, original: Range<usize>, len: usize) -> Result<(), SegmentError> {
+        let at = self.segments.len();
+        if original.start > original.end {
+            return Err(SegmentError::Reversed { at });
+        }
+        let (prev_original_end, prev_output_end) = self.segments.last().map_or((0, 0), |(original, output)| (original.end, output.end));
+        if original.start < prev_original_end {
+            return Err(SegmentError::Unordered { at });
+        }
+        let start = original.start - prev_original_end + prev_output_end;
+        self.segments.push((original, start..start + len));
+        Ok(())
+    }
+
+    /// Iterates over each segment's range of the original text and the range of output it became.
+    pub fn segments(&self) -> impl Iterator<Item = (Range<usize>, Range<usize>)> + '_ {
+        self.segments.iter().cloned()
+    }
+
+    /// Returns the map of the reverse transform, from the output back to the original text.
+    pub fn inverted(&self) -> Self {
+        Self {
+            segments: self.segments.iter().map(|(original, output)| (output.clone(), original.clone())).collect(),
+        }
+    }
+
+    /// Returns the map of applying this transform and then `next` to its output, as a single transform.
+    ///
+    /// Segments of the two transforms that overlap or touch in the intermediate text merge into one segment,
+    /// so positions within or at the ends of them may map less precisely than by mapping through each transform in turn.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// // "a\tb" had its tab expanded to four spaces, and then "a" was replaced by "alpha"
+    /// let expand = TransformMap::from_segments([(1..2, 4)]).unwrap();
+    /// let rename = TransformMap::from_segments([(0..1, 5)]).unwrap();
+    /// let both = expand.compose(&rename);
+    /// assert_eq!(both.to_output(2, Bias::Before), 9);
+    /// assert_eq!(both.to_original(9, Bias::Before), 2);
+    /// ```
+    pub fn compose(&self, next: &TransformMap) -> TransformMap {
+        // The changed regions of the intermediate text, from both sides
+        let mut regions: Vec<Range<usize>> =
+            self.segments.iter().map(|(_, output)| output.clone()).chain(next.segments.iter().map(|(original, _)| original.clone())).collect();
+        regions.sort_by_key(|region| (region.start, region.end));
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(regions.len());
+        for region in regions {
+            match merged.last_mut() {
+                Some(last) if region.start <= last.end => last.end = last.end.max(region.end),
+                _ => merged.push(region),
+            }
+        }
+        let segments = merged
+            .into_iter()
+            .map(|region| {
+                let original = self.to_original(region.start, Bias::Before)..self.to_original(region.end, Bias::After);
+                let output = next.to_output(region.start, Bias::Before)..next.to_output(region.end, Bias::After);
+                (original, output)
+            })
+            .collect();
+        TransformMap { segments }
+    }
+
+    /// Maps a byte index into the original text to the output.
+    pub fn to_output(&self, index: usize, bias: Bias) -> usize {
+        map_index(&self.segments, index, bias, true)
+    }
+
+    /// Maps a byte index into the output back to the original text.
+    pub fn to_original(&self, index: usize, bias: Bias) -> usize {
+        map_index(&self.segments, index, bias, false)
+    }
+
+    /// Maps a byte range of the original text to the output, widening it to cover any segment either end fell within.
+    pub fn span_to_output(&self, span: Range<usize>) -> Range<usize> {
+        self.to_output(span.start, Bias::Before)..self.to_output(span.end, Bias::After)
+    }
+
+    /// Maps a byte range of the output back to the original text, widening it to cover any segment either end fell within.
+    pub fn span_to_original(&self, span: Range<usize>) -> Range<usize> {
+        self.to_original(span.start, Bias::Before)..self.to_original(span.end, Bias::After)
+    }
+}
+
+/// Maps `index` across `segments`, from their original ranges to their output ranges if `forward` is set, and back otherwise.
+fn map_index(segments: &[(Range<usize>, Range<usize>)], index: usize, bias: Bias, forward: bool) -> usize {
+    let sides = |(original, output): &(Range<usize>, Range<usize>)| {
+        if forward {
+            (original.clone(), output.clone())
+        } else {
+            (output.clone(), original.clone())
+        }
+    };
+    // The first segment ending at or after `index`
+    let next = segments.partition_point(|segment| sides(segment).0.end < index);
+    match segments.get(next).map(sides) {
+        Some((from, to)) if from.start <= index => {
+            if from.is_empty() || (from.start < index && index < from.end) {
+                match bias {
+                    Bias::Before => to.start,
+                    Bias::After => to.end,
+                }
+            } else if index == from.start {
+                to.start
+            } else {
+                to.end
+            }
+        }
+        _ => match next.checked_sub(1).map(|prev| sides(&segments[prev])) {
+            Some((from, to)) => index - from.end + to.end,
+            None => index,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn mapping_through_segments() {
+        // "a/*x*/b  c" with the comment stripped and "c" expanded to "ccc"
+        let map = TransformMap::from_segments([(1..6, 0), (9..10, 3)]).unwrap();
+        assert_eq!(map.segments().collect::<Vec<_>>(), [(1..6, 1..1), (9..10, 4..7)]);
+        assert_eq!(map.to_output(0, Bias::After), 0);
+        assert_eq!(map.to_output(3, Bias::Before), 1);
+        assert_eq!(map.to_output(6, Bias::Before), 1);
+        assert_eq!(map.to_output(8, Bias::Before), 3);
+        assert_eq!(map.to_output(10, Bias::Before), 7);
+        assert_eq!(map.to_original(1, Bias::Before), 1);
+        assert_eq!(map.to_original(1, Bias::After), 6);
+        assert_eq!(map.to_original(5, Bias::Before), 9);
+        assert_eq!(map.span_to_original(5..6), 9..10);
+        assert_eq!(map.inverted().to_output(5, Bias::After), 10);
+        assert_eq!(map.span_to_output(0..11), 0..8);
+    }
+
+    #[test]
+    fn rejecting_bad_segments() {
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = 3..2;
+        assert_eq!(TransformMap::from_segments([(reversed, 0)]), Err(SegmentError::Reversed { at: 0 }));
+        assert_eq!(TransformMap::from_segments([(1..4, 0), (3..5, 1)]), Err(SegmentError::Unordered { at: 1 }));
+        assert!(TransformMap::from_segments([(1..4, 0), (4..4, 2)]).is_ok());
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{Edit, InverseEdit, OwnedLineColLookup};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/// Undo and redo history for edits applied to an [`OwnedLineColLookup`].
+///
+/// Every edit made through the stack, and every undo and redo, returns the [`Edit`] it applied,
+/// so selections, diagnostics and other positions kept in an [`AnchorSet`](crate::AnchorSet)
+/// or mapped with an [`EditLog`](crate::EditLog) stay consistent as the history is walked.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let mut lookup = OwnedLineColLookup::new("a = 1".to_string());
+/// let mut history = UndoStack::new();
+/// let mut anchors = AnchorSet::new();
+/// let one = anchors.insert(4, Bias::Before);
+/// anchors.apply(&history.apply_edit(&mut lookup, 0..1, "value"));
+/// assert_eq!(anchors.get(one), Some(8));
+/// anchors.apply(&history.undo(&mut lookup).unwrap());
+/// assert_eq!((lookup.src(), anchors.get(one)), ("a = 1", Some(4)));
+/// anchors.apply(&history.redo(&mut lookup).unwrap());
+/// assert_eq!((lookup.src(), anchors.get(one)), ("value = 1", Some(8)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct UndoStack {
+    undo: Vec<InverseEdit>,
+    redo: Vec<InverseEdit>,
+}
+
+impl UndoStack {
+    /// Creates an empty history.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies an edit to `lookup`, recording how to undo it. This discards any edits that could have been redone.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replaced` is out of bounds or doesn't lie on `char` boundaries.
+    pub fn apply_edit(&mut self, lookup: &mut OwnedLineColLookup, replaced: Range<usize>, new_text: &str) -> Edit {
+        let (edit, inverse) = lookup.apply_edit_with_inverse(replaced, new_text);
+        self.undo.push(inverse);
+        self.redo.clear();
+        edit
+    }
+
+    /// Undoes the last edit applied to `lookup`, returning the edit this made, or `None` if there was nothing to undo.
+    ///
+    /// `lookup` must not have been edited other than through this stack since.
+    pub fn undo(&mut self, lookup: &mut OwnedLineColLookup) -> Option<Edit> {
+        let inverse = self.undo.pop()?;
+        let (edit, redo) = lookup.apply_edit_with_inverse(inverse.replaced, &inverse.text);
+        self.redo.push(redo);
+        Some(edit)
+    }
+
+    /// Redoes the last undone edit, returning the edit this made, or `None` if there was nothing to redo.
+    ///
+    /// `lookup` must not have been edited other than through this stack since.
+    pub fn redo(&mut self, lookup: &mut OwnedLineColLookup) -> Option<Edit> {
+        let inverse = self.redo.pop()?;
+        let (edit, undo) = lookup.apply_edit_with_inverse(inverse.replaced, &inverse.text);
+        self.undo.push(undo);
+        Some(edit)
+    }
+
+    /// Returns `true` if there is an edit to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns `true` if there is an edit to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Forgets all recorded edits.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn undoing_and_redoing() {
+        let mut lookup = OwnedLineColLookup::new_eager("ab\ncd".to_string());
+        let mut history = UndoStack::new();
+        let mut log = EditLog::new();
+        log.push(history.apply_edit(&mut lookup, 1..4, "x\ny\nz"));
+        log.push(history.apply_edit(&mut lookup, 0..0, "é"));
+        assert_eq!(lookup.src(), "éax\ny\nzd");
+        log.push(history.undo(&mut lookup).unwrap());
+        log.push(history.undo(&mut lookup).unwrap());
+        assert_eq!(lookup.src(), "ab\ncd");
+        assert!(history.undo(&mut lookup).is_none());
+        assert_eq!(lookup.get(4), (2, 2));
+        // The `d` survives the round trip
+        assert_eq!(log.map_index(4, 0, log.version(), Bias::Before), Some(4));
+        log.push(history.redo(&mut lookup).unwrap());
+        assert_eq!(lookup.src(), "ax\ny\nzd");
+        assert!(history.can_redo());
+        history.apply_edit(&mut lookup, 0..0, "!");
+        assert!(!history.can_redo() && history.can_undo());
+    }
+}
@@ -0,0 +1,174 @@
+use crate::{line_lookup::line_range_in, LineColLookup, LineLookup, Position};
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+/// A line/column lookup table over UTF-16 text, such as a buffer received from a JavaScript or Windows host,
+/// whose indices and columns count UTF-16 code units.
+///
+/// It also converts indices to and from the UTF-8 encoding of the same text, for handing positions to code working on a `str`.
+/// Unpaired surrogates are taken to be U+FFFD REPLACEMENT CHARACTER there, as [`String::from_utf16_lossy`] decodes them.
+/// The table is built eagerly.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let units: Vec<u16> = "😀 a\nb".encode_utf16().collect();
+/// let lookup = LineColLookup::from_utf16(&units);
+/// assert_eq!(lookup.get(3), (1, 4));
+/// assert_eq!(lookup.get(5), (2, 1));
+/// assert_eq!(lookup.to_utf8_index(3), 5);
+/// assert_eq!(lookup.to_utf16_index(5), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Utf16LineColLookup<'source> {
+    units: &'source [u16],
+    starts: Vec<usize>,
+    /// The index in the UTF-8 encoding of the text at which each line starts.
+    utf8_starts: Vec<usize>,
+}
+
+impl<'source> LineColLookup<'source> {
+    /// Creates a lookup table over the UTF-16 code units `units`.
+    pub fn from_utf16(units: &'source [u16]) -> Utf16LineColLookup<'source> {
+        let mut starts = vec![0];
+        let mut utf8_starts = vec![0];
+        let mut utf8_len = 0;
+        for i in 0..units.len() {
+            utf8_len += utf8_len_at(units, i);
+            if units[i] == u16::from(b'\n') {
+                starts.push(i + 1);
+                utf8_starts.push(utf8_len);
+            }
+        }
+        Utf16LineColLookup { units, starts, utf8_starts }
+    }
+}
+
+/// Returns the number of UTF-8 bytes the code unit at `i` contributes, counting a surrogate pair's four at its low surrogate
+/// so that an index between the two maps to the start of their character.
+fn utf8_len_at(units: &[u16], i: usize) -> usize {
+    let is_high = |unit: u16| (0xD800..0xDC00).contains(&unit);
+    let is_low = |unit: u16| (0xDC00..0xE000).contains(&unit);
+    match units[i] {
+        0..=0x7F => 1,
+        0x80..=0x7FF => 2,
+        unit if is_high(unit) && units.get(i + 1).copied().is_some_and(is_low) => 0,
+        unit if is_low(unit) && i > 0 && is_high(units[i - 1]) => 4,
+        _ => 3,
+    }
+}
+
+impl<'source> Utf16LineColLookup<'source> {
+    /// Gets the source code units.
+    pub fn units(&self) -> &'source [u16] {
+        self.units
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified code unit index, counting columns in code units.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn get(&self, index: usize) -> Position {
+        let (line, start) = self.line_of(index);
+        (line + 1, index - start + 1)
+    }
+
+    fn line_of(&self, index: usize) -> (usize, usize) {
+        if index > self.units.len() {
+            panic!("Index cannot be greater than the length of the input slice.");
+        }
+        let line = self.starts.partition_point(|&start| start <= index) - 1;
+        (line, self.starts[line])
+    }
+
+    /// Returns the number of lines in the source.
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+
+    /// Returns the code unit range of the 1-based `line`, excluding its terminating `'\n'`, or `None` if there is no such line.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        line_range_in(&self.starts, self.units.len(), line.checked_sub(1)?)
+    }
+
+    /// Converts a code unit index to the byte index of the same position in the UTF-8 encoding of the text.
+    ///
+    /// An index between the two halves of a surrogate pair maps to the start of their character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn to_utf8_index(&self, index: usize) -> usize {
+        let (line, start) = self.line_of(index);
+        self.utf8_starts[line] + (start..index).map(|i| utf8_len_at(self.units, i)).sum::<usize>()
+    }
+
+    /// Converts a byte index into the UTF-8 encoding of the text to the code unit index of the same position.
+    ///
+    /// An index inside the encoding of a character maps to the start of the character.
+    /// An index past the end of the encoding maps to the end of the source.
+    pub fn to_utf16_index(&self, utf8_index: usize) -> usize {
+        let line = self.utf8_starts.partition_point(|&start| start <= utf8_index) - 1;
+        let end = self.line_range(line + 1).expect("the line exists").end;
+        let mut utf8 = self.utf8_starts[line];
+        let mut index = self.starts[line];
+        // Only move past a code unit once the whole of its character has been passed
+        let mut pending = index;
+        while index < end {
+            utf8 += utf8_len_at(self.units, index);
+            index += 1;
+            if utf8 > utf8_index {
+                return pending;
+            }
+            if utf8_len_at(self.units, index - 1) > 0 {
+                pending = index;
+            }
+        }
+        // The position is at the end of the line, or past the end of the source
+        end
+    }
+}
+
+impl LineLookup for Utf16LineColLookup<'_> {
+    fn get(&self, index: usize) -> Position {
+        Utf16LineColLookup::get(self, index)
+    }
+
+    fn line_count(&self) -> usize {
+        Utf16LineColLookup::line_count(self)
+    }
+
+    fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        Utf16LineColLookup::line_range(self, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn utf16_sources() {
+        let text = "aé😀\r\n\nx";
+        let units: Vec<u16> = text.encode_utf16().collect();
+        let lookup = LineColLookup::from_utf16(&units);
+        let utf8 = LineColLookup::new(text);
+        assert_eq!(lookup.line_count(), 3);
+        for (byte, c) in text.char_indices().chain(Some((text.len(), ' '))) {
+            let index = text[..byte].encode_utf16().count();
+            assert_eq!(lookup.to_utf8_index(index), byte);
+            assert_eq!(lookup.to_utf16_index(byte), index);
+            assert_eq!(lookup.get(index), utf8.get_by_utf16(byte));
+            for inside in byte + 1..byte + c.len_utf8().min(text.len() - byte) {
+                assert_eq!(lookup.to_utf16_index(inside), index);
+            }
+        }
+        // Between the halves of the emoji's surrogate pair
+        assert_eq!(lookup.to_utf8_index(3), 3);
+        assert_eq!(lookup.line_range(1), Some(0..5));
+        assert_eq!(LineLookup::line_range(&lookup, 3), Some(7..8));
+        let lone = [0x61, 0xD800, 0x62];
+        assert_eq!(LineColLookup::from_utf16(&lone).to_utf8_index(3), 5);
+    }
+}
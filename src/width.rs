@@ -0,0 +1,96 @@
+use crate::{LineColLookup, Position};
+use unicode_width::UnicodeWidthChar;
+
+/// How many terminal cells [`LineColLookup::get_by_width`] counts an emoji as, since terminals disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum EmojiWidth {
+    /// One cell, as in terminals that draw emoji like any other symbol.
+    Narrow,
+    /// Two cells, as Unicode's East Asian Width property gives emoji presentation characters.
+    #[default]
+    Wide,
+}
+
+/// Returns `true` for characters in the blocks emoji come from.
+fn is_emoji(c: char) -> bool {
+    matches!(c, '\u{2600}'..='\u{27BF}' | '\u{2B00}'..='\u{2BFF}' | '\u{1F000}'..='\u{1FAFF}')
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Looks up the 1-based line and column numbers of the specified byte index.
+    /// The column number correlates to the number of terminal cells taken up by the line up to the specified index,
+    /// with the width of each character as the `unicode-width` crate gives it, and of each emoji as `emoji` sets it.
+    ///
+    /// Emoji joined by zero-width joiners or followed by a skin tone modifier take up the cells of one emoji,
+    /// as does a pictograph made an emoji by the `U+FE0F` variation selector. Control characters, including tabs, take up none.
+    /// An index inside a character counts that character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("日本 👍🏽!");
+    /// assert_eq!(lookup.get_by_width(7, EmojiWidth::Wide), (1, 6));
+    /// assert_eq!(lookup.get_by_width(15, EmojiWidth::Wide), (1, 8));
+    /// assert_eq!(lookup.get_by_width(15, EmojiWidth::Narrow), (1, 7));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    ///
+    /// # Notes
+    /// The line is walked up to `index`.
+    pub fn get_by_width(&self, index: usize, emoji: EmojiWidth) -> Position {
+        let emoji = match emoji {
+            EmojiWidth::Narrow => 1,
+            EmojiWidth::Wide => 2,
+        };
+        let mut previous = None;
+        self.get_by_width_with(index, |c| {
+            let width = match (previous, c) {
+                (Some('\u{200D}'), c) if is_emoji(c) => 0,
+                (Some(p), '\u{1F3FB}'..='\u{1F3FF}') if is_emoji(p) => 0,
+                (Some(p), '\u{FE0F}') if is_emoji(p) && p.width() == Some(1) => emoji - 1,
+                (_, c) if is_emoji(c) && c.width() == Some(2) => emoji,
+                (_, c) => c.width().unwrap_or(0),
+            };
+            previous = Some(c);
+            width
+        })
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index, like [`LineColLookup::get_by_width`],
+    /// but with the number of cells each character of the line takes up given by `width`, called on each in order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use line_col::*;
+    /// let lookup = LineColLookup::new("a\n→b");
+    /// assert_eq!(lookup.get_by_width_with(5, |c| if c == '→' { 2 } else { 1 }), (2, 3));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the length of the source.
+    pub fn get_by_width_with(&self, index: usize, mut width: impl FnMut(char) -> usize) -> Position {
+        let (line, line_start_index, _) = self.line_info(index);
+        let chars = self.src()[line_start_index..].char_indices().take_while(|&(start, _)| line_start_index + start < index);
+        (line + 1, chars.map(|(_, c)| width(c)).sum::<usize>() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn display_width_columns() {
+        let text = "x👨\u{200D}👩\u{200D}👦❤\u{FE0F}e\u{301}\t中\ny";
+        let lookup = LineColLookup::new(text);
+        let columns = |emoji| text.char_indices().map(|(i, _)| lookup.get_by_width(i, emoji).1).collect::<Vec<_>>();
+        assert_eq!(columns(EmojiWidth::Wide), [1, 2, 4, 4, 4, 4, 4, 5, 6, 7, 7, 7, 9, 1]);
+        assert_eq!(columns(EmojiWidth::Narrow), [1, 2, 3, 3, 3, 3, 3, 4, 4, 5, 5, 5, 7, 1]);
+        assert_eq!(lookup.get_by_width(2, EmojiWidth::Wide), (1, 4));
+        assert_eq!(lookup.get_by_width(text.len(), EmojiWidth::Wide), (2, 2));
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{LineColLookup, Position};
+use core::ops::Range;
+
+/// A view of one region of a lookup's source, with its own line head table covering only that region.
+///
+/// Created by [`LineColLookup::window`]. The window's table is built lazily like any other,
+/// so only lines inside the region that lookups reach are ever scanned.
+///
+/// # Example
+/// ```rust
+/// use line_col::*;
+/// let text = "fn a() {}\nfn b() {\n    x\n}";
+/// let lookup = LineColLookup::new(text);
+/// let body = lookup.window(17..text.len());
+/// assert_eq!(body.get(23), (2, 5));
+/// assert_eq!(body.relative_to_file(true).get(23), (3, 5));
+/// ```
+pub struct LookupWindow<'lookup, 'source, S = &'source str> {
+    lookup: &'lookup LineColLookup<'source, S>,
+    range: Range<usize>,
+    inner: LineColLookup<'lookup>,
+    /// The position of the start of the window in the file, if positions count from the start of the file.
+    file_start: Option<Position>,
+}
+
+impl<'source, S: AsRef<str>> LineColLookup<'source, S> {
+    /// Creates a view of the byte `range` of the source, whose positions count from the start of the range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or does not lie on `char` boundaries.
+    pub fn window(&self, range: Range<usize>) -> LookupWindow<'_, 'source, S> {
+        LookupWindow {
+            lookup: self,
+            inner: LineColLookup::new(&self.src()[range.clone()]),
+            range,
+            file_start: None,
+        }
+    }
+}
+
+impl<'source, S: AsRef<str>> LookupWindow<'_, 'source, S> {
+    /// Sets whether positions count from the start of the file rather than the start of the window. Defaults to `false`.
+    ///
+    /// Enabling this looks up the window's position in the file right away, so later lookups don't need the parent lookup's table.
+    pub fn relative_to_file(mut self, relative_to_file: bool) -> Self {
+        self.file_start = if relative_to_file { Some(self.lookup.get(self.range.start)) } else { None };
+        self
+    }
+
+    /// Gets the byte range of the source the window covers.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Gets a reference to the part of the source the window covers.
+    pub fn src(&self) -> &str {
+        self.inner.src()
+    }
+
+    /// Looks up the 1-based line and column numbers of the specified byte index into the whole source.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` lies outside the window.
+    pub fn get(&self, index: usize) -> Position {
+        if index < self.range.start || index > self.range.end {
+            panic!("Index must lie within the window.");
+        }
+        let (line, col) = self.inner.get(index - self.range.start);
+        let (first_line, first_col) = match self.file_start {
+            Some(start) => start,
+            None => return (line, col),
+        };
+        // Only the window's first line is offset horizontally
+        (first_line + line - 1, if line == 1 { first_col + col - 1 } else { col })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn window_matches_parent() {
+        let text = "ab\ncd\nef\ngh";
+        let lookup = LineColLookup::new(text);
+        let window = lookup.window(4..9).relative_to_file(true);
+        for i in 4..=9 {
+            assert_eq!(window.get(i), lookup.get(i));
+        }
+        assert_eq!(lookup.window(4..9).get(4), (1, 1));
+        assert_eq!(lookup.window(4..9).get(7), (2, 2));
+        assert_eq!(window.src(), "d\nef\n");
+    }
+}